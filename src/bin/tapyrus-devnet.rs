@@ -0,0 +1,216 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+extern crate clap;
+extern crate env_logger;
+extern crate log;
+extern crate tapyrus_signer;
+
+use clap::{App, Arg};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use tapyrus_signer::devnet::{Devnet, DevnetOptions, FederationPlan};
+use tapyrus_signer::mock_core::MockCoreServer;
+
+/// Failure injection which disconnects the signer from the block height until the block height.
+struct Disconnection {
+    index: usize,
+    from: u64,
+    to: u64,
+}
+
+impl FromStr for Disconnection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parts: Vec<u64> = s
+            .split(':')
+            .map(|i| i.parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .map_err(|_| format!("Invalid disconnect: {}", s))?;
+        match parts[..] {
+            [index, from, to] if from < to => Ok(Disconnection {
+                index: index as usize,
+                from,
+                to,
+            }),
+            _ => Err(format!("Invalid disconnect: {}", s)),
+        }
+    }
+}
+
+/// This command is for running the whole federation in a process on the in-memory network and the
+/// in-memory chain, so that signers can be tried without Redis and Tapyrus Core.
+fn main() {
+    let matches = App::new("tapyrus-devnet")
+        .about("Runs a federation of signers with an in-memory network and an in-memory chain for development")
+        .arg(
+            Arg::with_name("signers")
+                .long("signers")
+                .value_name("COUNT")
+                .default_value("3")
+                .help("The number of signers."),
+        )
+        .arg(
+            Arg::with_name("threshold")
+                .long("threshold")
+                .value_name("NUM")
+                .default_value("2")
+                .help("The threshold of the federation of all signers. Ignored if --federation is given."),
+        )
+        .arg(
+            Arg::with_name("federation")
+                .long("federation")
+                .value_name("HEIGHT:THRESHOLD:MEMBERS")
+                .multiple(true)
+                .number_of_values(1)
+                .help("The federation which starts at the block height, and has the members given as the indices of the signers separated by commas, like 0:2:0,1,2. The first one must start at 0."),
+        )
+        .arg(
+            Arg::with_name("duration")
+                .long("duration")
+                .value_name("SECS")
+                .default_value("5")
+                .help("Round interval times(sec)."),
+        )
+        .arg(
+            Arg::with_name("round-limit")
+                .long("round-limit")
+                .value_name("SECS")
+                .default_value("5")
+                .help("Round limit time(sec)."),
+        )
+        .arg(
+            Arg::with_name("rpcbind")
+                .long("rpcbind")
+                .value_name("ADDRESS")
+                .help("Serves the chain as Tapyrus Core JSON-RPC at the address, like tapyrus-mockd."),
+        )
+        .arg(
+            Arg::with_name("control-dir")
+                .long("control-dir")
+                .value_name("DIR")
+                .help("Serves the control endpoint of the signer i on the Unix domain socket DIR/signer-i.sock, so that tapyrus-signer-cli can operate it."),
+        )
+        .arg(
+            Arg::with_name("disconnect")
+                .long("disconnect")
+                .value_name("INDEX:FROM:TO")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Disconnects the signer from the network while the chain is from the block height until the block height."),
+        )
+        .arg(
+            Arg::with_name("blocks")
+                .long("blocks")
+                .value_name("COUNT")
+                .help("Exits after the chain gets the number of blocks."),
+        )
+        .get_matches();
+
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "tapyrus_signer=warn,tapyrus_devnet=info");
+    }
+    env_logger::init();
+
+    let number = |name: &str| -> u64 {
+        let value = matches.value_of(name).unwrap();
+        value
+            .parse()
+            .expect(&format!("--{} must be a number. value: {}", name, value))
+    };
+
+    let signer_count = number("signers") as usize;
+    let plans: Vec<FederationPlan> = match matches.values_of("federation") {
+        Some(values) => values
+            .map(|v| FederationPlan::from_str(v).expect("--federation is invalid."))
+            .collect(),
+        None => vec![FederationPlan {
+            block_height: 0,
+            threshold: number("threshold") as u8,
+            members: (0..signer_count).collect(),
+        }],
+    };
+    let disconnections: Vec<Disconnection> = matches
+        .values_of("disconnect")
+        .map(|values| {
+            values
+                .map(|v| Disconnection::from_str(v).unwrap_or_else(|e| panic!("{}", e)))
+                .collect()
+        })
+        .unwrap_or_default();
+    if disconnections.iter().any(|d| d.index >= signer_count) {
+        panic!("--disconnect has a signer out of {} signers.", signer_count);
+    }
+    let blocks = matches.value_of("blocks").map(|_| number("blocks"));
+
+    let options = DevnetOptions {
+        round_duration: number("duration"),
+        round_limit: number("round-limit"),
+        ..DevnetOptions::default()
+    };
+    let mut devnet = Devnet::new(signer_count, &plans, options).expect("Failed to create devnet.");
+    for i in 0..devnet.len() {
+        log::info!("signer {}: {}", i, devnet.signer_id(i));
+    }
+
+    if let Some(address) = matches.value_of("rpcbind") {
+        MockCoreServer::bind(address, devnet.chain().clone())
+            .expect(&format!("Failed to bind the address. address: {}", address))
+            .start();
+        log::info!("Mock Tapyrus Core is listening on {}", address);
+    }
+    if let Some(dir) = matches.value_of("control-dir") {
+        for i in 0..devnet.len() {
+            let path = Path::new(dir).join(format!("signer-{}.sock", i));
+            devnet.serve_control(i, &path).expect(&format!(
+                "Failed to bind the socket. path: {}",
+                path.display()
+            ));
+            log::info!(
+                "Control endpoint of signer {} is listening on {}",
+                i,
+                path.display()
+            );
+        }
+    }
+
+    devnet.start().expect("Failed to start signers.");
+
+    let mut height = devnet.chain().height();
+    let mut disconnected = BTreeSet::new();
+    loop {
+        let expected: BTreeSet<usize> = disconnections
+            .iter()
+            .filter(|d| d.from <= height && height < d.to)
+            .map(|d| d.index)
+            .collect();
+        for i in expected.difference(&disconnected) {
+            devnet.disconnect(*i);
+        }
+        for i in disconnected.difference(&expected) {
+            devnet.reconnect(*i);
+        }
+        disconnected = expected;
+        if blocks.map_or(false, |b| height >= b) {
+            break;
+        }
+        while !devnet.wait_for_height(height + 1, Duration::from_secs(60)) {
+            log::warn!("No block for 60 seconds at {} height.", height);
+        }
+        for h in (height + 1)..=devnet.chain().height() {
+            let block = devnet.chain().block(h).unwrap();
+            log::info!(
+                "New block. height: {}, hash: {}, xfield: {:?}",
+                h,
+                block.block_hash(),
+                block.header.xfield
+            );
+        }
+        height = devnet.chain().height();
+    }
+    devnet.stop();
+}
@@ -0,0 +1,87 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+extern crate clap;
+extern crate env_logger;
+extern crate hex;
+extern crate log;
+extern crate tapyrus;
+extern crate tapyrus_signer;
+
+use clap::{App, Arg};
+use tapyrus::blockdata::block::Block;
+use tapyrus::consensus::encode::deserialize;
+use tapyrus_signer::mock_core::{MockChain, MockCoreServer, DEFAULT_RPC_ADDRESS};
+
+/// This command is for launch the fake Tapyrus Core which keeps the chain in memory, so that
+/// tapyrus-signerd can be tested end to end without tapyrusd.
+fn main() {
+    let matches = App::new("tapyrus-mockd")
+        .about("Fake Tapyrus Core JSON-RPC server with an in-memory chain for testing tapyrus-signerd")
+        .arg(
+            Arg::with_name("genesis")
+                .long("genesis")
+                .value_name("HEX")
+                .required_unless("genesis-file")
+                .help("The genesis block as a hex string, which has the aggregated public key in the xfield."),
+        )
+        .arg(
+            Arg::with_name("genesis-file")
+                .long("genesis-file")
+                .value_name("FILE")
+                .help("The path to the file which has the genesis block as a hex string."),
+        )
+        .arg(
+            Arg::with_name("rpcbind")
+                .long("rpcbind")
+                .value_name("ADDRESS")
+                .default_value(DEFAULT_RPC_ADDRESS)
+                .help("The address which the server listens on."),
+        )
+        .arg(
+            Arg::with_name("rpcuser")
+                .long("rpcuser")
+                .value_name("USER")
+                .requires("rpcpassword")
+                .help("The user name which clients must send. Any client is accepted if omitted."),
+        )
+        .arg(
+            Arg::with_name("rpcpassword")
+                .long("rpcpassword")
+                .value_name("PASSWORD")
+                .requires("rpcuser")
+                .help("The password which clients must send."),
+        )
+        .get_matches();
+
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "tapyrus_signer=info,tapyrus_mockd=info");
+    }
+    env_logger::init();
+
+    let genesis_hex = match matches.value_of("genesis") {
+        Some(hex) => hex.to_string(),
+        None => {
+            let path = matches.value_of("genesis-file").unwrap();
+            std::fs::read_to_string(path)
+                .expect(&format!("Can't open genesis-file. path: {:?}", path))
+        }
+    };
+    let genesis: Block = hex::decode(genesis_hex.trim())
+        .ok()
+        .and_then(|bytes| deserialize(&bytes).ok())
+        .expect("genesis is invalid.");
+    let chain = MockChain::new(genesis).expect("genesis is invalid.");
+
+    let address = matches.value_of("rpcbind").unwrap();
+    let mut server = MockCoreServer::bind(address, chain)
+        .expect(&format!("Failed to bind the address. address: {}", address));
+    if let (Some(user), Some(password)) =
+        (matches.value_of("rpcuser"), matches.value_of("rpcpassword"))
+    {
+        server = server.credentials(user.to_string(), password.to_string());
+    }
+    log::info!("Mock Tapyrus Core is listening on {}", address);
+    server.start().join().unwrap();
+}
@@ -4,20 +4,39 @@ use clap::App;
 use tapyrus_signer::cli::setup::aggregate::AggregateCommand;
 use tapyrus_signer::cli::setup::compute_sig::ComputeSigCommand;
 use tapyrus_signer::cli::setup::create_block_vss::CreateBlockVssCommand;
+use tapyrus_signer::cli::setup::create_federation::CreateFederationCommand;
+use tapyrus_signer::cli::setup::create_genesis::CreateGenesisCommand;
 use tapyrus_signer::cli::setup::create_key::CreateKeyCommand;
 use tapyrus_signer::cli::setup::create_node_vss::CreateNodeVssCommand;
+use tapyrus_signer::cli::setup::dkg::DkgCommand;
+use tapyrus_signer::cli::setup::global_args;
+use tapyrus_signer::cli::setup::refresh::RefreshCommand;
 use tapyrus_signer::cli::setup::sign::SignCommand;
+use tapyrus_signer::cli::setup::sign_genesis::SignGenesisCommand;
 use tapyrus_signer::cli::setup::traits::Response;
+use tapyrus_signer::cli::setup::verify::{
+    VerifyAggregateCommand, VerifyBlockCommand, VerifyNodeVssCommand,
+};
+use tapyrus_signer::cli::OutputFormat;
 use tapyrus_signer::errors::Error;
 
 fn main() {
     let matches = App::new("Setup")
+        .args(&global_args())
         .subcommand(CreateKeyCommand::args())
         .subcommand(CreateNodeVssCommand::args())
         .subcommand(AggregateCommand::args())
+        .subcommand(CreateGenesisCommand::args())
         .subcommand(CreateBlockVssCommand::args())
         .subcommand(SignCommand::args())
         .subcommand(ComputeSigCommand::args())
+        .subcommand(SignGenesisCommand::args())
+        .subcommand(CreateFederationCommand::args())
+        .subcommand(DkgCommand::args())
+        .subcommand(RefreshCommand::args())
+        .subcommand(VerifyNodeVssCommand::args())
+        .subcommand(VerifyAggregateCommand::args())
+        .subcommand(VerifyBlockCommand::args())
         .get_matches();
     let result: Result<Box<dyn Response>, Error> = match matches.subcommand_name() {
         Some("createkey") => CreateKeyCommand::execute(
@@ -35,6 +54,11 @@ fn main() {
                 .subcommand_matches("aggregate")
                 .expect("invalid args"),
         ),
+        Some("creategenesis") => CreateGenesisCommand::execute(
+            matches
+                .subcommand_matches("creategenesis")
+                .expect("invalid args"),
+        ),
         Some("createblockvss") => CreateBlockVssCommand::execute(
             matches
                 .subcommand_matches("createblockvss")
@@ -48,11 +72,49 @@ fn main() {
                 .subcommand_matches("computesig")
                 .expect("invalid args"),
         ),
+        Some("signgenesis") => SignGenesisCommand::execute(
+            matches
+                .subcommand_matches("signgenesis")
+                .expect("invalid args"),
+        ),
+        Some("createfederation") => CreateFederationCommand::execute(
+            matches
+                .subcommand_matches("createfederation")
+                .expect("invalid args"),
+        ),
+        Some("dkg") => {
+            DkgCommand::execute(matches.subcommand_matches("dkg").expect("invalid args"))
+        }
+        Some("refresh") => {
+            RefreshCommand::execute(matches.subcommand_matches("refresh").expect("invalid args"))
+        }
+        Some("verifynodevss") => VerifyNodeVssCommand::execute(
+            matches
+                .subcommand_matches("verifynodevss")
+                .expect("invalid args"),
+        ),
+        Some("verifyaggregate") => VerifyAggregateCommand::execute(
+            matches
+                .subcommand_matches("verifyaggregate")
+                .expect("invalid args"),
+        ),
+        Some("verifyblock") => VerifyBlockCommand::execute(
+            matches
+                .subcommand_matches("verifyblock")
+                .expect("invalid args"),
+        ),
         None => return println!("No subcommand was used"),
         _ => unreachable!(),
     };
     match result {
         Ok(response) => println!("{}", response),
-        Err(e) => println!("{}", e),
+        Err(e) if OutputFormat::from_matches(&matches) == OutputFormat::Json => {
+            println!("{}", serde_json::json!({ "error": format!("{}", e) }));
+            std::process::exit(1);
+        }
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
     }
 }
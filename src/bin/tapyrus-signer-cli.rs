@@ -0,0 +1,49 @@
+extern crate tapyrus_signer;
+
+use clap::App;
+use tapyrus_signer::cli::admin::federation::{ShowFederationCommand, UpdateFederationCommand};
+use tapyrus_signer::cli::admin::global_args;
+use tapyrus_signer::cli::admin::journal::JournalCommand;
+use tapyrus_signer::cli::admin::notify::NotifyBlockCommand;
+use tapyrus_signer::cli::admin::pause::{PauseCommand, ResumeCommand};
+use tapyrus_signer::cli::admin::peers::PeersCommand;
+use tapyrus_signer::cli::admin::status::StatusCommand;
+use tapyrus_signer::cli::setup::traits::Response;
+use tapyrus_signer::errors::Error;
+
+/// This command is for operating a running tapyrus-signerd through its control endpoint.
+fn main() {
+    let matches = App::new("tapyrus-signer-cli")
+        .args(&global_args())
+        .subcommand(StatusCommand::args())
+        .subcommand(ShowFederationCommand::args())
+        .subcommand(UpdateFederationCommand::args())
+        .subcommand(PeersCommand::args())
+        .subcommand(PauseCommand::args())
+        .subcommand(ResumeCommand::args())
+        .subcommand(JournalCommand::args())
+        .subcommand(NotifyBlockCommand::args())
+        .get_matches();
+    let result: Result<Box<dyn Response>, Error> = match matches.subcommand() {
+        ("status", Some(m)) => StatusCommand::execute(m),
+        ("show-federation", Some(m)) => ShowFederationCommand::execute(m),
+        ("update-federation", Some(m)) => UpdateFederationCommand::execute(m),
+        ("peers", Some(m)) => PeersCommand::execute(m),
+        ("pause", Some(m)) => PauseCommand::execute(m),
+        ("resume", Some(m)) => ResumeCommand::execute(m),
+        ("journal", Some(m)) => JournalCommand::execute(m),
+        ("notify-block", Some(m)) => NotifyBlockCommand::execute(m),
+        _ => return println!("No subcommand was used"),
+    };
+    match result {
+        Ok(response) => println!("{}", response),
+        Err(Error::ControlRequest(code, message)) => {
+            eprintln!("The node returned an error({}): {}", code, message);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -16,12 +16,18 @@ use daemonize::Daemonize;
 use std::fs::OpenOptions;
 use std::path::Path;
 use std::sync::atomic::Ordering;
+use std::sync::mpsc::Receiver;
 use std::time::Duration;
+use tapyrus::hash_types::BlockHash;
+use tapyrus_signer::block_notify::ZmqSubscriber;
 use tapyrus_signer::command_args::{CommandArgs, RedisConfig, RpcConfig};
+use tapyrus_signer::control::{ControlRequest, ControlServer};
 use tapyrus_signer::federation::Federations;
 use tapyrus_signer::net::{ConnectionManager, RedisManager};
-use tapyrus_signer::rpc::Rpc;
+use tapyrus_signer::parameter_schedule::ParameterSchedule;
+use tapyrus_signer::rpc::FailoverRpc;
 use tapyrus_signer::signer_node::{NodeParameters, SignerNode};
+use tapyrus_signer::signing_service::UnixSocketSigningService;
 use tapyrus_signer::util::{set_stop_signal_handler, signal_to_string};
 
 /// This command is for launch tapyrus-signer-node.
@@ -48,15 +54,21 @@ fn main() {
 
     let signer_config = configs.signer_config();
 
-    let con = connect_signer_network(configs.redis_config());
-    let rpc = connect_rpc(configs.rpc_config());
+    let con = connect_signer_network(configs.redis_config(), signer_config.network_id());
+    let rpc_config = configs.rpc_config();
+    let zmq_pub_hashblock = rpc_config.zmq_pub_hashblock().map(str::to_string);
+    let rpc = connect_rpc(rpc_config);
 
+    // The secret shares are kept only in the signing service if it is configured.
     let federations = load_federations(
         &signer_config.public_key(),
         signer_config.federations_file(),
+        signer_config.signing_service().is_none(),
     );
+    let parameter_schedule =
+        load_parameter_schedule(signer_config.federations_file(), &federations);
 
-    let params = NodeParameters::new(
+    let mut params = NodeParameters::new(
         signer_config.to_address(),
         signer_config.public_key(),
         rpc,
@@ -65,8 +77,25 @@ fn main() {
         general_config.skip_waiting_ibd(),
         federations,
     );
+    params.heartbeat_interval = general_config.heartbeat_interval().unwrap();
+    params.tip_check_interval = general_config.tip_check_interval();
+    params.set_parameter_schedule(parameter_schedule);
+    params.set_block_policy(configs.block_policy());
+    if let Some(path) = signer_config.signing_service() {
+        log::info!(
+            "Local signatures are computed by the signing service at {}",
+            path
+        );
+        params.signing_service = Box::new(UnixSocketSigningService::new(path));
+    }
 
     let node = &mut SignerNode::new(con, params);
+    if let Some(path) = general_config.control_socket() {
+        node.control_handler(start_control_server(path));
+    }
+    if let Some(endpoint) = zmq_pub_hashblock {
+        node.block_notification_handler(subscribe_block_notification(&endpoint));
+    }
     node.start();
 }
 
@@ -92,24 +121,63 @@ fn daemonize(pid: &str, log_file: &str) {
     }
 }
 
-fn connect_rpc(rpc_config: RpcConfig) -> Rpc {
-    let url = format!("http://{}:{}", rpc_config.host(), rpc_config.port());
-    let user = rpc_config.user_name().map(str::to_string);
-    let pass = rpc_config.password().map(str::to_string);
-    let rpc = tapyrus_signer::rpc::Rpc::new(url.clone(), user.clone(), pass);
-    rpc.test_connection().expect(&format!(
-        "RPC connect failed. Please confirm RPC connection info. url: {}, user: '{}' ,",
-        url,
-        user.unwrap_or("".to_string())
+fn start_control_server(path: &str) -> Receiver<ControlRequest> {
+    let server = ControlServer::bind(path).expect(&format!(
+        "Failed to bind the control endpoint. path: {}",
+        path
     ));
+    log::info!("Control endpoint is listening on {}", path);
+    let (_handler, receiver) = server.start();
+    receiver
+}
+
+fn subscribe_block_notification(endpoint: &str) -> Receiver<BlockHash> {
+    let subscriber = ZmqSubscriber::new(endpoint).unwrap_or_else(|e| {
+        panic!(
+            "Failed to subscribe block notification. endpoint: {}, reason: {:?}",
+            endpoint, e
+        )
+    });
+    log::info!("Subscribe block notification from {}", endpoint);
+    let (_handler, receiver) = subscriber.start();
+    receiver
+}
+
+fn connect_rpc(rpc_config: RpcConfig) -> FailoverRpc {
+    let urls = rpc_config.endpoints();
+    let user = rpc_config.user_name().map(str::to_string);
+    let rpc = FailoverRpc::new(
+        urls.clone(),
+        rpc_config.auth(),
+        rpc_config.ca_cert().map(Path::new),
+        rpc_config.options(),
+    )
+    .unwrap_or_else(|e| panic!("Failed to create RPC client. reason={:?}", e));
+    let results = rpc.health_check();
+    for (url, result) in &results {
+        if let Err(e) = result {
+            log::warn!("RPC endpoint {} is unavailable. reason={:?}", url, e);
+        }
+    }
+    if results.iter().all(|(_, result)| result.is_err()) {
+        panic!(
+            "RPC connect failed. Please confirm RPC connection info. url: {}, user: '{}' ,",
+            urls.join(", "),
+            user.unwrap_or("".to_string())
+        );
+    }
+    log::info!("Tapyrus Core RPC endpoint: {}", rpc.active_endpoint());
     rpc
 }
 
-fn connect_signer_network(rc: RedisConfig) -> impl ConnectionManager {
-    let redis_manager = RedisManager::new(rc.host().to_string(), rc.port().to_string());
-    redis_manager
-        .test_connection()
-        .expect("Failed to connect redis. Please confirm redis connection info");
+fn connect_signer_network(rc: RedisConfig, network_id: Option<&str>) -> impl ConnectionManager {
+    let redis_manager = RedisManager::with_options(rc.options())
+        .expect("Redis connection info is invalid.")
+        .network_id(network_id.map(str::to_string));
+    redis_manager.test_connection().expect(&format!(
+        "Failed to connect redis. Please confirm redis connection info. endpoint: {}",
+        redis_manager.client.endpoint()
+    ));
     redis_manager
 }
 
@@ -136,12 +204,17 @@ fn start_unix_signal_handling() {
     });
 }
 
-fn load_federations(pubkey: &PublicKey, path: &Path) -> Federations {
+fn load_federations(pubkey: &PublicKey, path: &Path, with_secret_shares: bool) -> Federations {
     let federations_toml = std::fs::read_to_string(path).expect(&format!(
         "Can't open federations_file. path: {:?} Error",
         path
     ));
-    match Federations::from_pubkey_and_toml(pubkey, &federations_toml) {
+    let federations = if with_secret_shares {
+        Federations::from_pubkey_and_toml(pubkey, &federations_toml)
+    } else {
+        Federations::from_pubkey_and_toml_without_secret_shares(pubkey, &federations_toml)
+    };
+    match federations {
         Ok(r) => r,
         Err(tapyrus_signer::errors::Error::InvalidTomlFormat(e)) => {
             panic!("federations_file: Invalid TOML format. {}", e);
@@ -161,6 +234,25 @@ fn load_federations(pubkey: &PublicKey, path: &Path) -> Federations {
     }
 }
 
+fn load_parameter_schedule(path: &Path, federations: &Federations) -> ParameterSchedule {
+    let federations_toml = std::fs::read_to_string(path).expect(&format!(
+        "Can't open federations_file. path: {:?} Error",
+        path
+    ));
+    match ParameterSchedule::from_toml(&federations_toml, federations) {
+        Ok(r) => r,
+        Err(tapyrus_signer::errors::Error::InvalidParameterChange(Some(height), m)) => {
+            panic!(
+                "federations_file: Invalid parameter change at {} height. message: {}",
+                height, m
+            );
+        }
+        Err(e) => {
+            panic!("federations_file: {}", e);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{connect_rpc, connect_signer_network, load_federations};
@@ -181,6 +273,13 @@ mod tests {
                 port: Some("9999"),
                 username: None,
                 password: None,
+                endpoints: None,
+                timeout: None,
+                retries: None,
+                deadline: None,
+                cookie_file: None,
+                ca_cert: None,
+                zmq_pub_hashblock: None,
             },
             toml_config: None,
         };
@@ -212,7 +311,7 @@ mod tests {
         .unwrap();
 
         let path = Path::new("tests/resources/federations.toml");
-        let federations = load_federations(&pubkey, path);
+        let federations = load_federations(&pubkey, path, true);
 
         assert_eq!(federations.len(), 2);
     }
@@ -226,7 +325,7 @@ mod tests {
         .unwrap();
 
         let path = Path::new("/foo/bar/no_exist_file.toml");
-        load_federations(&pubkey, path);
+        load_federations(&pubkey, path, true);
     }
 
     #[test]
@@ -240,7 +339,7 @@ mod tests {
         .unwrap();
 
         let path = Path::new("tests/resources/federations_has_invalid_federation.toml");
-        load_federations(&pubkey, path);
+        load_federations(&pubkey, path, true);
     }
 
     #[test]
@@ -254,6 +353,6 @@ mod tests {
         .unwrap();
 
         let path = Path::new("tests/resources/federations_invalid_toml_format.toml");
-        load_federations(&pubkey, path);
+        load_federations(&pubkey, path, true);
     }
 }
@@ -0,0 +1,84 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+extern crate clap;
+extern crate env_logger;
+extern crate log;
+extern crate tapyrus;
+extern crate tapyrus_signer;
+
+use clap::{App, Arg};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::str::FromStr;
+use tapyrus::PublicKey;
+use tapyrus_signer::federation::Federations;
+use tapyrus_signer::signing_service::SigningServer;
+
+/// This command is for launch the signing service which computes local signatures for
+/// tapyrus-signerd with the node secret share.
+fn main() {
+    let matches = App::new("tapyrus-signing-service")
+        .about("Signing service which holds the node secret share for tapyrus-signerd")
+        .arg(
+            Arg::with_name("socket")
+                .long("socket")
+                .value_name("SOCKET_PATH")
+                .required(true)
+                .help("The path to Unix domain socket which the service listens on."),
+        )
+        .arg(
+            Arg::with_name("public-key")
+                .short("p")
+                .long("public-key")
+                .value_name("PUBLIC_KEY")
+                .required(true)
+                .help("Public key of the signer."),
+        )
+        .arg(
+            Arg::with_name("federations-file")
+                .long("federations-file")
+                .value_name("FILE")
+                .required(true)
+                .help("The path to TOML file of the federations of the chain."),
+        )
+        .arg(
+            Arg::with_name("signed-blocks-file")
+                .long("signed-blocks-file")
+                .value_name("FILE")
+                .required(true)
+                .help("The path to the file which records the signed blocks. The service never signs with the same block VSS twice."),
+        )
+        .get_matches();
+
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            "tapyrus_signer=info,tapyrus_signing_service=info",
+        );
+    }
+    env_logger::init();
+
+    let public_key = PublicKey::from_str(matches.value_of("public-key").unwrap())
+        .expect("public-key is invalid.");
+    let path = matches.value_of("federations-file").unwrap();
+    let federations_toml = std::fs::read_to_string(path).expect(&format!(
+        "Can't open federations_file. path: {:?} Error",
+        path
+    ));
+    let federations = Federations::from_pubkey_and_toml(&public_key, &federations_toml)
+        .expect("federations_file: Invalid.");
+
+    let socket = matches.value_of("socket").unwrap();
+    let listener =
+        UnixListener::bind(socket).expect(&format!("Failed to bind the socket. path: {}", socket));
+    log::info!("Signing service is listening on {}", socket);
+    let signed_blocks_file = matches.value_of("signed-blocks-file").unwrap();
+    let mut server =
+        SigningServer::new(federations, Path::new(signed_blocks_file)).expect(&format!(
+            "Can't load signed_blocks_file. path: {:?} Error",
+            signed_blocks_file
+        ));
+    server.serve(listener);
+}
@@ -0,0 +1,294 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Subscriber of block notifications from Tapyrus Core.
+//!
+//! Tapyrus Core publishes the hash of every new tip block over ZeroMQ when it runs with
+//! `-zmqpubhashblock=tcp://<host>:<port>`. The subscriber speaks the ZMTP 3.0 protocol with the
+//! NULL security mechanism by itself, which is all the feed needs, and passes the hashes to the
+//! node through a channel. It reconnects after Tapyrus Core restarts, and also when nothing is
+//! received for `IDLE_TIMEOUT_SECS`, because a connection whose peer is gone without closing it
+//! would otherwise block forever.
+
+use crate::errors::Error;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tapyrus::hash_types::BlockHash;
+use tapyrus::hashes::hex::FromHex;
+
+/// Wait before reconnecting to Tapyrus Core.
+pub const RECONNECT_INTERVAL_SECS: u64 = 5;
+/// Reconnect if no message is received in this period.
+pub const IDLE_TIMEOUT_SECS: u64 = 600;
+/// The topic of the block hash feed.
+const HASHBLOCK_TOPIC: &[u8] = b"hashblock";
+/// Max size of a frame. Messages of the feed are much smaller.
+const MAX_FRAME_SIZE: u64 = 1024 * 1024;
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+pub struct ZmqSubscriber {
+    address: String,
+    idle_timeout: Duration,
+}
+
+impl ZmqSubscriber {
+    /// Creates the subscriber for the endpoint like `tcp://127.0.0.1:28332`.
+    pub fn new(endpoint: &str) -> Result<Self, Error> {
+        if !endpoint.starts_with("tcp://") {
+            return Err(Error::InvalidArgs(format!(
+                "ZMQ endpoint must start with tcp://: {}",
+                endpoint
+            )));
+        }
+        Ok(ZmqSubscriber {
+            address: endpoint["tcp://".len()..].to_string(),
+            idle_timeout: Duration::from_secs(IDLE_TIMEOUT_SECS),
+        })
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Start subscribing. Returns the receiver which the node should poll new tips from.
+    pub fn start(self) -> (JoinHandle<()>, Receiver<BlockHash>) {
+        let (sender, receiver) = channel();
+        let handler = std::thread::Builder::new()
+            .name("ZmqSubscriberThread".to_string())
+            .spawn(move || loop {
+                match self.subscribe(&sender) {
+                    Ok(()) => return,
+                    Err(e) => log::warn!(
+                        "Block notification from {} is disconnected. Reconnect after {} sec. reason={:?}",
+                        self.address,
+                        RECONNECT_INTERVAL_SECS,
+                        e
+                    ),
+                }
+                std::thread::sleep(Duration::from_secs(RECONNECT_INTERVAL_SECS));
+            })
+            .unwrap();
+        (handler, receiver)
+    }
+
+    /// Receives block hashes until the connection fails or is idle for the idle timeout. Returns
+    /// Ok if the node is gone.
+    fn subscribe(&self, sender: &Sender<BlockHash>) -> Result<(), Error> {
+        let mut stream = TcpStream::connect(&self.address)?;
+        stream.set_read_timeout(Some(self.idle_timeout))?;
+        handshake(&mut stream, b"SUB")?;
+
+        // A subscription is a message which starts with 0x01 followed by the topic in ZMTP 3.0.
+        let mut subscription = vec![0x01];
+        subscription.extend_from_slice(HASHBLOCK_TOPIC);
+        write_frame(&mut stream, 0, &subscription)?;
+        log::info!("Subscribed block notification from {}", self.address);
+
+        loop {
+            let message = read_message(&mut stream)?;
+            if message.len() < 2 || message[0] != HASHBLOCK_TOPIC {
+                continue;
+            }
+            let hash = match BlockHash::from_hex(&hex::encode(&message[1])) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    log::warn!("Invalid block notification. reason={:?}", e);
+                    continue;
+                }
+            };
+            log::debug!("Got block notification {}", hash);
+            if sender.send(hash).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Exchanges greetings and READY commands with the peer.
+fn handshake<S: Read + Write>(stream: &mut S, socket_type: &[u8]) -> Result<(), Error> {
+    let mut greeting = [0u8; 64];
+    greeting[0] = 0xff;
+    greeting[9] = 0x7f;
+    greeting[10] = 3;
+    greeting[12..16].copy_from_slice(b"NULL");
+    stream.write_all(&greeting)?;
+
+    let mut peer = [0u8; 64];
+    stream.read_exact(&mut peer)?;
+    if peer[0] != 0xff || peer[9] != 0x7f || peer[10] < 3 || &peer[12..16] != b"NULL" {
+        return Err(Error::Zmq("Unsupported greeting.".to_string()));
+    }
+
+    let mut ready = vec![5];
+    ready.extend_from_slice(b"READY");
+    ready.push(11);
+    ready.extend_from_slice(b"Socket-Type");
+    ready.extend_from_slice(&(socket_type.len() as u32).to_be_bytes());
+    ready.extend_from_slice(socket_type);
+    write_frame(stream, FLAG_COMMAND, &ready)?;
+
+    let (flags, body) = read_frame(stream)?;
+    if flags & FLAG_COMMAND == 0 || !body.starts_with(b"\x05READY") {
+        return Err(Error::Zmq("The peer is not ready.".to_string()));
+    }
+    Ok(())
+}
+
+fn write_frame<W: Write>(writer: &mut W, flags: u8, body: &[u8]) -> Result<(), Error> {
+    if body.len() > 255 {
+        writer.write_all(&[flags | FLAG_LONG])?;
+        writer.write_all(&(body.len() as u64).to_be_bytes())?;
+    } else {
+        writer.write_all(&[flags, body.len() as u8])?;
+    }
+    writer.write_all(body)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a frame and returns the flags and the body.
+fn read_frame<R: Read>(reader: &mut R) -> Result<(u8, Vec<u8>), Error> {
+    let mut flags = [0u8; 1];
+    reader.read_exact(&mut flags)?;
+    let size = if flags[0] & FLAG_LONG == 0 {
+        let mut size = [0u8; 1];
+        reader.read_exact(&mut size)?;
+        size[0] as u64
+    } else {
+        let mut size = [0u8; 8];
+        reader.read_exact(&mut size)?;
+        u64::from_be_bytes(size)
+    };
+    if size > MAX_FRAME_SIZE {
+        return Err(Error::Zmq(format!("Too large frame: {} bytes", size)));
+    }
+    let mut body = vec![0u8; size as usize];
+    reader.read_exact(&mut body)?;
+    Ok((flags[0], body))
+}
+
+/// Reads frames of a message. Commands between messages are skipped.
+fn read_message<R: Read>(reader: &mut R) -> Result<Vec<Vec<u8>>, Error> {
+    let mut frames = vec![];
+    loop {
+        let (flags, body) = read_frame(reader)?;
+        if flags & FLAG_COMMAND != 0 {
+            continue;
+        }
+        frames.push(body);
+        if flags & FLAG_MORE == 0 {
+            return Ok(frames);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Stand-in for the ZMQ publisher of Tapyrus Core. It publishes the messages to a subscriber.
+    fn publish(messages: Vec<Vec<Vec<u8>>>) -> (String, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            handshake(&mut stream, b"PUB").unwrap();
+
+            let (flags, body) = read_frame(&mut stream).unwrap();
+            assert_eq!(flags, 0);
+            assert_eq!(body, b"\x01hashblock".to_vec());
+
+            for message in messages {
+                let last = message.len() - 1;
+                for (i, frame) in message.iter().enumerate() {
+                    let flags = if i == last { 0 } else { FLAG_MORE };
+                    write_frame(&mut stream, flags, frame).unwrap();
+                }
+            }
+        });
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            ZmqSubscriber::new("tcp://127.0.0.1:28332").unwrap().address,
+            "127.0.0.1:28332"
+        );
+        assert!(ZmqSubscriber::new("127.0.0.1:28332").is_err());
+    }
+
+    #[test]
+    fn test_frame() {
+        let mut buf = vec![];
+        write_frame(&mut buf, FLAG_MORE, b"hashblock").unwrap();
+        write_frame(&mut buf, FLAG_COMMAND, b"\x04PING").unwrap();
+        write_frame(&mut buf, 0, &[1u8; 300]).unwrap();
+        assert_eq!(buf[0], FLAG_MORE);
+        assert_eq!(buf[1], 9);
+
+        let message = read_message(&mut &buf[..]).unwrap();
+        assert_eq!(message, vec![b"hashblock".to_vec(), vec![1u8; 300]]);
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let hash = "7303687fb5d80781bd9fece466e76d97a94613d409d127030ff7f34081a899f7";
+        let sequence = 1u32.to_le_bytes().to_vec();
+        let (endpoint, publisher) = publish(vec![
+            vec![
+                b"hashtx".to_vec(),
+                hex::decode("00").unwrap(),
+                sequence.clone(),
+            ],
+            vec![
+                b"hashblock".to_vec(),
+                hex::decode(hash).unwrap(),
+                sequence.clone(),
+            ],
+        ]);
+
+        let (_handler, receiver) = ZmqSubscriber::new(&endpoint).unwrap().start();
+        let received = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(received, BlockHash::from_hex(hash).unwrap());
+        publisher.join().unwrap();
+    }
+
+    #[test]
+    fn test_subscribe_idle_timeout() {
+        // The publisher keeps the connection open but sends nothing, like a peer which is gone
+        // without closing the connection.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("tcp://{}", listener.local_addr().unwrap());
+        let (done_sender, done_receiver) = channel::<()>();
+        let publisher = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            handshake(&mut stream, b"PUB").unwrap();
+            read_frame(&mut stream).unwrap();
+            done_receiver.recv().unwrap();
+        });
+
+        let subscriber = ZmqSubscriber::new(&endpoint)
+            .unwrap()
+            .with_idle_timeout(Duration::from_millis(100));
+        let (sender, _receiver) = channel();
+        match subscriber.subscribe(&sender) {
+            Err(Error::ConfigFileIOError(e)) => assert!(
+                e.kind() == std::io::ErrorKind::WouldBlock
+                    || e.kind() == std::io::ErrorKind::TimedOut
+            ),
+            r => panic!("unexpected result {:?}", r),
+        }
+        done_sender.send(()).unwrap();
+        publisher.join().unwrap();
+    }
+}
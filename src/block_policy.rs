@@ -0,0 +1,394 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use crate::errors::Error;
+use crate::rpc::TapyrusApi;
+use tapyrus::blockdata::block::Block;
+use tapyrus::consensus::encode::serialize;
+use tapyrus::Address;
+
+/// Local rules which members apply to the candidate block in addition to the consensus rules.
+/// It is loaded from the `[policy]` section of the signer config file, and every rule is disabled
+/// unless it is specified.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockPolicy {
+    /// Max number of transactions in the block, including the coinbase transaction.
+    pub max_tx_count: Option<usize>,
+    /// Max serialized size(bytes) of the block.
+    pub max_block_size: Option<usize>,
+    /// Min time(sec) from the previous block.
+    pub min_block_interval: Option<u32>,
+    /// Max time(sec) to wait for transactions from the previous block. A block without any
+    /// transaction except the coinbase is produced only after this time.
+    pub max_block_interval: Option<u32>,
+    /// Max difference(sec) between the block time and the local clock.
+    pub max_time_skew: Option<u32>,
+    /// Addresses which the coinbase transaction can pay to. Any address is allowed if it is empty.
+    pub coinbase_addresses: Vec<Address>,
+}
+
+impl BlockPolicy {
+    /// Checks the candidate block at the block height. `now` is the current unix time(sec).
+    /// It returns `Error::PolicyRejection` with the reason if the block violates any rule, and the
+    /// RPC error as it is if the previous block header can't be got from Tapyrus Core.
+    pub fn check<T: TapyrusApi>(
+        &self,
+        block: &Block,
+        block_height: u64,
+        rpc: &T,
+        now: u64,
+    ) -> Result<(), Error> {
+        if let Some(reason) = self.interval_violation(block, block_height, rpc)? {
+            return Err(Error::PolicyRejection(reason));
+        }
+        self.check_contents(block, now)
+    }
+
+    /// Checks the rules of the block except min-block-interval and max-block-interval.
+    pub fn check_contents(&self, block: &Block, now: u64) -> Result<(), Error> {
+        if let Some(max) = self.max_tx_count {
+            if block.txdata.len() > max {
+                return Err(Error::PolicyRejection(format!(
+                    "The block has {} transactions, which exceeds max-tx-count {}.",
+                    block.txdata.len(),
+                    max
+                )));
+            }
+        }
+
+        if let Some(max) = self.max_block_size {
+            let size = serialize(block).len();
+            if size > max {
+                return Err(Error::PolicyRejection(format!(
+                    "The block size {} exceeds max-block-size {}.",
+                    size, max
+                )));
+            }
+        }
+
+        if let Some(skew) = self.max_time_skew {
+            let time = block.header.time as u64;
+            if time > now + skew as u64 || time + (skew as u64) < now {
+                return Err(Error::PolicyRejection(format!(
+                    "The block time {} is out of max-time-skew {} from the local time {}.",
+                    time, skew, now
+                )));
+            }
+        }
+
+        if !self.coinbase_addresses.is_empty() {
+            let coinbase = block
+                .txdata
+                .first()
+                .ok_or_else(|| Error::PolicyRejection("The block has no coinbase.".to_string()))?;
+            for output in coinbase.output.iter().filter(|o| o.value > 0) {
+                if !self
+                    .coinbase_addresses
+                    .iter()
+                    .any(|a| a.script_pubkey() == output.script_pubkey)
+                {
+                    return Err(Error::PolicyRejection(format!(
+                        "The coinbase pays to {:?}, which is not in coinbase-addresses.",
+                        output.script_pubkey
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the reason why the block violates min-block-interval or max-block-interval, or
+    /// None if it doesn't. The intervals are measured with the block time, so that the round
+    /// master and the members decide on the same time base regardless of their local clocks.
+    pub fn interval_violation<T: TapyrusApi>(
+        &self,
+        block: &Block,
+        block_height: u64,
+        rpc: &T,
+    ) -> Result<Option<String>, Error> {
+        if block_height == 0
+            || (self.min_block_interval.is_none() && self.max_block_interval.is_none())
+        {
+            return Ok(None);
+        }
+
+        let prev = rpc.getblockheader(block_height - 1)?;
+        if let Some(interval) = self.min_block_interval {
+            if block.header.time < prev.time.saturating_add(interval) {
+                return Ok(Some(format!(
+                    "The block time {} is less than min-block-interval {} from the previous block time {}.",
+                    block.header.time, interval, prev.time
+                )));
+            }
+        }
+        if let Some(interval) = self.max_block_interval {
+            if block.txdata.len() <= 1 && block.header.time < prev.time.saturating_add(interval) {
+                return Ok(Some(format!(
+                    "The block has no transactions and the block time {} is less than max-block-interval {} from the previous block time {}.",
+                    block.header.time, interval, prev.time
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the reason why the round master should wait before requesting the candidate block
+    /// at the block height, or None if it should request it now. It is estimated with the local
+    /// clock and the mempool of Tapyrus Core to avoid requesting blocks which would be too early.
+    /// The master decides with `interval_violation` on the candidate block in the end, as the
+    /// members do. `now` is the current unix time(sec).
+    pub fn production_wait<T: TapyrusApi>(
+        &self,
+        block_height: u64,
+        rpc: &T,
+        now: u64,
+    ) -> Result<Option<String>, Error> {
+        if block_height == 0
+            || (self.min_block_interval.is_none() && self.max_block_interval.is_none())
+        {
+            return Ok(None);
+        }
+
+        let prev_time = rpc.getblockheader(block_height - 1)?.time as u64;
+        if let Some(interval) = self.min_block_interval {
+            if now < prev_time + interval as u64 {
+                return Ok(Some(format!(
+                    "min-block-interval {} has not passed since the previous block.",
+                    interval
+                )));
+            }
+        }
+        if let Some(interval) = self.max_block_interval {
+            if now < prev_time + interval as u64 && rpc.getmempoolinfo()?.size == 0 {
+                return Ok(Some(format!(
+                    "No transactions in the mempool until max-block-interval {} passes since the previous block.",
+                    interval
+                )));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::GetMempoolInfoResult;
+    use crate::tests::helper::address;
+    use crate::tests::helper::blocks::get_block;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use crate::tests::helper::rpc::MockRpc;
+
+    fn assert_rejected(result: Result<(), Error>) {
+        match result {
+            Err(Error::PolicyRejection(_)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_default_policy() {
+        let block = get_block(0);
+        let rpc = MockRpc::new();
+        assert!(BlockPolicy::default().check(&block, 10, &rpc, 0).is_ok());
+    }
+
+    #[test]
+    fn test_max_tx_count_and_size() {
+        let block = get_block(0);
+        let rpc = MockRpc::new();
+        let now = block.header.time as u64;
+
+        let policy = BlockPolicy {
+            max_tx_count: Some(block.txdata.len()),
+            max_block_size: Some(serialize(&block).len()),
+            ..Default::default()
+        };
+        assert!(policy.check(&block, 10, &rpc, now).is_ok());
+
+        let policy = BlockPolicy {
+            max_tx_count: Some(block.txdata.len() - 1),
+            ..Default::default()
+        };
+        assert_rejected(policy.check(&block, 10, &rpc, now));
+
+        let policy = BlockPolicy {
+            max_block_size: Some(serialize(&block).len() - 1),
+            ..Default::default()
+        };
+        assert_rejected(policy.check(&block, 10, &rpc, now));
+    }
+
+    #[test]
+    fn test_max_time_skew() {
+        let block = get_block(0);
+        let rpc = MockRpc::new();
+        let time = block.header.time as u64;
+        let policy = BlockPolicy {
+            max_time_skew: Some(60),
+            ..Default::default()
+        };
+        assert!(policy.check(&block, 10, &rpc, time + 60).is_ok());
+        assert!(policy.check(&block, 10, &rpc, time - 60).is_ok());
+        assert_rejected(policy.check(&block, 10, &rpc, time + 61));
+        assert_rejected(policy.check(&block, 10, &rpc, time - 61));
+    }
+
+    #[test]
+    fn test_min_block_interval() {
+        let block = get_block(0);
+        let now = block.header.time as u64;
+        let policy = BlockPolicy {
+            min_block_interval: Some(30),
+            ..Default::default()
+        };
+
+        let mut rpc = MockRpc::new();
+        let mut prev = block.header.clone();
+        prev.time = block.header.time - 30;
+        rpc.should_call_getblockheader(Ok(prev.clone()));
+        prev.time = block.header.time - 29;
+        rpc.should_call_getblockheader(Ok(prev));
+
+        assert!(policy.check(&block, 10, &rpc, now).is_ok());
+        assert_rejected(policy.check(&block, 10, &rpc, now));
+        rpc.assert();
+    }
+
+    #[test]
+    fn test_interval_violation_uses_block_time() {
+        let block = get_block(0);
+        let policy = BlockPolicy {
+            min_block_interval: Some(30),
+            ..Default::default()
+        };
+
+        let mut rpc = MockRpc::new();
+        let mut prev = block.header.clone();
+        prev.time = block.header.time - 29;
+        rpc.should_call_getblockheader(Ok(prev.clone()));
+        rpc.should_call_getblockheader(Ok(prev));
+
+        // The local time doesn't matter.
+        assert!(policy
+            .interval_violation(&block, 10, &rpc)
+            .unwrap()
+            .is_some());
+        let far_future = block.header.time as u64 + 3600;
+        assert_rejected(policy.check(&block, 10, &rpc, far_future));
+        rpc.assert();
+    }
+
+    #[test]
+    fn test_rpc_error_is_not_rejection() {
+        let block = get_block(0);
+        let now = block.header.time as u64;
+        let policy = BlockPolicy {
+            min_block_interval: Some(30),
+            ..Default::default()
+        };
+
+        let mut rpc = MockRpc::new();
+        rpc.should_call_getblockheader(Err(Error::JsonRpc(jsonrpc::error::Error::NonceMismatch)));
+        match policy.check(&block, 10, &rpc, now) {
+            Err(Error::JsonRpc(_)) => assert!(true),
+            r => panic!("unexpected result {:?}", r),
+        }
+        rpc.assert();
+    }
+
+    #[test]
+    fn test_max_block_interval() {
+        let mut block = get_block(0);
+        block.txdata.truncate(1);
+        let now = block.header.time as u64;
+        let policy = BlockPolicy {
+            max_block_interval: Some(600),
+            ..Default::default()
+        };
+
+        let mut rpc = MockRpc::new();
+        let mut prev = block.header.clone();
+        prev.time = block.header.time - 600;
+        rpc.should_call_getblockheader(Ok(prev.clone()));
+        prev.time = block.header.time - 599;
+        rpc.should_call_getblockheader(Ok(prev));
+
+        assert!(policy.check(&block, 10, &rpc, now).is_ok());
+        assert_rejected(policy.check(&block, 10, &rpc, now));
+        rpc.assert();
+    }
+
+    #[test]
+    fn test_production_wait() {
+        let header = get_block(0).header;
+        let now = header.time as u64;
+        let mempool = |size| GetMempoolInfoResult {
+            size,
+            bytes: size * 250,
+        };
+        let prev = |elapsed: u32| {
+            let mut prev = header.clone();
+            prev.time = header.time - elapsed;
+            Ok(prev)
+        };
+
+        // No rules, no RPC calls.
+        let rpc = MockRpc::new();
+        assert_eq!(
+            BlockPolicy::default()
+                .production_wait(10, &rpc, now)
+                .unwrap(),
+            None
+        );
+
+        let policy = BlockPolicy {
+            min_block_interval: Some(10),
+            max_block_interval: Some(600),
+            ..Default::default()
+        };
+        let mut rpc = MockRpc::new();
+        // Too early even if there are transactions.
+        rpc.should_call_getblockheader(prev(9));
+        // Waiting for transactions.
+        rpc.should_call_getblockheader(prev(10));
+        rpc.should_call_getmempoolinfo(Ok(mempool(0)));
+        // There are transactions.
+        rpc.should_call_getblockheader(prev(10));
+        rpc.should_call_getmempoolinfo(Ok(mempool(2)));
+        // The max interval passed.
+        rpc.should_call_getblockheader(prev(600));
+
+        assert!(policy.production_wait(10, &rpc, now).unwrap().is_some());
+        assert!(policy.production_wait(10, &rpc, now).unwrap().is_some());
+        assert_eq!(policy.production_wait(10, &rpc, now).unwrap(), None);
+        assert_eq!(policy.production_wait(10, &rpc, now).unwrap(), None);
+        rpc.assert();
+    }
+
+    #[test]
+    fn test_coinbase_addresses() {
+        let block = get_block(0);
+        let rpc = MockRpc::new();
+        let now = block.header.time as u64;
+        let payee = Address::from_script(
+            &block.txdata[0].output[0].script_pubkey,
+            TEST_KEYS.key[0].network,
+        )
+        .unwrap();
+
+        let policy = BlockPolicy {
+            coinbase_addresses: vec![address(&TEST_KEYS.key[0]), payee],
+            ..Default::default()
+        };
+        assert!(policy.check(&block, 10, &rpc, now).is_ok());
+
+        let policy = BlockPolicy {
+            coinbase_addresses: vec![address(&TEST_KEYS.key[0])],
+            ..Default::default()
+        };
+        assert_rejected(policy.check(&block, 10, &rpc, now));
+    }
+}
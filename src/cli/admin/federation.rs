@@ -0,0 +1,132 @@
+use crate::cli::admin::{request, write_json, OutputFormat};
+use crate::cli::setup::traits::Response;
+use crate::errors::Error;
+use crate::signer_node::status::FederationStatus;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fmt;
+
+pub fn write_federation(f: &mut fmt::Formatter<'_>, federation: &FederationStatus) -> fmt::Result {
+    writeln!(f, "  block height: {}", federation.block_height)?;
+    writeln!(f, "    member: {}", federation.is_member)?;
+    match federation.threshold {
+        Some(threshold) => writeln!(f, "    threshold: {}", threshold)?,
+        None => writeln!(f, "    threshold: -")?,
+    }
+    writeln!(
+        f,
+        "    aggregated public key: {}",
+        federation.aggregated_public_key
+    )?;
+    for signer in &federation.signers {
+        writeln!(f, "    signer: {}", signer)?;
+    }
+    Ok(())
+}
+
+pub struct FederationsResponse {
+    federations: Vec<FederationStatus>,
+    format: OutputFormat,
+}
+
+impl Response for FederationsResponse {}
+
+impl fmt::Display for FederationsResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(f, &self.federations);
+        }
+
+        writeln!(f, "federations:")?;
+        for federation in &self.federations {
+            write_federation(f, federation)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct ShowFederationCommand {}
+
+impl<'a> ShowFederationCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let federations: Vec<FederationStatus> = request(matches, "GET", "/federations", None)?;
+        Ok(Box::new(FederationsResponse {
+            federations,
+            format: OutputFormat::from_matches(matches),
+        }))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("show-federation")
+            .about("show the federations which the running signer node has")
+    }
+}
+
+pub struct UpdateFederationCommand {}
+
+impl<'a> UpdateFederationCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let file = matches
+            .value_of("file")
+            .ok_or(Error::InvalidArgs("file".to_string()))?;
+        let toml = std::fs::read_to_string(file)?;
+        let federations: Vec<FederationStatus> =
+            request(matches, "PUT", "/federations", Some(&toml))?;
+        Ok(Box::new(FederationsResponse {
+            federations,
+            format: OutputFormat::from_matches(matches),
+        }))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("update-federation")
+            .about("replace the federations of the running signer node. The federation for the current block height can not be changed. The federations file in the signer config should be updated as well, because it is loaded again when the node restarts.")
+            .arg(
+                Arg::with_name("file")
+                    .required(true)
+                    .takes_value(true)
+                    .help("path to the federations toml file"),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let response = FederationsResponse {
+            federations: vec![
+                FederationStatus {
+                    block_height: 0,
+                    is_member: true,
+                    threshold: Some(2),
+                    signers: vec!["02aa".to_string(), "03bb".to_string()],
+                    aggregated_public_key: "03cc".to_string(),
+                },
+                FederationStatus {
+                    block_height: 100,
+                    is_member: false,
+                    threshold: None,
+                    signers: vec![],
+                    aggregated_public_key: "03dd".to_string(),
+                },
+            ],
+            format: OutputFormat::Human,
+        };
+        assert_eq!(
+            format!("{}", response),
+            "federations:\n\
+             \x20 block height: 0\n\
+             \x20   member: true\n\
+             \x20   threshold: 2\n\
+             \x20   aggregated public key: 03cc\n\
+             \x20   signer: 02aa\n\
+             \x20   signer: 03bb\n\
+             \x20 block height: 100\n\
+             \x20   member: false\n\
+             \x20   threshold: -\n\
+             \x20   aggregated public key: 03dd\n"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::cli::admin::{request, write_json, OutputFormat};
+use crate::cli::setup::traits::Response;
+use crate::errors::Error;
+use crate::signer_node::journal::RoundRecord;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fmt;
+
+pub struct JournalResponse {
+    record: RoundRecord,
+    format: OutputFormat,
+}
+
+impl Response for JournalResponse {}
+
+impl fmt::Display for JournalResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(f, &self.record);
+        }
+
+        write!(f, "block height: {}", self.record.block_height)?;
+        for entry in &self.record.entries {
+            write!(f, "\n{} {}", entry.time, entry.event)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct JournalCommand {}
+
+impl<'a> JournalCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let path = match matches.value_of("height") {
+            Some(height) => {
+                let height: u64 = height
+                    .parse()
+                    .map_err(|_| Error::InvalidArgs("height".to_string()))?;
+                format!("/journal?height={}", height)
+            }
+            None => "/journal".to_string(),
+        };
+        let record: RoundRecord = request(matches, "GET", &path, None)?;
+        Ok(Box::new(JournalResponse {
+            record,
+            format: OutputFormat::from_matches(matches),
+        }))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("journal")
+            .about("show round events which the running signer node recorded")
+            .arg(
+                Arg::with_name("height")
+                    .long("height")
+                    .takes_value(true)
+                    .help("block height of the rounds. The latest one is shown if it is omitted."),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer_node::journal::JournalEntry;
+
+    #[test]
+    fn test_format() {
+        let response = JournalResponse {
+            record: RoundRecord {
+                block_height: 10,
+                entries: vec![
+                    JournalEntry {
+                        time: 1600000000,
+                        event: "Round started. master_index=1".to_string(),
+                    },
+                    JournalEntry {
+                        time: 1600000060,
+                        event: "Round completed.".to_string(),
+                    },
+                ],
+            },
+            format: OutputFormat::Human,
+        };
+        assert_eq!(
+            format!("{}", response),
+            "block height: 10\n1600000000 Round started. master_index=1\n1600000060 Round completed."
+        );
+    }
+}
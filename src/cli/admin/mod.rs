@@ -0,0 +1,49 @@
+use crate::control::{ControlClient, DEFAULT_CONTROL_SOCKET};
+use crate::errors::Error;
+use clap::{Arg, ArgMatches};
+use serde::de::DeserializeOwned;
+
+pub mod federation;
+pub mod journal;
+pub mod notify;
+pub mod pause;
+pub mod peers;
+pub mod status;
+
+pub use crate::cli::{write_json, OutputFormat};
+
+/// Arguments which all commands share.
+pub fn global_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("control-socket")
+            .long("control-socket")
+            .global(true)
+            .takes_value(true)
+            .value_name("SOCKET_PATH")
+            .default_value(DEFAULT_CONTROL_SOCKET)
+            .help(
+                "path to the Unix domain socket of the control endpoint of the running signer node",
+            ),
+        Arg::with_name("format")
+            .long("format")
+            .global(true)
+            .takes_value(true)
+            .possible_values(&["text", "json"])
+            .default_value("text")
+            .help("output format. `json` prints the result as JSON"),
+    ]
+}
+
+/// Send a request to the control endpoint and deserialize the response.
+pub fn request<T: DeserializeOwned>(
+    matches: &ArgMatches,
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+) -> Result<T, Error> {
+    let socket = matches
+        .value_of("control-socket")
+        .unwrap_or(DEFAULT_CONTROL_SOCKET);
+    let response = ControlClient::new(socket).request(method, path, body)?;
+    Ok(serde_json::from_str(&response.body)?)
+}
@@ -0,0 +1,57 @@
+use crate::cli::admin::{request, write_json, OutputFormat};
+use crate::cli::setup::traits::Response;
+use crate::errors::Error;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fmt;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BlockNotification {
+    blockhash: String,
+}
+
+pub struct NotifyBlockResponse {
+    notification: BlockNotification,
+    format: OutputFormat,
+}
+
+impl Response for NotifyBlockResponse {}
+
+impl fmt::Display for NotifyBlockResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(f, &self.notification);
+        }
+
+        write!(
+            f,
+            "The node is notified of {}.",
+            self.notification.blockhash
+        )
+    }
+}
+
+pub struct NotifyBlockCommand {}
+
+impl<'a> NotifyBlockCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let blockhash = matches
+            .value_of("blockhash")
+            .ok_or_else(|| Error::InvalidArgs("blockhash".to_string()))?;
+        let notification: BlockNotification =
+            request(matches, "POST", "/blocknotify", Some(blockhash))?;
+        Ok(Box::new(NotifyBlockResponse {
+            notification,
+            format: OutputFormat::from_matches(matches),
+        }))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("notify-block")
+            .about("notify the running signer node of the new tip. It is for `-blocknotify` of Tapyrus Core.")
+            .arg(
+                Arg::with_name("blockhash")
+                    .required(true)
+                    .help("hash of the new tip block"),
+            )
+    }
+}
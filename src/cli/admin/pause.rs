@@ -0,0 +1,65 @@
+use crate::cli::admin::{request, write_json, OutputFormat};
+use crate::cli::setup::traits::Response;
+use crate::errors::Error;
+use clap::{App, ArgMatches, SubCommand};
+use std::fmt;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PauseState {
+    paused: bool,
+}
+
+pub struct PauseResponse {
+    state: PauseState,
+    format: OutputFormat,
+}
+
+impl Response for PauseResponse {}
+
+impl fmt::Display for PauseResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(f, &self.state);
+        }
+
+        if self.state.paused {
+            write!(f, "The node is paused.")
+        } else {
+            write!(f, "The node is resumed.")
+        }
+    }
+}
+
+fn execute(matches: &ArgMatches, path: &str) -> Result<Box<dyn Response>, Error> {
+    let state: PauseState = request(matches, "POST", path, None)?;
+    Ok(Box::new(PauseResponse {
+        state,
+        format: OutputFormat::from_matches(matches),
+    }))
+}
+
+pub struct PauseCommand {}
+
+impl<'a> PauseCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        execute(matches, "/pause")
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("pause").about(
+            "stop proposing and signing blocks. The node keeps following rounds with other signers.",
+        )
+    }
+}
+
+pub struct ResumeCommand {}
+
+impl<'a> ResumeCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        execute(matches, "/resume")
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("resume").about("resume proposing and signing blocks")
+    }
+}
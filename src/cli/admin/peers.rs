@@ -0,0 +1,82 @@
+use crate::cli::admin::{request, write_json, OutputFormat};
+use crate::cli::setup::traits::Response;
+use crate::errors::Error;
+use crate::signer_node::status::PeerInfo;
+use clap::{App, ArgMatches, SubCommand};
+use std::fmt;
+
+pub struct PeersResponse {
+    peers: Vec<PeerInfo>,
+    format: OutputFormat,
+}
+
+impl Response for PeersResponse {}
+
+impl fmt::Display for PeersResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(f, &self.peers);
+        }
+
+        if self.peers.is_empty() {
+            return write!(f, "No heartbeat has been received from peers.");
+        }
+        let lines: Vec<String> = self
+            .peers
+            .iter()
+            .map(|peer| {
+                format!(
+                    "{} {} tip_height={} state={} version={} last_seen={}s ago",
+                    peer.signer_id,
+                    if peer.alive { "online" } else { "offline" },
+                    peer.tip_height,
+                    peer.state,
+                    peer.version,
+                    peer.last_seen
+                )
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+pub struct PeersCommand {}
+
+impl<'a> PeersCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let peers: Vec<PeerInfo> = request(matches, "GET", "/peers", None)?;
+        Ok(Box::new(PeersResponse {
+            peers,
+            format: OutputFormat::from_matches(matches),
+        }))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("peers")
+            .about("show the latest heartbeats which the running signer node received from peers")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let response = PeersResponse {
+            peers: vec![PeerInfo {
+                signer_id: "02aa".to_string(),
+                version: "0.4.0".to_string(),
+                tip_height: 10,
+                state: "Member".to_string(),
+                last_seen: 3,
+                alive: true,
+            }],
+            format: OutputFormat::Human,
+        };
+        assert_eq!(
+            format!("{}", response),
+            "02aa online tip_height=10 state=Member version=0.4.0 last_seen=3s ago"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::cli::admin::federation::write_federation;
+use crate::cli::admin::{request, write_json, OutputFormat};
+use crate::cli::setup::traits::Response;
+use crate::errors::Error;
+use crate::signer_node::NodeStatus;
+use clap::{App, ArgMatches, SubCommand};
+use std::fmt;
+
+pub struct StatusResponse {
+    status: NodeStatus,
+    format: OutputFormat,
+}
+
+impl Response for StatusResponse {}
+
+impl fmt::Display for StatusResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(f, &self.status);
+        }
+
+        let status = &self.status;
+        writeln!(f, "signer id: {}", status.signer_id)?;
+        writeln!(f, "state: {}", status.state)?;
+        match status.master_index {
+            Some(index) => writeln!(f, "master index: {}", index)?,
+            None => writeln!(f, "master index: -")?,
+        }
+        writeln!(f, "paused: {}", status.paused)?;
+        match &status.active_federation {
+            Some(federation) => {
+                writeln!(f, "active federation:")?;
+                write_federation(f, federation)?;
+            }
+            None => writeln!(f, "active federation: -")?,
+        }
+        if status.upcoming_federations.is_empty() {
+            writeln!(f, "upcoming federations: -")?;
+        } else {
+            writeln!(f, "upcoming federations:")?;
+            for federation in &status.upcoming_federations {
+                write_federation(f, federation)?;
+            }
+        }
+        writeln!(
+            f,
+            "timers: round_limit={}, round_interval={}, heartbeat={}",
+            running(status.timers.round_limit),
+            running(status.timers.round_interval),
+            running(status.timers.heartbeat)
+        )?;
+        let rpc_endpoint = match &status.connections.rpc_endpoint {
+            Some(endpoint) => format!(" ({})", endpoint),
+            None => String::new(),
+        };
+        write!(
+            f,
+            "connections: rpc={}{}, redis={}",
+            connected(status.connections.rpc),
+            rpc_endpoint,
+            connected(status.connections.redis)
+        )
+    }
+}
+
+fn running(started: bool) -> &'static str {
+    if started {
+        "running"
+    } else {
+        "stopped"
+    }
+}
+
+fn connected(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "unreachable"
+    }
+}
+
+pub struct StatusCommand {}
+
+impl<'a> StatusCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let status: NodeStatus = request(matches, "GET", "/status", None)?;
+        Ok(Box::new(StatusResponse {
+            status,
+            format: OutputFormat::from_matches(matches),
+        }))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("status").about("show the status of the running signer node")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer_node::status::{ConnectionsStatus, StateSummary, TimersStatus};
+
+    #[test]
+    fn test_format() {
+        let status = NodeStatus {
+            signer_id: "03831a69b8009833ab5b0326012eaf489bfea35a7321b1ca15b11d88131423fafc"
+                .to_string(),
+            state: StateSummary {
+                kind: "Member".to_string(),
+                block_height: Some(10),
+                candidate_block: None,
+                vss_count: 0,
+                signature_count: 0,
+                participants: vec![],
+            },
+            master_index: Some(1),
+            paused: false,
+            active_federation: None,
+            upcoming_federations: vec![],
+            timers: TimersStatus {
+                round_limit: true,
+                round_interval: false,
+                heartbeat: true,
+            },
+            connections: ConnectionsStatus {
+                rpc: true,
+                rpc_endpoint: Some("http://127.0.0.1:2377".to_string()),
+                redis: false,
+            },
+        };
+        let response = StatusResponse {
+            status: status.clone(),
+            format: OutputFormat::Human,
+        };
+        assert_eq!(
+            format!("{}", response),
+            "signer id: 03831a69b8009833ab5b0326012eaf489bfea35a7321b1ca15b11d88131423fafc\n\
+             state: Member { block_height: 10, candidate_block: None, vss: 0, signatures: 0, participants: 0 }\n\
+             master index: 1\n\
+             paused: false\n\
+             active federation: -\n\
+             upcoming federations: -\n\
+             timers: round_limit=running, round_interval=stopped, heartbeat=running\n\
+             connections: rpc=ok (http://127.0.0.1:2377), redis=unreachable"
+        );
+
+        let response = StatusResponse {
+            status: status.clone(),
+            format: OutputFormat::Json,
+        };
+        let json: NodeStatus = serde_json::from_str(&format!("{}", response)).unwrap();
+        assert_eq!(json, status);
+    }
+}
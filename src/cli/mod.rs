@@ -1 +1,29 @@
+use clap::ArgMatches;
+use serde::Serialize;
+use std::fmt;
+
+pub mod admin;
 pub mod setup;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+impl OutputFormat {
+    /// Returns the format which is given by `--format json`.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        if matches.value_of("format") == Some("json") {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Human
+        }
+    }
+}
+
+/// Write the value as pretty JSON.
+pub fn write_json<T: Serialize>(f: &mut fmt::Formatter<'_>, value: &T) -> fmt::Result {
+    let json = serde_json::to_string_pretty(value).map_err(|_| fmt::Error)?;
+    write!(f, "{}", json)
+}
@@ -1,6 +1,8 @@
 use crate::cli::setup::index_of;
 use crate::cli::setup::traits::Response;
+use crate::cli::setup::values_of;
 use crate::cli::setup::vss_to_shared_secret_map;
+use crate::cli::{write_json, OutputFormat};
 use crate::crypto::vss::Vss;
 use crate::errors::Error;
 use crate::rpc::Rpc;
@@ -19,13 +21,15 @@ use tapyrus::{PrivateKey, PublicKey};
 pub struct AggregateResponse {
     aggregated_public_key: PublicKey,
     node_shared_secret: FE,
+    format: OutputFormat,
 }
 
 impl AggregateResponse {
-    fn new(aggregated_public_key: PublicKey, node_shared_secret: FE) -> Self {
+    fn new(aggregated_public_key: PublicKey, node_shared_secret: FE, format: OutputFormat) -> Self {
         AggregateResponse {
             aggregated_public_key: aggregated_public_key,
             node_shared_secret: node_shared_secret,
+            format: format,
         }
     }
 }
@@ -35,6 +39,15 @@ impl Response for AggregateResponse {}
 impl fmt::Display for AggregateResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let secret = format!("{:0>64}", self.node_shared_secret.to_big_int().to_hex());
+        if self.format == OutputFormat::Json {
+            return write_json(
+                f,
+                &serde_json::json!({
+                    "aggregated_public_key": self.aggregated_public_key.to_string(),
+                    "node_secret_share": secret,
+                }),
+            );
+        }
         write!(f, "{} {}", self.aggregated_public_key, secret,)
     }
 }
@@ -48,9 +61,8 @@ impl<'a> AggregateCommand {
             .and_then(|key| PrivateKey::from_wif(key).ok())
             .ok_or(Error::InvalidArgs("private-key".to_string()))?;
 
-        let vss_vec: Vec<Vss> = matches
-            .values_of("vss")
-            .ok_or(Error::InvalidArgs("vss is invalid".to_string()))?
+        let vss_vec: Vec<Vss> = values_of(matches, "vss")?
+            .iter()
             .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidSS))
             .collect::<Result<Vec<Vss>, _>>()?;
 
@@ -76,6 +88,7 @@ impl<'a> AggregateCommand {
         Ok(Box::new(AggregateResponse::new(
             public_key,
             shared_keys.x_i,
+            OutputFormat::from_matches(matches),
         )))
     }
 
@@ -83,10 +96,16 @@ impl<'a> AggregateCommand {
         SubCommand::with_name("aggregate").args(&[
             Arg::with_name("vss")
                 .long("vss")
-                .required(true)
+                .required_unless("vss-file")
                 .multiple(true)
                 .takes_value(true)
                 .help("secret values (Vss) of the all signers. These values is generated by `tapyrus-setup createnodevss`"),
+            Arg::with_name("vss-file")
+                .long("vss-file")
+                .multiple(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file or directory of files which has a Vss in each line"),
             Arg::with_name("private-key")
                 .long("private-key")
                 .required(true)
@@ -111,11 +130,37 @@ mod tests {
             "03842d51608d08bee79587fb3b54ea68f5279e13fac7d72515a7205e6672858ca2",
         )
         .unwrap();
-        let response = AggregateResponse::new(public_key, ECScalar::from(&BigInt::from(0xff)));
+        let response = AggregateResponse::new(
+            public_key,
+            ECScalar::from(&BigInt::from(0xff)),
+            OutputFormat::Human,
+        );
 
         assert_eq!(format!("{}", response), "03842d51608d08bee79587fb3b54ea68f5279e13fac7d72515a7205e6672858ca2 00000000000000000000000000000000000000000000000000000000000000ff");
     }
 
+    #[test]
+    fn test_aggregate_response_json() {
+        let public_key = PublicKey::from_str(
+            "03842d51608d08bee79587fb3b54ea68f5279e13fac7d72515a7205e6672858ca2",
+        )
+        .unwrap();
+        let response = AggregateResponse::new(
+            public_key,
+            ECScalar::from(&BigInt::from(0xff)),
+            OutputFormat::Json,
+        );
+        let json: serde_json::Value = serde_json::from_str(&format!("{}", response)).unwrap();
+        assert_eq!(
+            json["aggregated_public_key"],
+            "03842d51608d08bee79587fb3b54ea68f5279e13fac7d72515a7205e6672858ca2"
+        );
+        assert_eq!(
+            json["node_secret_share"],
+            "00000000000000000000000000000000000000000000000000000000000000ff"
+        );
+    }
+
     #[test]
     fn test_execute() {
         let matches = AggregateCommand::args().get_matches_from(vec![
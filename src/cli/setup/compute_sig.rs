@@ -2,6 +2,8 @@ use crate::cli::setup::index_of;
 use crate::cli::setup::traits::Response;
 use crate::cli::setup::vss_to_bidirectional_shared_secret_map;
 use crate::cli::setup::vss_to_shared_secret_map;
+use crate::cli::setup::{value_of, values_of};
+use crate::cli::{write_json, OutputFormat};
 use crate::crypto::multi_party_schnorr::LocalSig;
 use crate::crypto::multi_party_schnorr::SharedKeys;
 use crate::crypto::vss::Vss;
@@ -24,12 +26,14 @@ use tapyrus::{PrivateKey, PublicKey};
 
 pub struct ComputeSigResponse {
     block_with_signature: Block,
+    format: OutputFormat,
 }
 
 impl ComputeSigResponse {
-    fn new(block_with_signature: Block) -> Self {
+    fn new(block_with_signature: Block, format: OutputFormat) -> Self {
         ComputeSigResponse {
             block_with_signature: block_with_signature,
+            format: format,
         }
     }
 }
@@ -38,8 +42,95 @@ impl Response for ComputeSigResponse {}
 
 impl fmt::Display for ComputeSigResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", hex::encode(serialize(&self.block_with_signature)))
+        let block = hex::encode(serialize(&self.block_with_signature));
+        if self.format == OutputFormat::Json {
+            return write_json(f, &serde_json::json!({ "block": block }));
+        }
+        write!(f, "{}", block)
+    }
+}
+
+/// Parses the local signature formatted by `sign::format_local_sig`.
+pub fn parse_local_sig(s: &str) -> Result<(LocalSig, PublicKey), Error> {
+    if s.len() != 194 {
+        return Err(Error::InvalidArgs("sig".to_string()));
     }
+    let gamma_i = ECScalar::from(
+        &BigInt::from_str_radix(&s[0..64], 16)
+            .map_err(|_| Error::InvalidArgs("value gamma is invalid".to_string()))?,
+    );
+    let e = ECScalar::from(
+        &BigInt::from_str_radix(&s[64..128], 16)
+            .map_err(|_| Error::InvalidArgs("value e is invalid".to_string()))?,
+    );
+    let public_key = PublicKey::from_str(&s[128..])
+        .map_err(|_| Error::InvalidArgs("public key is invalid".to_string()))?;
+    Ok((
+        LocalSig {
+            gamma_i: gamma_i,
+            e: e,
+        },
+        public_key,
+    ))
+}
+
+/// Aggregates the local signatures of all signers and returns the block with the proof.
+pub fn compute_sig(
+    private_key: &PrivateKey,
+    threshold: usize,
+    aggregated_public_key: &PublicKey,
+    node_secret_share: FE,
+    mut block: Block,
+    node_vss_vec: &Vec<Vss>,
+    block_vss_vec: &Vec<Vss>,
+    keyed_local_sigs: Vec<(LocalSig, PublicKey)>,
+) -> Result<Block, Error> {
+    let mut public_keys: Vec<PublicKey> = block_vss_vec
+        .iter()
+        .map(|vss| vss.sender_public_key)
+        .collect();
+    NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
+
+    let index = index_of(private_key, &public_keys);
+
+    let params = ShamirSecretSharing {
+        threshold: threshold - 1,
+        share_count: public_keys.len(),
+    };
+    let shared_block_secrets = vss_to_bidirectional_shared_secret_map(block_vss_vec, &params);
+
+    let bytes: Vec<u8> = aggregated_public_key.key.serialize_uncompressed().to_vec();
+    let point = GE::from_bytes(&bytes[1..]).expect("failed to convert to point");
+    let priv_shared_keys = SharedKeys {
+        y: point,
+        x_i: node_secret_share,
+    };
+
+    let (is_positive, block_shared_keys, _local_sig) =
+        Vss::create_local_sig_from_shares(&priv_shared_keys, index, &shared_block_secrets, &block)?;
+
+    let shared_secrets = vss_to_shared_secret_map(node_vss_vec, &params);
+
+    let mut signatures = BTreeMap::new();
+    for (sig, public_key) in keyed_local_sigs {
+        signatures.insert(SignerID { pubkey: public_key }, (sig.gamma_i, sig.e));
+    }
+    let signature = Vss::aggregate_and_verify_signature(
+        &block,
+        signatures,
+        &public_keys,
+        &shared_secrets,
+        &Some((is_positive, block_shared_keys.x_i, block_shared_keys.y)),
+        &shared_block_secrets,
+        &priv_shared_keys.y,
+    )?;
+    let hash = block.header.signature_hash();
+    signature.verify(&hash, &priv_shared_keys.y)?;
+    let sig_hex = Sign::format_signature(&signature);
+    let sig: tapyrus::util::signature::Signature =
+        deserialize(&hex::decode(sig_hex).map_err(|_| Error::InvalidSig)?)?;
+    block.header.proof = Some(sig);
+    Ok(block)
 }
 
 pub struct ComputeSigCommand {}
@@ -67,49 +158,24 @@ impl<'a> ComputeSigCommand {
             .map(|i| ECScalar::from(&i))
             .ok_or(Error::InvalidArgs("node-secret-share".to_string()))?;
 
-        let mut block: Block = matches
-            .value_of("block")
+        let block: Block = value_of(matches, "block")?
             .and_then(|s| hex::decode(s).ok())
             .and_then(|hex| deserialize::<Block>(&hex).ok())
             .ok_or(Error::InvalidArgs("block".to_string()))?;
 
-        let node_vss_vec: Vec<Vss> = matches
-            .values_of("node-vss")
-            .ok_or(Error::InvalidArgs("node-vss".to_string()))?
+        let node_vss_vec: Vec<Vss> = values_of(matches, "node-vss")?
+            .iter()
             .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidArgs("node-vss".to_string())))
             .collect::<Result<Vec<Vss>, _>>()?;
 
-        let block_vss_vec: Vec<Vss> = matches
-            .values_of("block-vss")
-            .ok_or(Error::InvalidArgs("block-vss".to_string()))?
+        let block_vss_vec: Vec<Vss> = values_of(matches, "block-vss")?
+            .iter()
             .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidArgs("block-vss".to_string())))
             .collect::<Result<Vec<Vss>, _>>()?;
 
-        let keyed_local_sigs: Vec<(LocalSig, PublicKey)> = matches
-            .values_of("sig")
-            .ok_or(Error::InvalidArgs("local-sig is invalid".to_string()))?
-            .map(|s| {
-                if s.len() != 194 {
-                    return Err(Error::InvalidArgs("sig".to_string()));
-                }
-                let gamma_i = ECScalar::from(
-                    &BigInt::from_str_radix(&s[0..64], 16)
-                        .map_err(|_| Error::InvalidArgs("value gamma is invalid".to_string()))?,
-                );
-                let e = ECScalar::from(
-                    &BigInt::from_str_radix(&s[64..128], 16)
-                        .map_err(|_| Error::InvalidArgs("value e is invalid".to_string()))?,
-                );
-                let public_key = PublicKey::from_str(&s[128..])
-                    .map_err(|_| Error::InvalidArgs("public key is invalid".to_string()))?;
-                Ok((
-                    LocalSig {
-                        gamma_i: gamma_i,
-                        e: e,
-                    },
-                    public_key,
-                ))
-            })
+        let keyed_local_sigs: Vec<(LocalSig, PublicKey)> = values_of(matches, "sig")?
+            .iter()
+            .map(|s| parse_local_sig(s))
             .collect::<Result<Vec<(LocalSig, PublicKey)>, Error>>()?;
 
         assert_eq!(
@@ -123,56 +189,20 @@ impl<'a> ComputeSigCommand {
             "the length of sig should equal to the length of node vss"
         );
 
-        let mut public_keys: Vec<PublicKey> = block_vss_vec
-            .iter()
-            .map(|vss| vss.sender_public_key)
-            .collect();
-        NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
-
-        let index = index_of(&private_key, &public_keys);
-
-        let params = ShamirSecretSharing {
-            threshold: threshold - 1,
-            share_count: public_keys.len(),
-        };
-        let shared_block_secrets = vss_to_bidirectional_shared_secret_map(&block_vss_vec, &params);
-
-        let bytes: Vec<u8> = aggregated_public_key.key.serialize_uncompressed().to_vec();
-        let point = GE::from_bytes(&bytes[1..]).expect("failed to convert to point");
-        let priv_shared_keys = SharedKeys {
-            y: point,
-            x_i: node_secret_share,
-        };
-
-        let (is_positive, block_shared_keys, _local_sig) = Vss::create_local_sig_from_shares(
-            &priv_shared_keys,
-            index,
-            &shared_block_secrets,
-            &block,
+        let block = compute_sig(
+            &private_key,
+            threshold,
+            &aggregated_public_key,
+            node_secret_share,
+            block,
+            &node_vss_vec,
+            &block_vss_vec,
+            keyed_local_sigs,
         )?;
-
-        let shared_secrets = vss_to_shared_secret_map(&node_vss_vec, &params);
-
-        let mut signatures = BTreeMap::new();
-        for (sig, public_key) in keyed_local_sigs {
-            signatures.insert(SignerID { pubkey: public_key }, (sig.gamma_i, sig.e));
-        }
-        let signature = Vss::aggregate_and_verify_signature(
-            &block,
-            signatures,
-            &public_keys,
-            &shared_secrets,
-            &Some((is_positive, block_shared_keys.x_i, block_shared_keys.y)),
-            &shared_block_secrets,
-            &priv_shared_keys,
-        )?;
-        let hash = block.header.signature_hash();
-        signature.verify(&hash, &priv_shared_keys.y)?;
-        let sig_hex = Sign::format_signature(&signature);
-        let sig: tapyrus::util::signature::Signature =
-            deserialize(&hex::decode(sig_hex).map_err(|_| Error::InvalidSig)?)?;
-        block.header.proof = Some(sig);
-        Ok(Box::new(ComputeSigResponse::new(block)))
+        Ok(Box::new(ComputeSigResponse::new(
+            block,
+            OutputFormat::from_matches(matches),
+        )))
     }
 
     pub fn args<'b>() -> App<'a, 'b> {
@@ -189,9 +219,14 @@ impl<'a> ComputeSigCommand {
                 .help("the minimum number of signers required to sign block"),
             Arg::with_name("block")
                 .long("block")
-                .required(true)
+                .required_unless("block-file")
                 .takes_value(true)
                 .help("block to be signed as a hex string format"),
+            Arg::with_name("block-file")
+                .long("block-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file which has the block to be signed as a hex string format"),
             Arg::with_name("node-secret-share")
                 .long("node-secret-share")
                 .required(true)
@@ -204,22 +239,40 @@ impl<'a> ComputeSigCommand {
                 .help("aggregated public key of all signers"),
             Arg::with_name("node-vss")
                 .long("node-vss")
-                .required(true)
+                .required_unless("node-vss-file")
                 .multiple(true)
                 .takes_value(true)
                 .help("the node VSSs generated by tapyrus-setup createnodevss command"),
+            Arg::with_name("node-vss-file")
+                .long("node-vss-file")
+                .multiple(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file or directory of files which has a node VSS in each line"),
             Arg::with_name("block-vss")
                 .long("block-vss")
-                .required(true)
+                .required_unless("block-vss-file")
                 .multiple(true)
                 .takes_value(true)
                 .help("the block VSSs generated by tapyrus-setup createblockvss command"),
+            Arg::with_name("block-vss-file")
+                .long("block-vss-file")
+                .multiple(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file or directory of files which has a block VSS in each line"),
             Arg::with_name("sig")
                 .long("sig")
-                .required(true)
+                .required_unless("sig-file")
                 .multiple(true)
                 .takes_value(true)
                 .help("the local signatures generated by tapyrus-setup sign command"),
+            Arg::with_name("sig-file")
+                .long("sig-file")
+                .multiple(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file or directory of files which has a local signature in each line"),
         ])
     }
 }
@@ -227,10 +280,10 @@ impl<'a> ComputeSigCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli::setup::verify::VerifyBlockCommand;
 
-    #[test]
-    fn test_execute_success() {
-        let matches = ComputeSigCommand::args().get_matches_from(vec![
+    fn success_args() -> Vec<&'static str> {
+        vec![
             "computesig",
             "--threshold",
             "2",
@@ -260,11 +313,57 @@ mod tests {
             "1d733ca6f69ee87b75515666fb36672512d7cdb7173258f7c75ef8b7e763cab7c26be91145a7b67096e44bb318eb1780f4fc1630e6cde6d5705690236fbe0890023cb7d6326e33332d04d026be1a04cdaf084703d8dc75322182d8fb314a03a877",
             "--private-key",
             "L2hmApEYQBQo81RLJc5MMwo6ZZywnfVzuQj6uCfxFLaV2Yo2pVyq",
-        ]);
+        ]
+    }
+
+    #[test]
+    fn test_execute_success() {
+        let matches = ComputeSigCommand::args().get_matches_from(success_args());
         let response = ComputeSigCommand::execute(&matches);
         assert!(response.is_ok());
     }
 
+    #[test]
+    fn test_execute_and_verify_block() {
+        let matches = ComputeSigCommand::args().get_matches_from(success_args());
+        let block = format!("{}", ComputeSigCommand::execute(&matches).unwrap());
+
+        let matches = VerifyBlockCommand::args().get_matches_from(vec![
+            "verifyblock",
+            "--block",
+            &block,
+            "--aggregated-public-key",
+            "03addb2555f37abf8f28f11f498bec7bd1460e7243c1813847c49a7ae326a97d1c",
+        ]);
+        assert!(VerifyBlockCommand::execute(&matches).is_ok());
+
+        // The proof is not valid for another key.
+        let matches = VerifyBlockCommand::args().get_matches_from(vec![
+            "verifyblock",
+            "--block",
+            &block,
+            "--aggregated-public-key",
+            "03b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d",
+        ]);
+        match VerifyBlockCommand::execute(&matches) {
+            Err(Error::VerificationFailed(_)) => {}
+            _ => panic!("should fail"),
+        }
+
+        // The block without proof.
+        let matches = VerifyBlockCommand::args().get_matches_from(vec![
+            "verifyblock",
+            "--block",
+            success_args()[4],
+            "--aggregated-public-key",
+            "03addb2555f37abf8f28f11f498bec7bd1460e7243c1813847c49a7ae326a97d1c",
+        ]);
+        match VerifyBlockCommand::execute(&matches) {
+            Err(Error::IncompleteBlock) => {}
+            _ => panic!("should fail"),
+        }
+    }
+
     #[test]
     fn test_execute_invalid_threshold() {
         let matches = ComputeSigCommand::args().get_matches_from(vec![
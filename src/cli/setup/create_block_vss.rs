@@ -1,5 +1,7 @@
 use crate::cli::setup::index_of;
 use crate::cli::setup::traits::Response;
+use crate::cli::setup::values_of;
+use crate::cli::{write_json, OutputFormat};
 use crate::crypto::vss::{Commitment, Vss};
 use crate::errors::Error;
 use crate::rpc::Rpc;
@@ -13,11 +15,15 @@ use tapyrus::{PrivateKey, PublicKey};
 
 pub struct CreateBlockVssResponse {
     vss: BTreeMap<PublicKey, Vss>,
+    format: OutputFormat,
 }
 
 impl CreateBlockVssResponse {
-    fn new(vss: BTreeMap<PublicKey, Vss>) -> Self {
-        CreateBlockVssResponse { vss: vss }
+    fn new(vss: BTreeMap<PublicKey, Vss>, format: OutputFormat) -> Self {
+        CreateBlockVssResponse {
+            vss: vss,
+            format: format,
+        }
     }
 }
 
@@ -25,6 +31,20 @@ impl Response for CreateBlockVssResponse {}
 
 impl fmt::Display for CreateBlockVssResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            let vss: Vec<serde_json::Value> = self
+                .vss
+                .iter()
+                .map(|(k, v)| {
+                    serde_json::json!({
+                        "receiver_public_key": k.to_string(),
+                        "vss": v.to_string(),
+                    })
+                })
+                .collect();
+            return write_json(f, &serde_json::json!({ "vss": vss }));
+        }
+
         let c = self
             .vss
             .iter()
@@ -35,6 +55,47 @@ impl fmt::Display for CreateBlockVssResponse {
     }
 }
 
+/// Creates the block VSSs which the signer of `private_key` sends to each signer. `public_keys`
+/// should be sorted.
+pub fn create_block_vss(
+    private_key: &PrivateKey,
+    public_keys: &Vec<PublicKey>,
+    threshold: usize,
+) -> BTreeMap<PublicKey, Vss> {
+    let index = index_of(private_key, public_keys);
+    let (
+        _key,
+        vss_scheme_for_positive,
+        secret_shares_for_positive,
+        vss_scheme_for_negative,
+        secret_shares_for_negative,
+    ) = Vss::create_block_shares(index, threshold, public_keys.len());
+    let mut vss_map = BTreeMap::new();
+    let secp = tapyrus::secp256k1::Secp256k1::new();
+    let sender_public_key = PublicKey::from_private_key(&secp, private_key);
+
+    for j in 0..public_keys.len() {
+        let vss = Vss {
+            sender_public_key: sender_public_key,
+            receiver_public_key: public_keys[j].clone(),
+            positive_commitments: vss_scheme_for_positive
+                .commitments
+                .iter()
+                .map(|c| Commitment::from(c))
+                .collect(),
+            positive_secret: secret_shares_for_positive[j],
+            negative_commitments: vss_scheme_for_negative
+                .commitments
+                .iter()
+                .map(|c| Commitment::from(c))
+                .collect(),
+            negative_secret: secret_shares_for_negative[j],
+        };
+        vss_map.insert(public_keys[j].clone(), vss);
+    }
+    vss_map
+}
+
 pub struct CreateBlockVssCommand {}
 
 impl<'a> CreateBlockVssCommand {
@@ -44,9 +105,8 @@ impl<'a> CreateBlockVssCommand {
             .and_then(|key| PrivateKey::from_wif(key).ok())
             .ok_or(Error::InvalidArgs("private-key".to_string()))?;
 
-        let mut public_keys: Vec<PublicKey> = matches
-            .values_of("public-key")
-            .ok_or(Error::InvalidArgs("public-key".to_string()))?
+        let mut public_keys: Vec<PublicKey> = values_of(matches, "public-key")?
+            .iter()
             .map(|key| PublicKey::from_str(key).map_err(|_| Error::InvalidKey))
             .collect::<Result<Vec<PublicKey>, _>>()?;
         NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
@@ -58,48 +118,27 @@ impl<'a> CreateBlockVssCommand {
                 "threshold should be integer.".to_string(),
             ))?;
 
-        let index = index_of(&private_key, &public_keys);
-        let (
-            _key,
-            vss_scheme_for_positive,
-            secret_shares_for_positive,
-            vss_scheme_for_negative,
-            secret_shares_for_negative,
-        ) = Vss::create_block_shares(index, threshold as usize, public_keys.len());
-        let mut vss_map = BTreeMap::new();
-        let secp = tapyrus::secp256k1::Secp256k1::new();
-        let sender_public_key = PublicKey::from_private_key(&secp, &private_key);
-
-        for j in 0..public_keys.len() {
-            let vss = Vss {
-                sender_public_key: sender_public_key,
-                receiver_public_key: public_keys[j].clone(),
-                positive_commitments: vss_scheme_for_positive
-                    .commitments
-                    .iter()
-                    .map(|c| Commitment::from(c))
-                    .collect(),
-                positive_secret: secret_shares_for_positive[j],
-                negative_commitments: vss_scheme_for_negative
-                    .commitments
-                    .iter()
-                    .map(|c| Commitment::from(c))
-                    .collect(),
-                negative_secret: secret_shares_for_negative[j],
-            };
-            vss_map.insert(public_keys[j].clone(), vss);
-        }
-        Ok(Box::new(CreateBlockVssResponse::new(vss_map)))
+        let vss_map = create_block_vss(&private_key, &public_keys, threshold as usize);
+        Ok(Box::new(CreateBlockVssResponse::new(
+            vss_map,
+            OutputFormat::from_matches(matches),
+        )))
     }
 
     pub fn args<'b>() -> App<'a, 'b> {
         SubCommand::with_name("createblockvss").args(&[
             Arg::with_name("public-key")
                 .long("public-key")
-                .required(true)
+                .required_unless("public-key-file")
                 .multiple(true)
                 .takes_value(true)
                 .help("compressed public key of the each signer with a hex format string"),
+            Arg::with_name("public-key-file")
+                .long("public-key-file")
+                .multiple(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file or directory of files which has a public key in each line"),
             Arg::with_name("private-key")
                 .long("private-key")
                 .required(true)
@@ -0,0 +1,326 @@
+use crate::cli::setup::traits::Response;
+use crate::cli::setup::values_of;
+use crate::cli::{write_json, OutputFormat};
+use crate::crypto::vss::Vss;
+use crate::errors::Error;
+use crate::federation::{Federation, Federations};
+use crate::util::sum_point;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use curv::elliptic::curves::traits::ECPoint;
+use curv::GE;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::str::FromStr;
+use tapyrus::PublicKey;
+
+pub struct FederationResponse {
+    federation: Federation,
+    federations_file: Option<String>,
+    format: OutputFormat,
+}
+
+impl FederationResponse {
+    pub fn new(
+        federation: Federation,
+        federations_file: Option<String>,
+        format: OutputFormat,
+    ) -> Self {
+        FederationResponse {
+            federation,
+            federations_file,
+            format,
+        }
+    }
+}
+
+impl Response for FederationResponse {}
+
+impl fmt::Display for FederationResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(
+                f,
+                &serde_json::json!({
+                    "federations_file": self.federations_file,
+                    "federation": self.federation.clone().to_ser(),
+                }),
+            );
+        }
+
+        if let Some(file) = &self.federations_file {
+            writeln!(f, "The federation is appended to {}.", file)?;
+        }
+        write!(f, "{}", self.federation.to_toml())
+    }
+}
+
+/// Appends the federation to the federations file whose content is `toml`. The file is not changed
+/// if the federations become invalid. The file is created if it doesn't exist.
+pub fn append_federation(
+    file: &str,
+    toml: &str,
+    federation: &Federation,
+    public_key: &PublicKey,
+) -> Result<(), Error> {
+    let entry = federation.to_toml();
+    let new_toml = format!("{}\n{}", toml, entry);
+    Federations::from_pubkey_and_toml(public_key, &new_toml)?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)?
+        .write_all(format!("\n{}", entry).as_bytes())?;
+    Ok(())
+}
+
+pub struct CreateFederationCommand {}
+
+impl<'a> CreateFederationCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let vss_vec: Vec<Vss> = values_of(matches, "vss")?
+            .iter()
+            .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidSS))
+            .collect::<Result<Vec<Vss>, _>>()?;
+        if vss_vec.is_empty() {
+            return Err(Error::InvalidArgs("vss".to_string()));
+        }
+
+        let threshold: u8 = matches
+            .value_of("threshold")
+            .and_then(|t| t.parse::<u8>().ok())
+            .ok_or(Error::InvalidArgs(
+                "threshold should be integer.".to_string(),
+            ))?;
+
+        let block_height: u64 = matches
+            .value_of("block-height")
+            .and_then(|t| t.parse::<u64>().ok())
+            .ok_or(Error::InvalidArgs(
+                "block-height should be integer.".to_string(),
+            ))?;
+
+        // The aggregated public key is the sum of the first commitments of the senders.
+        let points = vss_vec
+            .iter()
+            .map(|vss| {
+                vss.positive_commitments
+                    .first()
+                    .map(|c| c.to_point())
+                    .ok_or(Error::InvalidSS)
+            })
+            .collect::<Result<Vec<GE>, _>>()?;
+        let aggregated_public_key = PublicKey::from_slice(&sum_point(&points).pk_to_key_slice())
+            .map_err(|_| Error::InvalidKey)?;
+        if let Some(expected) = matches.value_of("aggregated-public-key") {
+            let expected = PublicKey::from_str(expected)
+                .map_err(|_| Error::InvalidArgs("aggregated-public-key".to_string()))?;
+            if expected != aggregated_public_key {
+                return Err(Error::InvalidArgs(format!(
+                    "aggregated-public-key does not match the node VSSs. The node VSSs have {}.",
+                    aggregated_public_key
+                )));
+            }
+        }
+
+        let public_key = vss_vec[0].receiver_public_key;
+        let federation = Federation::new(
+            public_key,
+            block_height,
+            Some(threshold),
+            Some(vss_vec),
+            aggregated_public_key,
+        );
+        federation.validate()?;
+
+        let federations_file = matches.value_of("federations-file");
+        if let Some(file) = federations_file {
+            let toml = match std::fs::read_to_string(file) {
+                Ok(toml) => toml,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e.into()),
+            };
+            if !toml.trim().is_empty() {
+                let federations = Federations::from_pubkey_and_toml(&public_key, &toml)?;
+                if federations.iter().any(|f| f.block_height() == block_height) {
+                    return Err(Error::InvalidArgs(format!(
+                        "the federation at block height {} already exists in {}.",
+                        block_height, file
+                    )));
+                }
+            }
+            append_federation(file, &toml, &federation, &public_key)?;
+        }
+
+        Ok(Box::new(FederationResponse::new(
+            federation,
+            federations_file.map(|s| s.to_string()),
+            OutputFormat::from_matches(matches),
+        )))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("createfederation")
+            .about("create an entry of federations.toml from the node VSSs which this signer received, and validate it")
+            .args(&[
+                Arg::with_name("vss")
+                    .long("vss")
+                    .required_unless("vss-file")
+                    .multiple(true)
+                    .takes_value(true)
+                    .help("node VSSs which this signer received from the all signers"),
+                Arg::with_name("vss-file")
+                    .long("vss-file")
+                    .multiple(true)
+                    .takes_value(true)
+                    .value_name("PATH")
+                    .help("file or directory of files which has a node VSS in each line"),
+                Arg::with_name("threshold")
+                    .long("threshold")
+                    .required(true)
+                    .takes_value(true)
+                    .help("the minimum number of signers required to sign block"),
+                Arg::with_name("block-height")
+                    .long("block-height")
+                    .required(true)
+                    .takes_value(true)
+                    .help("block height from which the federation signs blocks"),
+                Arg::with_name("aggregated-public-key")
+                    .long("aggregated-public-key")
+                    .takes_value(true)
+                    .help("aggregated public key which `tapyrus-setup aggregate` printed. If it is set, it is checked against the node VSSs."),
+                Arg::with_name("federations-file")
+                    .long("federations-file")
+                    .takes_value(true)
+                    .help("path to the federations toml file. The federation is appended to it. The file is created if it doesn't exist."),
+            ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use crate::tests::helper::node_vss::{node_vss, NODE_VSS};
+
+    #[test]
+    fn test_format() {
+        let federation = Federation::new(
+            TEST_KEYS.pubkeys()[0],
+            100,
+            Some(3),
+            Some(node_vss(0)),
+            TEST_KEYS.aggregated(),
+        );
+        let response = FederationResponse::new(
+            federation.clone(),
+            Some("/tmp/federations.toml".to_string()),
+            OutputFormat::Human,
+        );
+        let output = format!("{}", response);
+        assert!(output.starts_with("The federation is appended to /tmp/federations.toml.\n"));
+        assert!(output.contains("[[federation]]\nblock-height = 100\nthreshold = 3\n"));
+
+        // The entry is loaded as the same federation.
+        let toml = federation.to_toml();
+        let federations =
+            Federations::from_pubkey_and_toml(&TEST_KEYS.pubkeys()[0], &toml).unwrap();
+        assert_eq!(*federations.last(), federation);
+
+        let response = FederationResponse::new(federation, None, OutputFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&format!("{}", response)).unwrap();
+        assert_eq!(json["federations_file"], serde_json::Value::Null);
+        assert_eq!(json["federation"]["block-height"], 100);
+        assert_eq!(json["federation"]["threshold"], 3);
+        assert_eq!(json["federation"]["node-vss"].as_array().unwrap().len(), 5);
+    }
+
+    fn args(file: &str, block_height: &str) -> Vec<String> {
+        let mut args: Vec<String> = vec!["createfederation".to_string()];
+        for vss in NODE_VSS[0].iter() {
+            args.push("--vss".to_string());
+            args.push(vss.to_string());
+        }
+        args.extend(
+            vec![
+                "--threshold",
+                "3",
+                "--block-height",
+                block_height,
+                "--federations-file",
+                file,
+            ]
+            .into_iter()
+            .map(|s| s.to_string()),
+        );
+        args
+    }
+
+    #[test]
+    fn test_execute() {
+        let file = std::env::temp_dir().join(format!(
+            "tapyrus-setup-createfederation-test-{}.toml",
+            std::process::id()
+        ));
+        let file = file.to_str().unwrap();
+        let _ = std::fs::remove_file(file);
+
+        let matches = CreateFederationCommand::args().get_matches_from(args(file, "100"));
+        assert!(CreateFederationCommand::execute(&matches).is_ok());
+        let matches = CreateFederationCommand::args().get_matches_from(args(file, "200"));
+        assert!(CreateFederationCommand::execute(&matches).is_ok());
+
+        let toml = std::fs::read_to_string(file).unwrap();
+        let federations =
+            Federations::from_pubkey_and_toml(&TEST_KEYS.pubkeys()[0], &toml).unwrap();
+        assert_eq!(federations.len(), 2);
+        assert_eq!(federations.last().block_height(), 200);
+        assert_eq!(
+            federations.last().aggregated_public_key(),
+            TEST_KEYS.aggregated()
+        );
+
+        // Refuse the duplicate block height and keep the file.
+        let matches = CreateFederationCommand::args().get_matches_from(args(file, "100"));
+        let response = CreateFederationCommand::execute(&matches);
+        match response {
+            Err(Error::InvalidArgs(_)) => assert!(true),
+            _ => assert!(false, "it should error"),
+        }
+        assert_eq!(std::fs::read_to_string(file).unwrap(), toml);
+
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_execute_invalid_args() {
+        let mut args: Vec<String> = vec!["createfederation".to_string()];
+        for vss in NODE_VSS[0].iter() {
+            args.push("--vss".to_string());
+            args.push(vss.to_string());
+        }
+        args.extend(
+            vec!["--threshold", "2", "--block-height", "100"]
+                .into_iter()
+                .map(|s| s.to_string()),
+        );
+
+        // The commitments of the node VSSs doesn't match with the threshold.
+        let matches = CreateFederationCommand::args().get_matches_from(args.clone());
+        let response = CreateFederationCommand::execute(&matches);
+        match response {
+            Err(Error::InvalidFederation(Some(100), _)) => assert!(true),
+            _ => assert!(false, "it should error"),
+        }
+
+        args[12] = "3".to_string();
+        args.push("--aggregated-public-key".to_string());
+        args.push(TEST_KEYS.pubkeys()[0].to_string());
+        let matches = CreateFederationCommand::args().get_matches_from(args);
+        let response = CreateFederationCommand::execute(&matches);
+        match response {
+            Err(Error::InvalidArgs(_)) => assert!(true),
+            _ => assert!(false, "it should error"),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::cli::setup::traits::Response;
+use crate::cli::{write_json, OutputFormat};
+use crate::errors::Error;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tapyrus::blockdata::block::{Block, BlockHeader, XField};
+use tapyrus::blockdata::script::Builder;
+use tapyrus::blockdata::transaction::{OutPoint, Transaction, TxIn, TxOut};
+use tapyrus::consensus::encode::serialize;
+use tapyrus::hash_types::{BlockHash, TxMerkleNode};
+use tapyrus::{Address, PublicKey};
+
+/// The reward of the genesis coinbase, 50 TPC, which is the same as tapyrus-genesis.
+const GENESIS_REWARD: u64 = 50 * 100_000_000;
+
+pub struct CreateGenesisResponse {
+    block: Block,
+    format: OutputFormat,
+}
+
+impl CreateGenesisResponse {
+    fn new(block: Block, format: OutputFormat) -> Self {
+        CreateGenesisResponse { block, format }
+    }
+}
+
+impl Response for CreateGenesisResponse {}
+
+impl fmt::Display for CreateGenesisResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let block = hex::encode(serialize(&self.block));
+        if self.format == OutputFormat::Json {
+            return write_json(f, &serde_json::json!({ "block": block }));
+        }
+        write!(f, "{}", block)
+    }
+}
+
+/// Builds the genesis block without proof. The coinbase pays the reward to `address` and the
+/// header has `aggregated_public_key` in its xfield.
+pub fn genesis_block(aggregated_public_key: PublicKey, address: &Address, time: u32) -> Block {
+    // As the other coinbases in Tapyrus, the index of the previous output is the block height.
+    let coinbase = Transaction {
+        version: 1,
+        lock_time: 0,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Default::default(), 0),
+            script_sig: Builder::new().push_int(0).push_slice(&[1]).into_script(),
+            sequence: 0xffffffff,
+            witness: vec![],
+        }],
+        output: vec![TxOut {
+            value: GENESIS_REWARD,
+            script_pubkey: address.script_pubkey(),
+        }],
+    };
+
+    let mut block = Block {
+        header: BlockHeader {
+            version: 1,
+            prev_blockhash: BlockHash::default(),
+            merkle_root: TxMerkleNode::default(),
+            im_merkle_root: TxMerkleNode::default(),
+            time,
+            xfield: XField::AggregatePublicKey(aggregated_public_key),
+            proof: None,
+        },
+        txdata: vec![coinbase],
+    };
+    block.header.merkle_root = block.merkle_root();
+    block.header.im_merkle_root = block.immutable_merkle_root();
+    block
+}
+
+pub struct CreateGenesisCommand {}
+
+impl<'a> CreateGenesisCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let aggregated_public_key: PublicKey = matches
+            .value_of("aggregated-public-key")
+            .and_then(|hex| PublicKey::from_str(hex).ok())
+            .ok_or(Error::InvalidArgs("aggregated-public-key".to_string()))?;
+
+        let address: Address = matches
+            .value_of("address")
+            .and_then(|s| Address::from_str(s).ok())
+            .ok_or(Error::InvalidArgs("address".to_string()))?;
+
+        let time: u32 = match matches.value_of("time") {
+            Some(s) => s
+                .parse::<u32>()
+                .map_err(|_| Error::InvalidArgs("time should be integer.".to_string()))?,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system time is before the unix epoch")
+                .as_secs() as u32,
+        };
+
+        Ok(Box::new(CreateGenesisResponse::new(
+            genesis_block(aggregated_public_key, &address, time),
+            OutputFormat::from_matches(matches),
+        )))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("creategenesis")
+            .about("create the genesis block without proof. Sign it with `signgenesis`.")
+            .args(&[
+                Arg::with_name("aggregated-public-key")
+                    .long("aggregated-public-key")
+                    .required(true)
+                    .takes_value(true)
+                    .help("aggregated public key of the federation which signs the genesis block"),
+                Arg::with_name("address")
+                    .long("address")
+                    .required(true)
+                    .takes_value(true)
+                    .help("address to which the coinbase of the genesis block pays"),
+                Arg::with_name("time").long("time").takes_value(true).help(
+                    "timestamp of the genesis block in unix time. The default is the current time.",
+                ),
+            ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use tapyrus::consensus::encode::deserialize;
+
+    const ADDRESS: &str = "1Co1dFUNuYXY4izSNM9t71VpuUaYdMfq3S";
+
+    #[test]
+    fn test_genesis_block() {
+        let address = Address::from_str(ADDRESS).unwrap();
+        let block = genesis_block(TEST_KEYS.aggregated(), &address, 1_562_925_929);
+
+        assert_eq!(block.header.prev_blockhash, BlockHash::default());
+        assert_eq!(block.header.time, 1_562_925_929);
+        assert_eq!(
+            block.header.xfield,
+            XField::AggregatePublicKey(TEST_KEYS.aggregated())
+        );
+        assert!(block.header.proof.is_none());
+        assert_eq!(block.header.merkle_root, block.merkle_root());
+        assert_eq!(block.header.im_merkle_root, block.immutable_merkle_root());
+
+        let coinbase = &block.txdata[0];
+        assert!(coinbase.is_coin_base());
+        assert_eq!(coinbase.output[0].value, GENESIS_REWARD);
+        assert_eq!(coinbase.output[0].script_pubkey, address.script_pubkey());
+
+        let decoded: Block = deserialize(&serialize(&block)).unwrap();
+        assert_eq!(decoded, block);
+    }
+
+    #[test]
+    fn test_execute() {
+        let matches = CreateGenesisCommand::args().get_matches_from(vec![
+            "creategenesis",
+            "--aggregated-public-key",
+            "x",
+            "--address",
+            ADDRESS,
+            "--time",
+            "1562925929",
+        ]);
+        let response = CreateGenesisCommand::execute(&matches);
+        assert_eq!(
+            format!("{}", response.err().unwrap()),
+            "InvalidArgs(\"aggregated-public-key\")"
+        );
+
+        let aggregated_public_key = TEST_KEYS.aggregated().to_string();
+        let matches = CreateGenesisCommand::args().get_matches_from(vec![
+            "creategenesis",
+            "--aggregated-public-key",
+            &aggregated_public_key,
+            "--address",
+            ADDRESS,
+            "--time",
+            "1562925929",
+        ]);
+        let response = CreateGenesisCommand::execute(&matches).unwrap();
+        let block = genesis_block(
+            TEST_KEYS.aggregated(),
+            &Address::from_str(ADDRESS).unwrap(),
+            1_562_925_929,
+        );
+        assert_eq!(format!("{}", response), hex::encode(serialize(&block)));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::cli::setup::traits::Response;
+use crate::cli::{write_json, OutputFormat};
 use crate::errors::Error;
 use crate::key::generate_key_pair;
 use clap::{App, ArgMatches, SubCommand};
@@ -8,13 +9,15 @@ use tapyrus::{PrivateKey, PublicKey};
 pub struct CreateKeyResponse {
     private_key: PrivateKey,
     public_key: PublicKey,
+    format: OutputFormat,
 }
 
 impl CreateKeyResponse {
-    fn new(private_key: PrivateKey, public_key: PublicKey) -> Self {
+    fn new(private_key: PrivateKey, public_key: PublicKey, format: OutputFormat) -> Self {
         CreateKeyResponse {
             private_key: private_key,
             public_key: public_key,
+            format: format,
         }
     }
 }
@@ -23,6 +26,16 @@ impl Response for CreateKeyResponse {}
 
 impl fmt::Display for CreateKeyResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            return write_json(
+                f,
+                &serde_json::json!({
+                    "private_key": self.private_key.to_wif(),
+                    "public_key": hex::encode(&self.public_key.key.serialize()[..]),
+                }),
+            );
+        }
+
         write!(
             f,
             "{} {}",
@@ -35,9 +48,13 @@ impl fmt::Display for CreateKeyResponse {
 pub struct CreateKeyCommand {}
 
 impl<'a> CreateKeyCommand {
-    pub fn execute(_matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
         let (private_key, public_key) = generate_key_pair();
-        Ok(Box::new(CreateKeyResponse::new(private_key, public_key)))
+        Ok(Box::new(CreateKeyResponse::new(
+            private_key,
+            public_key,
+            OutputFormat::from_matches(matches),
+        )))
     }
     pub fn args<'b>() -> App<'a, 'b> {
         SubCommand::with_name("createkey")
@@ -58,7 +75,27 @@ mod test {
             "0369d39154c0d011db02085392142e369d920c2531fe38e14484546ee6713465d6",
         )
         .unwrap();
-        let response = CreateKeyResponse::new(private_key, public_key);
+        let response = CreateKeyResponse::new(private_key, public_key, OutputFormat::Human);
         assert_eq!(format!("{}", response), "KzZtqg6QKr4TaifSWNsSXfH1h7eHDKJL7rGV3w9Tx2tL83GhPdLv 0369d39154c0d011db02085392142e369d920c2531fe38e14484546ee6713465d6")
     }
+
+    #[test]
+    fn test_format_json() {
+        let private_key =
+            PrivateKey::from_wif("KzZtqg6QKr4TaifSWNsSXfH1h7eHDKJL7rGV3w9Tx2tL83GhPdLv").unwrap();
+        let public_key = PublicKey::from_str(
+            "0369d39154c0d011db02085392142e369d920c2531fe38e14484546ee6713465d6",
+        )
+        .unwrap();
+        let response = CreateKeyResponse::new(private_key, public_key, OutputFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&format!("{}", response)).unwrap();
+        assert_eq!(
+            json["private_key"],
+            "KzZtqg6QKr4TaifSWNsSXfH1h7eHDKJL7rGV3w9Tx2tL83GhPdLv"
+        );
+        assert_eq!(
+            json["public_key"],
+            "0369d39154c0d011db02085392142e369d920c2531fe38e14484546ee6713465d6"
+        );
+    }
 }
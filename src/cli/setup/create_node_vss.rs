@@ -1,4 +1,6 @@
 use crate::cli::setup::traits::Response;
+use crate::cli::setup::values_of;
+use crate::cli::{write_json, OutputFormat};
 use crate::crypto::vss::{Commitment, Vss};
 use crate::errors::Error;
 use crate::rpc::Rpc;
@@ -11,11 +13,15 @@ use tapyrus::{PrivateKey, PublicKey};
 
 pub struct CreateNodeVssResponse {
     vss: BTreeMap<PublicKey, Vss>,
+    format: OutputFormat,
 }
 
 impl CreateNodeVssResponse {
-    fn new(vss: BTreeMap<PublicKey, Vss>) -> Self {
-        CreateNodeVssResponse { vss: vss }
+    fn new(vss: BTreeMap<PublicKey, Vss>, format: OutputFormat) -> Self {
+        CreateNodeVssResponse {
+            vss: vss,
+            format: format,
+        }
     }
 }
 
@@ -23,6 +29,20 @@ impl Response for CreateNodeVssResponse {}
 
 impl fmt::Display for CreateNodeVssResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            let vss: Vec<serde_json::Value> = self
+                .vss
+                .iter()
+                .map(|(k, v)| {
+                    serde_json::json!({
+                        "receiver_public_key": k.to_string(),
+                        "vss": v.to_string(),
+                    })
+                })
+                .collect();
+            return write_json(f, &serde_json::json!({ "vss": vss }));
+        }
+
         let c = self
             .vss
             .iter()
@@ -42,9 +62,8 @@ impl<'a> CreateNodeVssCommand {
             .and_then(|key| PrivateKey::from_wif(key).ok())
             .ok_or(Error::InvalidArgs("private-key".to_string()))?;
 
-        let mut public_keys: Vec<PublicKey> = matches
-            .values_of("public-key")
-            .ok_or(Error::InvalidArgs("public-key".to_string()))?
+        let mut public_keys: Vec<PublicKey> = values_of(matches, "public-key")?
+            .iter()
             .map(|key| PublicKey::from_str(key).map_err(|_| Error::InvalidKey))
             .collect::<Result<Vec<PublicKey>, _>>()?;
         NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
@@ -78,17 +97,26 @@ impl<'a> CreateNodeVssCommand {
             );
             vss_map.insert(public_keys[j].clone(), vss);
         }
-        Ok(Box::new(CreateNodeVssResponse::new(vss_map)))
+        Ok(Box::new(CreateNodeVssResponse::new(
+            vss_map,
+            OutputFormat::from_matches(matches),
+        )))
     }
 
     pub fn args<'b>() -> App<'a, 'b> {
         SubCommand::with_name("createnodevss").args(&[
             Arg::with_name("public-key")
                 .long("public-key")
-                .required(true)
+                .required_unless("public-key-file")
                 .multiple(true)
                 .takes_value(true)
                 .help("compressed public key of the each signer with a hex format string"),
+            Arg::with_name("public-key-file")
+                .long("public-key-file")
+                .multiple(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file or directory of files which has a public key in each line"),
             Arg::with_name("private-key")
                 .long("private-key")
                 .required(true)
@@ -126,6 +154,46 @@ mod tests {
         assert!(response.is_ok());
     }
 
+    #[test]
+    fn test_execute_with_file_and_json() {
+        let file = std::env::temp_dir().join(format!(
+            "tapyrus-setup-public-keys-{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(
+            &file,
+            "03e568e3a5641ac21930b51f92fb6dd201fb46faae560b108cf3a96380da08dee1\n\
+             02a1c8965ed06987fa6d7e0f552db707065352283ab3c1471510b12a76a5905287\n",
+        )
+        .unwrap();
+        let matches = CreateNodeVssCommand::args()
+            .args(&crate::cli::setup::global_args())
+            .get_matches_from(vec![
+                "createnodevss",
+                "--format",
+                "json",
+                "--threshold",
+                "2",
+                "--public-key",
+                "03842d51608d08bee79587fb3b54ea68f5279e13fac7d72515a7205e6672858ca2",
+                "--public-key-file",
+                file.to_str().unwrap(),
+                "--private-key",
+                "cQYYBMFS9dRR3Mt16gW4jixCqSiMhCwuDMHUBs6WeHMTxMnsq8Gh",
+            ]);
+        let response = CreateNodeVssCommand::execute(&matches).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&format!("{}", response)).unwrap();
+        let vss = json["vss"].as_array().unwrap();
+        assert_eq!(vss.len(), 3);
+        for v in vss {
+            let receiver = v["receiver_public_key"].as_str().unwrap();
+            let vss = Vss::from_str(v["vss"].as_str().unwrap()).unwrap();
+            assert_eq!(vss.receiver_public_key.to_string(), receiver);
+        }
+    }
+
     #[test]
     fn test_execute_invalid_public_key() {
         let matches = CreateNodeVssCommand::args().get_matches_from(vec![
@@ -0,0 +1,127 @@
+use crate::cli::setup::create_federation::{append_federation, FederationResponse};
+use crate::cli::setup::traits::Response;
+use crate::cli::setup::{redis_args, redis_options, session_nonce, session_nonce_arg, values_of};
+use crate::cli::OutputFormat;
+use crate::dkg::{run_ceremony, Ceremony, DkgCeremony};
+use crate::errors::Error;
+use crate::net::RedisManager;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::str::FromStr;
+use std::time::Duration;
+use tapyrus::{PrivateKey, PublicKey};
+
+pub struct DkgCommand {}
+
+impl<'a> DkgCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let private_key: PrivateKey = matches
+            .value_of("private-key")
+            .and_then(|key| PrivateKey::from_wif(key).ok())
+            .ok_or(Error::InvalidArgs("private-key".to_string()))?;
+
+        let public_keys: Vec<PublicKey> = values_of(matches, "public-key")?
+            .iter()
+            .map(|key| PublicKey::from_str(key).map_err(|_| Error::InvalidKey))
+            .collect::<Result<Vec<PublicKey>, _>>()?;
+
+        let threshold: u8 = matches
+            .value_of("threshold")
+            .and_then(|t| t.parse::<u8>().ok())
+            .ok_or(Error::InvalidArgs(
+                "threshold should be integer.".to_string(),
+            ))?;
+
+        let block_height: u64 = matches
+            .value_of("block-height")
+            .and_then(|t| t.parse::<u64>().ok())
+            .ok_or(Error::InvalidArgs(
+                "block-height should be integer.".to_string(),
+            ))?;
+
+        let timeout: u64 = matches
+            .value_of("timeout")
+            .and_then(|t| t.parse::<u64>().ok())
+            .ok_or(Error::InvalidArgs("timeout should be integer.".to_string()))?;
+
+        let federations_file = matches.value_of("federations-file");
+        // Check the file before the ceremony, so that the result is not lost.
+        let federations_toml = match federations_file {
+            Some(file) => Some(std::fs::read_to_string(file)?),
+            None => None,
+        };
+
+        let nonce = session_nonce(matches)?;
+        let mut ceremony =
+            DkgCeremony::new(&private_key, &public_keys, threshold, block_height, &nonce)?;
+        let connection_manager = RedisManager::with_options(redis_options(matches))?
+            .network_id(matches.value_of("network-id").map(str::to_string));
+        run_ceremony(
+            &mut ceremony,
+            &connection_manager,
+            Duration::from_secs(timeout),
+        )?;
+        let federation = ceremony
+            .federation()
+            .expect("The federation must be constructed when the ceremony finished.")
+            .clone();
+
+        if let (Some(file), Some(toml)) = (federations_file, federations_toml) {
+            append_federation(file, &toml, &federation, &ceremony.signer_id().pubkey)?;
+        }
+
+        Ok(Box::new(FederationResponse::new(
+            federation,
+            federations_file.map(|s| s.to_string()),
+            OutputFormat::from_matches(matches),
+        )))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("dkg")
+            .about("run distributed key generation with the other members of a new federation over the signer network. All members must run this command with the same public keys, threshold, block height and session nonce.")
+            .args(&[
+                Arg::with_name("public-key")
+                    .long("public-key")
+                    .required_unless("public-key-file")
+                    .multiple(true)
+                    .takes_value(true)
+                    .help("compressed public key of the each member of the new federation with a hex format string"),
+                Arg::with_name("public-key-file")
+                    .long("public-key-file")
+                    .multiple(true)
+                    .takes_value(true)
+                    .value_name("PATH")
+                    .help("file or directory of files which has a public key in each line"),
+                Arg::with_name("private-key")
+                    .long("private-key")
+                    .required(true)
+                    .takes_value(true)
+                    .help("private key of this signer with an extend WIF format"),
+                Arg::with_name("threshold")
+                    .long("threshold")
+                    .required(true)
+                    .takes_value(true)
+                    .help("the minimum number of signers required to sign block"),
+                Arg::with_name("block-height")
+                    .long("block-height")
+                    .required(true)
+                    .takes_value(true)
+                    .help("block height from which the new federation signs blocks"),
+                Arg::with_name("federations-file")
+                    .long("federations-file")
+                    .takes_value(true)
+                    .help("path to the federations toml file. The new federation is appended to it as a pending federation."),
+                Arg::with_name("network-id")
+                    .long("network-id")
+                    .takes_value(true)
+                    .help("identifier of the signer network, which must be the same as `network-id` of tapyrus-signerd"),
+                session_nonce_arg(),
+                Arg::with_name("timeout")
+                    .long("timeout")
+                    .takes_value(true)
+                    .default_value("600")
+                    .help("time limit(sec) to wait for the other members"),
+            ])
+            .args(&redis_args())
+    }
+}
@@ -1,20 +1,195 @@
+use crate::command_args::{
+    RedisCommandArgs, RedisConfig, ENV_NAME_REDIS_PASS, ENV_NAME_REDIS_SENTINEL_PASS,
+};
 use crate::crypto::vss::Vss;
+use crate::dkg::{new_session_nonce, SESSION_NONCE_LEN};
+use crate::errors::Error;
 use crate::net::SignerID;
+use crate::redis_client::RedisOptions;
 use crate::signer_node::BidirectionalSharedSecretMap;
 use crate::signer_node::SharedSecret;
 use crate::signer_node::SharedSecretMap;
+use clap::{Arg, ArgMatches};
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
     ShamirSecretSharing, VerifiableSS,
 };
+use std::path::Path;
 use tapyrus::{PrivateKey, PublicKey};
 
 pub mod aggregate;
 pub mod compute_sig;
 pub mod create_block_vss;
+pub mod create_federation;
+pub mod create_genesis;
 pub mod create_key;
 pub mod create_node_vss;
+pub mod dkg;
+pub mod refresh;
 pub mod sign;
+pub mod sign_genesis;
 pub mod traits;
+pub mod verify;
+
+/// Arguments which all commands share.
+pub fn global_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![Arg::with_name("format")
+        .long("format")
+        .global(true)
+        .takes_value(true)
+        .possible_values(&["text", "json"])
+        .default_value("text")
+        .help("output format. `json` prints the result as JSON with named fields")]
+}
+
+/// Returns the values of the argument `name` and the values in the files of the argument
+/// `<name>-file`. A directory is read as the files in it in the order of the file names. Each
+/// non-empty line of the files is a value.
+pub fn values_of(matches: &ArgMatches, name: &str) -> Result<Vec<String>, Error> {
+    let mut values: Vec<String> = matches
+        .values_of(name)
+        .map(|values| values.map(|v| v.to_string()).collect())
+        .unwrap_or_default();
+    if let Some(paths) = matches.values_of(&format!("{}-file", name)) {
+        for path in paths {
+            values.extend(read_values(Path::new(path))?);
+        }
+    }
+    Ok(values)
+}
+
+/// Returns the value of the argument `name` or the value in the file of the argument
+/// `<name>-file`.
+pub fn value_of(matches: &ArgMatches, name: &str) -> Result<Option<String>, Error> {
+    let mut values = values_of(matches, name)?;
+    match values.len() {
+        0 => Ok(None),
+        1 => Ok(values.pop()),
+        _ => Err(Error::InvalidArgs(format!("{} should be one value.", name))),
+    }
+}
+
+/// The arguments of the Redis of the signer network which `dkg` and `refresh` share. They are the
+/// same as those of tapyrus-signerd, and the passwords are also read from the environment variables
+/// `TAPYRUS_SIGNER_REDIS_PASS` and `TAPYRUS_SIGNER_REDIS_SENTINEL_PASS`.
+pub fn redis_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("redis-host")
+            .long("redis-host")
+            .takes_value(true)
+            .default_value("127.0.0.1")
+            .help("Redis host of the signer network"),
+        Arg::with_name("redis-port")
+            .long("redis-port")
+            .takes_value(true)
+            .default_value("6379")
+            .help("Redis port of the signer network"),
+        Arg::with_name("redis-user")
+            .long("redis-user")
+            .takes_value(true)
+            .help("Redis ACL user name"),
+        Arg::with_name("redis-tls")
+            .long("redis-tls")
+            .help("connect to Redis with TLS, as `rediss://`"),
+        Arg::with_name("redis-ca-cert")
+            .long("redis-ca-cert")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("path to the PEM file of the CA certificate which Redis is verified with. It is used with --redis-tls."),
+        Arg::with_name("redis-sentinels")
+            .long("redis-sentinels")
+            .takes_value(true)
+            .value_name("HOST:PORT,...")
+            .help("comma separated Redis Sentinel addresses. If it is specified, the primary is looked up from the Sentinels and --redis-host and --redis-port are ignored."),
+        Arg::with_name("redis-sentinel-master")
+            .long("redis-sentinel-master")
+            .takes_value(true)
+            .help("name of the primary monitored by Redis Sentinels. The default is `mymaster`."),
+    ]
+}
+
+/// Returns the settings of the connection to Redis from the arguments of `redis_args`.
+pub fn redis_options(matches: &ArgMatches) -> RedisOptions {
+    RedisConfig {
+        command_args: RedisCommandArgs {
+            host: matches.value_of("redis-host"),
+            port: matches.value_of("redis-port"),
+            username: matches.value_of("redis-user"),
+            password: std::env::var(ENV_NAME_REDIS_PASS).ok(),
+            tls: matches.is_present("redis-tls"),
+            ca_cert: matches.value_of("redis-ca-cert"),
+            sentinels: matches.value_of("redis-sentinels"),
+            sentinel_master: matches.value_of("redis-sentinel-master"),
+            sentinel_password: std::env::var(ENV_NAME_REDIS_SENTINEL_PASS).ok(),
+        },
+        toml_config: None,
+    }
+    .options()
+}
+
+/// The argument of the session nonce which `dkg` and `refresh` share.
+pub fn session_nonce_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("session-nonce")
+        .long("session-nonce")
+        .takes_value(true)
+        .help("session nonce of the run with a hex format string. One member runs the command without it, and the other members run with the nonce which it prints.")
+}
+
+/// Returns the session nonce of the argument `session-nonce`. If it is not given, a new nonce is
+/// created and printed to stderr, so that the member can announce it to the others.
+pub fn session_nonce(matches: &ArgMatches) -> Result<[u8; SESSION_NONCE_LEN], Error> {
+    match matches.value_of("session-nonce") {
+        Some(hex) => {
+            let bytes = hex::decode(hex).map_err(|_| {
+                Error::InvalidArgs("session-nonce should be a hex string.".to_string())
+            })?;
+            if bytes.len() != SESSION_NONCE_LEN {
+                return Err(Error::InvalidArgs(format!(
+                    "session-nonce should be {} bytes.",
+                    SESSION_NONCE_LEN
+                )));
+            }
+            let mut nonce = [0u8; SESSION_NONCE_LEN];
+            nonce.copy_from_slice(&bytes);
+            Ok(nonce)
+        }
+        None => {
+            let nonce = new_session_nonce();
+            eprintln!(
+                "Started a new session. Run the command on the other members with --session-nonce={}",
+                hex::encode(&nonce)
+            );
+            Ok(nonce)
+        }
+    }
+}
+
+fn read_values(path: &Path) -> Result<Vec<String>, Error> {
+    let mut files = vec![];
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            let file = entry?.path();
+            if file.is_file() {
+                files.push(file);
+            }
+        }
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    let mut values = vec![];
+    for file in files {
+        let content = std::fs::read_to_string(&file)?;
+        values.extend(
+            content
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string()),
+        );
+    }
+    Ok(values)
+}
 
 pub fn index_of(private_key: &PrivateKey, public_keys: &Vec<PublicKey>) -> usize {
     let secp = tapyrus::secp256k1::Secp256k1::new();
@@ -94,9 +269,117 @@ pub fn vss_to_bidirectional_shared_secret_map(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
     use std::str::FromStr;
     use tapyrus::{PrivateKey, PublicKey};
 
+    #[test]
+    fn test_values_of() {
+        let dir = std::env::temp_dir().join(format!("tapyrus-setup-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.txt"), "vss3\n").unwrap();
+        std::fs::write(dir.join("a.txt"), "vss1\n\n  vss2  \n").unwrap();
+        let file =
+            std::env::temp_dir().join(format!("tapyrus-setup-test-{}.txt", std::process::id()));
+        std::fs::write(&file, "block\n").unwrap();
+
+        let app = clap::App::new("test").args(&[
+            Arg::with_name("vss")
+                .long("vss")
+                .multiple(true)
+                .takes_value(true),
+            Arg::with_name("vss-file")
+                .long("vss-file")
+                .multiple(true)
+                .takes_value(true),
+            Arg::with_name("block").long("block").takes_value(true),
+            Arg::with_name("block-file")
+                .long("block-file")
+                .takes_value(true),
+        ]);
+        let matches = app.get_matches_from(vec![
+            "test",
+            "--vss",
+            "vss0",
+            "--vss-file",
+            dir.to_str().unwrap(),
+            "--block-file",
+            file.to_str().unwrap(),
+        ]);
+        assert_eq!(
+            values_of(&matches, "vss").unwrap(),
+            vec!["vss0", "vss1", "vss2", "vss3"]
+        );
+        assert_eq!(
+            value_of(&matches, "block").unwrap(),
+            Some("block".to_string())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_redis_options() {
+        let app = clap::App::new("test").args(&redis_args());
+        let options = redis_options(&app.clone().get_matches_from(vec!["test"]));
+        assert_eq!(options.host, "127.0.0.1");
+        assert_eq!(options.port, 6379);
+        assert_eq!(options.tls, false);
+        assert_eq!(options.sentinel, None);
+
+        let options = redis_options(&app.get_matches_from(vec![
+            "test",
+            "--redis-user=signer",
+            "--redis-tls",
+            "--redis-ca-cert=/etc/tapyrus-signer/redis-ca.pem",
+            "--redis-sentinels=192.168.0.10:26379,192.168.0.11:26379",
+        ]));
+        assert_eq!(options.username, Some("signer".to_string()));
+        assert_eq!(options.tls, true);
+        assert_eq!(
+            options.ca_cert,
+            Some(PathBuf::from("/etc/tapyrus-signer/redis-ca.pem"))
+        );
+        let sentinel = options.sentinel.unwrap();
+        assert_eq!(sentinel.master_name, "mymaster");
+        assert_eq!(
+            sentinel.addresses,
+            vec!["192.168.0.10:26379", "192.168.0.11:26379"]
+        );
+    }
+
+    #[test]
+    fn test_session_nonce() {
+        let app = clap::App::new("test").arg(session_nonce_arg());
+        let matches = app.clone().get_matches_from(vec![
+            "test",
+            "--session-nonce",
+            "000102030405060708090a0b0c0d0e0f",
+        ]);
+        assert_eq!(
+            session_nonce(&matches).unwrap(),
+            [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+        );
+
+        // A new nonce is created for each run.
+        let matches = app.clone().get_matches_from(vec!["test"]);
+        assert_ne!(
+            session_nonce(&matches).unwrap(),
+            session_nonce(&matches).unwrap()
+        );
+
+        for invalid in &["0001", "zz"] {
+            let matches = app
+                .clone()
+                .get_matches_from(vec!["test", "--session-nonce", invalid]);
+            match session_nonce(&matches) {
+                Err(Error::InvalidArgs(_)) => {}
+                _ => panic!("session-nonce {} should be invalid", invalid),
+            }
+        }
+    }
+
     #[test]
     fn test_index_of() {
         let private_key =
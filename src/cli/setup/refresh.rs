@@ -0,0 +1,109 @@
+use crate::cli::setup::create_federation::{append_federation, FederationResponse};
+use crate::cli::setup::traits::Response;
+use crate::cli::setup::{redis_args, redis_options, session_nonce, session_nonce_arg};
+use crate::cli::OutputFormat;
+use crate::dkg::{run_ceremony, Ceremony, RefreshCeremony};
+use crate::errors::Error;
+use crate::federation::Federations;
+use crate::net::RedisManager;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use std::time::Duration;
+use tapyrus::{PrivateKey, PublicKey};
+
+pub struct RefreshCommand {}
+
+impl<'a> RefreshCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let private_key: PrivateKey = matches
+            .value_of("private-key")
+            .and_then(|key| PrivateKey::from_wif(key).ok())
+            .ok_or(Error::InvalidArgs("private-key".to_string()))?;
+
+        let block_height: u64 = matches
+            .value_of("block-height")
+            .and_then(|t| t.parse::<u64>().ok())
+            .ok_or(Error::InvalidArgs(
+                "block-height should be integer.".to_string(),
+            ))?;
+
+        let timeout: u64 = matches
+            .value_of("timeout")
+            .and_then(|t| t.parse::<u64>().ok())
+            .ok_or(Error::InvalidArgs("timeout should be integer.".to_string()))?;
+
+        let federations_file = matches
+            .value_of("federations-file")
+            .ok_or(Error::InvalidArgs("federations-file".to_string()))?;
+        let federations_toml = std::fs::read_to_string(federations_file)?;
+        let secp = tapyrus::secp256k1::Secp256k1::new();
+        let public_key = PublicKey::from_private_key(&secp, &private_key);
+        let federations = Federations::from_pubkey_and_toml(&public_key, &federations_toml)?;
+        let current = federations.get_by_block_height(block_height);
+        if current.block_height() == block_height {
+            return Err(Error::InvalidArgs(format!(
+                "the federation at block height {} already exists.",
+                block_height
+            )));
+        }
+
+        let nonce = session_nonce(matches)?;
+        let mut ceremony = RefreshCeremony::new(&private_key, current, block_height, &nonce)?;
+        let connection_manager = RedisManager::with_options(redis_options(matches))?
+            .network_id(matches.value_of("network-id").map(str::to_string));
+        run_ceremony(
+            &mut ceremony,
+            &connection_manager,
+            Duration::from_secs(timeout),
+        )?;
+        let federation = ceremony
+            .federation()
+            .expect("The federation must be constructed when the ceremony finished.")
+            .clone();
+
+        append_federation(
+            federations_file,
+            &federations_toml,
+            &federation,
+            &ceremony.signer_id().pubkey,
+        )?;
+
+        Ok(Box::new(FederationResponse::new(
+            federation,
+            Some(federations_file.to_string()),
+            OutputFormat::from_matches(matches),
+        )))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("refresh")
+            .about("refresh the node secret shares of the federation with the other members over the signer network. The aggregated public key is not changed. All members must run this command with the same block height and session nonce.")
+            .args(&[
+                Arg::with_name("private-key")
+                    .long("private-key")
+                    .required(true)
+                    .takes_value(true)
+                    .help("private key of this signer with an extend WIF format"),
+                Arg::with_name("block-height")
+                    .long("block-height")
+                    .required(true)
+                    .takes_value(true)
+                    .help("block height from which the refreshed shares are used. The federation which is active at the height is refreshed."),
+                Arg::with_name("federations-file")
+                    .long("federations-file")
+                    .required(true)
+                    .takes_value(true)
+                    .help("path to the federations toml file. The refreshed federation is appended to it as a pending federation."),
+                Arg::with_name("network-id")
+                    .long("network-id")
+                    .takes_value(true)
+                    .help("identifier of the signer network, which must be the same as `network-id` of tapyrus-signerd"),
+                session_nonce_arg(),
+                Arg::with_name("timeout")
+                    .long("timeout")
+                    .takes_value(true)
+                    .default_value("600")
+                    .help("time limit(sec) to wait for the other members"),
+            ])
+            .args(&redis_args())
+    }
+}
@@ -1,6 +1,8 @@
 use crate::cli::setup::index_of;
 use crate::cli::setup::traits::Response;
 use crate::cli::setup::vss_to_bidirectional_shared_secret_map;
+use crate::cli::setup::{value_of, values_of};
+use crate::cli::{write_json, OutputFormat};
 use crate::crypto::multi_party_schnorr::LocalSig;
 use crate::crypto::multi_party_schnorr::SharedKeys;
 use crate::crypto::vss::Vss;
@@ -22,13 +24,15 @@ use tapyrus::{PrivateKey, PublicKey};
 pub struct SignResponse {
     local_sig: LocalSig,
     public_key: PublicKey,
+    format: OutputFormat,
 }
 
 impl SignResponse {
-    fn new(local_sig: LocalSig, public_key: PublicKey) -> Self {
+    fn new(local_sig: LocalSig, public_key: PublicKey, format: OutputFormat) -> Self {
         SignResponse {
             local_sig: local_sig,
             public_key: public_key,
+            format: format,
         }
     }
 }
@@ -37,16 +41,74 @@ impl Response for SignResponse {}
 
 impl fmt::Display for SignResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{:0>64}{:0>64}{}",
-            self.local_sig.gamma_i.to_big_int().to_hex(),
-            self.local_sig.e.to_big_int().to_hex(),
-            hex::encode(&self.public_key.key.serialize()[..]),
-        )
+        let gamma_i = format!("{:0>64}", self.local_sig.gamma_i.to_big_int().to_hex());
+        let e = format!("{:0>64}", self.local_sig.e.to_big_int().to_hex());
+        let public_key = hex::encode(&self.public_key.key.serialize()[..]);
+        if self.format == OutputFormat::Json {
+            // `sig` is the value for `--sig` of `computesig`.
+            return write_json(
+                f,
+                &serde_json::json!({
+                    "gamma_i": gamma_i,
+                    "e": e,
+                    "public_key": public_key,
+                    "sig": format_local_sig(&self.local_sig, &self.public_key),
+                }),
+            );
+        }
+        write!(f, "{}", format_local_sig(&self.local_sig, &self.public_key))
     }
 }
 
+/// Formats the local signature as the value for `--sig` of `computesig`, which is gamma_i, e and
+/// the public key of the signer in hex.
+pub fn format_local_sig(local_sig: &LocalSig, public_key: &PublicKey) -> String {
+    format!(
+        "{:0>64}{:0>64}{}",
+        local_sig.gamma_i.to_big_int().to_hex(),
+        local_sig.e.to_big_int().to_hex(),
+        hex::encode(&public_key.key.serialize()[..])
+    )
+}
+
+/// Creates the local signature of the signer of `private_key` from the block VSSs which it
+/// received from all signers.
+pub fn create_local_sig(
+    private_key: &PrivateKey,
+    threshold: usize,
+    block: &Block,
+    node_secret_share: FE,
+    aggregated_public_key: &PublicKey,
+    block_vss_vec: &Vec<Vss>,
+) -> Result<(LocalSig, PublicKey), Error> {
+    let mut public_keys: Vec<PublicKey> = block_vss_vec
+        .iter()
+        .map(|vss| vss.sender_public_key)
+        .collect();
+    NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
+    let index = index_of(private_key, &public_keys);
+
+    let params = ShamirSecretSharing {
+        threshold: (threshold - 1) as usize,
+        share_count: public_keys.len(),
+    };
+    let shared_block_secrets = vss_to_bidirectional_shared_secret_map(block_vss_vec, &params);
+
+    let bytes: Vec<u8> = aggregated_public_key.key.serialize_uncompressed().to_vec();
+    let point = GE::from_bytes(&bytes[1..]).expect("failed to convert to point");
+    let priv_shared_keys = SharedKeys {
+        y: point,
+        x_i: node_secret_share,
+    };
+
+    let (_, _, local_sig) =
+        Vss::create_local_sig_from_shares(&priv_shared_keys, index, &shared_block_secrets, block)?;
+
+    let secp = tapyrus::secp256k1::Secp256k1::new();
+    let public_key = PublicKey::from_private_key(&secp, private_key);
+    Ok((local_sig, public_key))
+}
+
 pub struct SignCommand {}
 
 impl<'a> SignCommand {
@@ -61,8 +123,7 @@ impl<'a> SignCommand {
             .and_then(|s| s.parse::<usize>().ok())
             .ok_or(Error::InvalidArgs("threshold".to_string()))?;
 
-        let block: Block = matches
-            .value_of("block")
+        let block: Block = value_of(matches, "block")?
             .and_then(|s| hex::decode(s).ok())
             .and_then(|hex| deserialize::<Block>(&hex).ok())
             .ok_or(Error::InvalidArgs("block".to_string()))?;
@@ -78,42 +139,24 @@ impl<'a> SignCommand {
             .and_then(|hex| PublicKey::from_str(hex).ok())
             .ok_or(Error::InvalidArgs("aggregated-public-key".to_string()))?;
 
-        let block_vss_vec: Vec<Vss> = matches
-            .values_of("block-vss")
-            .ok_or(Error::InvalidArgs("block-vss".to_string()))?
+        let block_vss_vec: Vec<Vss> = values_of(matches, "block-vss")?
+            .iter()
             .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidArgs("block-vss".to_string())))
             .collect::<Result<Vec<Vss>, _>>()?;
 
-        let mut public_keys: Vec<PublicKey> = block_vss_vec
-            .iter()
-            .map(|vss| vss.sender_public_key)
-            .collect();
-        NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
-        let index = index_of(&private_key, &public_keys);
-
-        let params = ShamirSecretSharing {
-            threshold: (threshold - 1) as usize,
-            share_count: public_keys.len(),
-        };
-        let shared_block_secrets = vss_to_bidirectional_shared_secret_map(&block_vss_vec, &params);
-
-        let bytes: Vec<u8> = aggregated_public_key.key.serialize_uncompressed().to_vec();
-        let point = GE::from_bytes(&bytes[1..]).expect("failed to convert to point");
-        let priv_shared_keys = SharedKeys {
-            y: point,
-            x_i: node_secret_share,
-        };
-
-        let (_, _, local_sig) = Vss::create_local_sig_from_shares(
-            &priv_shared_keys,
-            index,
-            &shared_block_secrets,
+        let (local_sig, public_key) = create_local_sig(
+            &private_key,
+            threshold,
             &block,
+            node_secret_share,
+            &aggregated_public_key,
+            &block_vss_vec,
         )?;
-
-        let secp = tapyrus::secp256k1::Secp256k1::new();
-        let public_key = PublicKey::from_private_key(&secp, &private_key);
-        Ok(Box::new(SignResponse::new(local_sig, public_key)))
+        Ok(Box::new(SignResponse::new(
+            local_sig,
+            public_key,
+            OutputFormat::from_matches(matches),
+        )))
     }
 
     pub fn args<'b>() -> App<'a, 'b> {
@@ -130,9 +173,14 @@ impl<'a> SignCommand {
                 .help("the minimum number of signers required to sign block"),
             Arg::with_name("block")
                 .long("block")
-                .required(true)
+                .required_unless("block-file")
                 .takes_value(true)
                 .help("block to be signed as a hex string format"),
+            Arg::with_name("block-file")
+                .long("block-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file which has the block to be signed as a hex string format"),
             Arg::with_name("node-secret-share")
                 .long("node-secret-share")
                 .required(true)
@@ -145,10 +193,16 @@ impl<'a> SignCommand {
                 .help("aggregated public key of all signers"),
             Arg::with_name("block-vss")
                 .long("block-vss")
-                .required(true)
+                .required_unless("block-vss-file")
                 .multiple(true)
                 .takes_value(true)
                 .help("the block VSSs generated by tapyrus-setup createblockvss command"),
+            Arg::with_name("block-vss-file")
+                .long("block-vss-file")
+                .multiple(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .help("file or directory of files which has a block VSS in each line"),
         ])
     }
 }
@@ -157,6 +211,40 @@ impl<'a> SignCommand {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_json() {
+        let local_sig = LocalSig {
+            gamma_i: ECScalar::from(&BigInt::from(1)),
+            e: ECScalar::from(&BigInt::from(2)),
+        };
+        let public_key = PublicKey::from_str(
+            "03842d51608d08bee79587fb3b54ea68f5279e13fac7d72515a7205e6672858ca2",
+        )
+        .unwrap();
+        let text = format!(
+            "{}",
+            SignResponse::new(local_sig.clone(), public_key, OutputFormat::Human)
+        );
+        let json = format!(
+            "{}",
+            SignResponse::new(local_sig, public_key, OutputFormat::Json)
+        );
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            json["gamma_i"],
+            "0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(
+            json["e"],
+            "0000000000000000000000000000000000000000000000000000000000000002"
+        );
+        assert_eq!(
+            json["public_key"],
+            "03842d51608d08bee79587fb3b54ea68f5279e13fac7d72515a7205e6672858ca2"
+        );
+        assert_eq!(json["sig"], text);
+    }
+
     #[test]
     fn test_execute() {
         let matches = SignCommand::args().get_matches_from(vec![
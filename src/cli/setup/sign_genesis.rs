@@ -0,0 +1,498 @@
+use crate::cli::setup::compute_sig::{compute_sig, parse_local_sig};
+use crate::cli::setup::create_block_vss::create_block_vss;
+use crate::cli::setup::read_values;
+use crate::cli::setup::sign::{create_local_sig, format_local_sig};
+use crate::cli::setup::traits::Response;
+use crate::cli::setup::{value_of, values_of};
+use crate::cli::{write_json, OutputFormat};
+use crate::crypto::multi_party_schnorr::LocalSig;
+use crate::crypto::vss::Vss;
+use crate::errors::Error;
+use crate::rpc::Rpc;
+use crate::sign::Sign;
+use crate::signer_node::NodeParameters;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use curv::arithmetic::traits::Converter;
+use curv::elliptic::curves::traits::ECScalar;
+use curv::{BigInt, FE};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tapyrus::blockdata::block::Block;
+use tapyrus::consensus::encode::{deserialize, serialize};
+use tapyrus::{PrivateKey, PublicKey};
+
+/// The directory in the exchange directory which has the nonce files. The nonce file from the
+/// sender to the receiver is `nonce/<receiver public key>/<sender public key>`.
+const NONCE_DIR: &str = "nonce";
+/// The directory in the exchange directory which has the signature share files. The file of the
+/// signer is `sig/<signer public key>`.
+const SIG_DIR: &str = "sig";
+/// The file in the exchange directory to which `combine` writes the genesis block with proof.
+const GENESIS_FILE: &str = "genesis";
+
+pub struct SignGenesisResponse {
+    files: Vec<PathBuf>,
+    format: OutputFormat,
+}
+
+impl SignGenesisResponse {
+    fn new(files: Vec<PathBuf>, format: OutputFormat) -> Self {
+        SignGenesisResponse { files, format }
+    }
+}
+
+impl Response for SignGenesisResponse {}
+
+impl fmt::Display for SignGenesisResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect();
+        if self.format == OutputFormat::Json {
+            return write_json(f, &serde_json::json!({ "files": files }));
+        }
+        write!(f, "{}", files.join("\n"))
+    }
+}
+
+fn nonce_dir(dir: &Path, receiver: &PublicKey) -> PathBuf {
+    dir.join(NONCE_DIR).join(receiver.to_string())
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, format!("{}\n", content))?;
+    Ok(())
+}
+
+/// Writes the nonce files of the signer, which are its block VSSs for each signer.
+pub fn write_nonce_files(
+    private_key: &PrivateKey,
+    public_keys: &Vec<PublicKey>,
+    threshold: usize,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, Error> {
+    let secp = tapyrus::secp256k1::Secp256k1::new();
+    let sender = PublicKey::from_private_key(&secp, private_key);
+    let mut files = vec![];
+    for (receiver, vss) in create_block_vss(private_key, public_keys, threshold) {
+        let file = nonce_dir(dir, &receiver).join(sender.to_string());
+        write_file(&file, &vss.to_string())?;
+        files.push(file);
+    }
+    Ok(files)
+}
+
+/// Reads the nonce files which the signer received from all signers.
+fn read_nonce_files(public_key: &PublicKey, dir: &Path) -> Result<Vec<Vss>, Error> {
+    let block_vss_vec = read_values(&nonce_dir(dir, public_key))?
+        .iter()
+        .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidArgs("nonce".to_string())))
+        .collect::<Result<Vec<Vss>, _>>()?;
+    if block_vss_vec
+        .iter()
+        .any(|vss| vss.receiver_public_key != *public_key)
+    {
+        return Err(Error::InvalidArgs(
+            "nonce file for another signer".to_string(),
+        ));
+    }
+    Ok(block_vss_vec)
+}
+
+/// Writes the signature share file of the signer from the nonce files which it received.
+pub fn write_sig_file(
+    private_key: &PrivateKey,
+    threshold: usize,
+    block: &Block,
+    node_secret_share: FE,
+    aggregated_public_key: &PublicKey,
+    dir: &Path,
+) -> Result<PathBuf, Error> {
+    let secp = tapyrus::secp256k1::Secp256k1::new();
+    let public_key = PublicKey::from_private_key(&secp, private_key);
+    let block_vss_vec = read_nonce_files(&public_key, dir)?;
+    let (local_sig, public_key) = create_local_sig(
+        private_key,
+        threshold,
+        block,
+        node_secret_share,
+        aggregated_public_key,
+        &block_vss_vec,
+    )?;
+    let file = dir.join(SIG_DIR).join(public_key.to_string());
+    write_file(&file, &format_local_sig(&local_sig, &public_key))?;
+    Ok(file)
+}
+
+/// Combines the signature share files of all signers into the proof and writes the genesis block
+/// with the proof. The proof is verified with the aggregated public key before it is written.
+pub fn write_genesis_file(
+    private_key: &PrivateKey,
+    threshold: usize,
+    block: Block,
+    node_secret_share: FE,
+    aggregated_public_key: &PublicKey,
+    node_vss_vec: &Vec<Vss>,
+    dir: &Path,
+) -> Result<PathBuf, Error> {
+    let secp = tapyrus::secp256k1::Secp256k1::new();
+    let public_key = PublicKey::from_private_key(&secp, private_key);
+    let block_vss_vec = read_nonce_files(&public_key, dir)?;
+    let keyed_local_sigs: Vec<(LocalSig, PublicKey)> = read_values(&dir.join(SIG_DIR))?
+        .iter()
+        .map(|s| parse_local_sig(s))
+        .collect::<Result<Vec<(LocalSig, PublicKey)>, Error>>()?;
+
+    if block_vss_vec.len() != node_vss_vec.len() {
+        return Err(Error::InvalidArgs(
+            "the number of nonce files should equal to the number of node VSSs".to_string(),
+        ));
+    }
+    if keyed_local_sigs.len() != node_vss_vec.len() {
+        return Err(Error::InvalidArgs(
+            "the number of signature share files should equal to the number of node VSSs"
+                .to_string(),
+        ));
+    }
+
+    let block = compute_sig(
+        private_key,
+        threshold,
+        aggregated_public_key,
+        node_secret_share,
+        block,
+        node_vss_vec,
+        &block_vss_vec,
+        keyed_local_sigs,
+    )?;
+    Sign::verify_proof(&block.header, aggregated_public_key)?;
+
+    let file = dir.join(GENESIS_FILE);
+    write_file(&file, &hex::encode(serialize(&block)))?;
+    Ok(file)
+}
+
+pub struct SignGenesisCommand {}
+
+impl<'a> SignGenesisCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let (step, step_matches) = match matches.subcommand() {
+            (step, Some(step_matches)) => (step, step_matches),
+            _ => return Err(Error::InvalidArgs("step".to_string())),
+        };
+        let files = match step {
+            "nonce" => Self::nonce(step_matches)?,
+            "sign" => vec![Self::sign(step_matches)?],
+            "combine" => vec![Self::combine(step_matches)?],
+            _ => return Err(Error::InvalidArgs("step".to_string())),
+        };
+        Ok(Box::new(SignGenesisResponse::new(
+            files,
+            OutputFormat::from_matches(step_matches),
+        )))
+    }
+
+    fn nonce(matches: &ArgMatches) -> Result<Vec<PathBuf>, Error> {
+        let mut public_keys: Vec<PublicKey> = values_of(matches, "public-key")?
+            .iter()
+            .map(|key| PublicKey::from_str(key).map_err(|_| Error::InvalidKey))
+            .collect::<Result<Vec<PublicKey>, _>>()?;
+        NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
+
+        write_nonce_files(
+            &private_key(matches)?,
+            &public_keys,
+            threshold(matches)?,
+            Path::new(matches.value_of("dir").expect("dir is required")),
+        )
+    }
+
+    fn sign(matches: &ArgMatches) -> Result<PathBuf, Error> {
+        write_sig_file(
+            &private_key(matches)?,
+            threshold(matches)?,
+            &block(matches)?,
+            node_secret_share(matches)?,
+            &aggregated_public_key(matches)?,
+            Path::new(matches.value_of("dir").expect("dir is required")),
+        )
+    }
+
+    fn combine(matches: &ArgMatches) -> Result<PathBuf, Error> {
+        let node_vss_vec: Vec<Vss> = values_of(matches, "node-vss")?
+            .iter()
+            .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidArgs("node-vss".to_string())))
+            .collect::<Result<Vec<Vss>, _>>()?;
+
+        write_genesis_file(
+            &private_key(matches)?,
+            threshold(matches)?,
+            block(matches)?,
+            node_secret_share(matches)?,
+            &aggregated_public_key(matches)?,
+            &node_vss_vec,
+            Path::new(matches.value_of("dir").expect("dir is required")),
+        )
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("signgenesis")
+            .about("sign the genesis block offline. Each signer runs `nonce`, `sign` and `combine` in order and exchanges the files which the steps write in the directory.")
+            .subcommand(
+                SubCommand::with_name("nonce")
+                    .about("write the nonce files of this signer to nonce/<receiver>/<this signer>. Send each file to its receiver.")
+                    .args(&[
+                        private_key_arg(),
+                        threshold_arg(),
+                        dir_arg(),
+                        Arg::with_name("public-key")
+                            .long("public-key")
+                            .required_unless("public-key-file")
+                            .multiple(true)
+                            .takes_value(true)
+                            .help("compressed public key of the each signer with a hex format string"),
+                        Arg::with_name("public-key-file")
+                            .long("public-key-file")
+                            .multiple(true)
+                            .takes_value(true)
+                            .value_name("PATH")
+                            .help("file or directory of files which has a public key in each line"),
+                    ]),
+            )
+            .subcommand(
+                SubCommand::with_name("sign")
+                    .about("read the nonce files in nonce/<this signer> and write the signature share file to sig/<this signer>. Send it to all signers.")
+                    .args(&signing_args()),
+            )
+            .subcommand(
+                SubCommand::with_name("combine")
+                    .about("combine the signature share files in sig into the proof and write the genesis block with the proof to genesis.")
+                    .args(&signing_args())
+                    .args(&[
+                        Arg::with_name("node-vss")
+                            .long("node-vss")
+                            .required_unless("node-vss-file")
+                            .multiple(true)
+                            .takes_value(true)
+                            .help("the node VSSs generated by tapyrus-setup createnodevss command"),
+                        Arg::with_name("node-vss-file")
+                            .long("node-vss-file")
+                            .multiple(true)
+                            .takes_value(true)
+                            .value_name("PATH")
+                            .help("file or directory of files which has a node VSS in each line"),
+                    ]),
+            )
+    }
+}
+
+fn private_key(matches: &ArgMatches) -> Result<PrivateKey, Error> {
+    matches
+        .value_of("private-key")
+        .and_then(|key| PrivateKey::from_wif(key).ok())
+        .ok_or(Error::InvalidArgs("private-key".to_string()))
+}
+
+fn threshold(matches: &ArgMatches) -> Result<usize, Error> {
+    matches
+        .value_of("threshold")
+        .and_then(|s| s.parse::<usize>().ok())
+        .ok_or(Error::InvalidArgs("threshold".to_string()))
+}
+
+fn block(matches: &ArgMatches) -> Result<Block, Error> {
+    value_of(matches, "block")?
+        .and_then(|s| hex::decode(s).ok())
+        .and_then(|hex| deserialize::<Block>(&hex).ok())
+        .ok_or(Error::InvalidArgs("block".to_string()))
+}
+
+fn node_secret_share(matches: &ArgMatches) -> Result<FE, Error> {
+    matches
+        .value_of("node-secret-share")
+        .and_then(|s| BigInt::from_str_radix(s, 16).ok())
+        .map(|i| ECScalar::from(&i))
+        .ok_or(Error::InvalidArgs("node-secret-share".to_string()))
+}
+
+fn aggregated_public_key(matches: &ArgMatches) -> Result<PublicKey, Error> {
+    matches
+        .value_of("aggregated-public-key")
+        .and_then(|hex| PublicKey::from_str(hex).ok())
+        .ok_or(Error::InvalidArgs("aggregated-public-key".to_string()))
+}
+
+fn private_key_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("private-key")
+        .long("private-key")
+        .required(true)
+        .takes_value(true)
+        .help("private key of this signer with an extend WIF format")
+}
+
+fn threshold_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("threshold")
+        .long("threshold")
+        .required(true)
+        .takes_value(true)
+        .help("the minimum number of signers required to sign block")
+}
+
+fn dir_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("dir")
+        .long("dir")
+        .required(true)
+        .takes_value(true)
+        .value_name("PATH")
+        .help("directory in which the files exchanged between the signers are")
+}
+
+/// Arguments which `sign` and `combine` share.
+fn signing_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        private_key_arg(),
+        threshold_arg(),
+        dir_arg(),
+        Arg::with_name("block")
+            .long("block")
+            .required_unless("block-file")
+            .takes_value(true)
+            .help("genesis block created by tapyrus-setup creategenesis as a hex string format"),
+        Arg::with_name("block-file")
+            .long("block-file")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("file which has the genesis block as a hex string format"),
+        Arg::with_name("node-secret-share")
+            .long("node-secret-share")
+            .required(true)
+            .takes_value(true)
+            .help("secret key share of the signers with a hex string format"),
+        Arg::with_name("aggregated-public-key")
+            .long("aggregated-public-key")
+            .required(true)
+            .takes_value(true)
+            .help("aggregated public key of all signers"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::setup::create_genesis::genesis_block;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use crate::tests::helper::node_vss::node_vss;
+    use tapyrus::Address;
+
+    const THRESHOLD: usize = 3;
+
+    fn secret_share(index: usize) -> FE {
+        node_vss(index)
+            .iter()
+            .fold(FE::zero(), |acc, vss| acc + &vss.positive_secret)
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tapyrus-setup-signgenesis-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_sign_genesis() {
+        let dir = test_dir("sign");
+        let public_keys = TEST_KEYS.pubkeys();
+        let block = genesis_block(
+            TEST_KEYS.aggregated(),
+            &Address::from_str("1Co1dFUNuYXY4izSNM9t71VpuUaYdMfq3S").unwrap(),
+            1_562_925_929,
+        );
+
+        for key in TEST_KEYS.key.iter() {
+            let files = write_nonce_files(key, &public_keys, THRESHOLD, &dir).unwrap();
+            assert_eq!(files.len(), public_keys.len());
+        }
+        for (i, key) in TEST_KEYS.key.iter().enumerate() {
+            write_sig_file(
+                key,
+                THRESHOLD,
+                &block,
+                secret_share(i),
+                &TEST_KEYS.aggregated(),
+                &dir,
+            )
+            .unwrap();
+        }
+        let file = write_genesis_file(
+            &TEST_KEYS.key[0],
+            THRESHOLD,
+            block.clone(),
+            secret_share(0),
+            &TEST_KEYS.aggregated(),
+            &node_vss(0),
+            &dir,
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        let genesis: Block = deserialize(&hex::decode(content.trim()).unwrap()).unwrap();
+        assert_eq!(
+            genesis.header.signature_hash(),
+            block.header.signature_hash()
+        );
+        assert!(Sign::verify_proof(&genesis.header, &TEST_KEYS.aggregated()).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_combine_without_all_sigs() {
+        let dir = test_dir("combine");
+        let public_keys = TEST_KEYS.pubkeys();
+        let block = genesis_block(
+            TEST_KEYS.aggregated(),
+            &Address::from_str("1Co1dFUNuYXY4izSNM9t71VpuUaYdMfq3S").unwrap(),
+            1_562_925_929,
+        );
+
+        for key in TEST_KEYS.key.iter() {
+            write_nonce_files(key, &public_keys, THRESHOLD, &dir).unwrap();
+        }
+        write_sig_file(
+            &TEST_KEYS.key[0],
+            THRESHOLD,
+            &block,
+            secret_share(0),
+            &TEST_KEYS.aggregated(),
+            &dir,
+        )
+        .unwrap();
+        let result = write_genesis_file(
+            &TEST_KEYS.key[0],
+            THRESHOLD,
+            block,
+            secret_share(0),
+            &TEST_KEYS.aggregated(),
+            &node_vss(0),
+            &dir,
+        );
+        match result {
+            Err(Error::InvalidArgs(_)) => assert!(true),
+            _ => assert!(false, "it should error"),
+        }
+        assert!(!dir.join(GENESIS_FILE).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
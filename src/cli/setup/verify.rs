@@ -0,0 +1,353 @@
+use crate::cli::setup::traits::Response;
+use crate::cli::setup::{value_of, values_of};
+use crate::cli::{write_json, OutputFormat};
+use crate::crypto::vss::Vss;
+use crate::errors::Error;
+use crate::rpc::Rpc;
+use crate::sign::Sign;
+use crate::signer_node::NodeParameters;
+use crate::util::sum_point;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use curv::elliptic::curves::traits::ECPoint;
+use curv::GE;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use tapyrus::blockdata::block::Block;
+use tapyrus::consensus::encode::deserialize;
+use tapyrus::PublicKey;
+
+/// The result of the successful verification. Verification failures are returned as
+/// `Error::VerificationFailed`.
+pub struct VerifyResponse {
+    message: String,
+    details: serde_json::Value,
+    format: OutputFormat,
+}
+
+impl VerifyResponse {
+    fn new(message: String, details: serde_json::Value, format: OutputFormat) -> Self {
+        VerifyResponse {
+            message: message,
+            details: details,
+            format: format,
+        }
+    }
+}
+
+impl Response for VerifyResponse {}
+
+impl fmt::Display for VerifyResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format == OutputFormat::Json {
+            let mut json = self.details.clone();
+            json["valid"] = serde_json::Value::Bool(true);
+            return write_json(f, &json);
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+fn node_vss_of(matches: &ArgMatches) -> Result<Vec<Vss>, Error> {
+    let vss_vec = values_of(matches, "vss")?
+        .iter()
+        .map(|s| Vss::from_str(s).map_err(|_| Error::InvalidSS))
+        .collect::<Result<Vec<Vss>, _>>()?;
+    if vss_vec.is_empty() {
+        return Err(Error::InvalidArgs("vss".to_string()));
+    }
+
+    let senders: HashSet<PublicKey> = vss_vec.iter().map(|v| v.sender_public_key).collect();
+    if senders.len() != vss_vec.len() {
+        return Err(Error::VerificationFailed(
+            "the node VSSs include the same sender twice.".to_string(),
+        ));
+    }
+    Ok(vss_vec)
+}
+
+fn aggregated_public_key_of(matches: &ArgMatches) -> Result<PublicKey, Error> {
+    matches
+        .value_of("aggregated-public-key")
+        .and_then(|hex| PublicKey::from_str(hex).ok())
+        .ok_or(Error::InvalidArgs("aggregated-public-key".to_string()))
+}
+
+fn vss_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("vss")
+            .long("vss")
+            .required_unless("vss-file")
+            .multiple(true)
+            .takes_value(true)
+            .help("node VSSs which the signer received from the all signers"),
+        Arg::with_name("vss-file")
+            .long("vss-file")
+            .multiple(true)
+            .takes_value(true)
+            .value_name("PATH")
+            .help("file or directory of files which has a node VSS in each line"),
+    ]
+}
+
+fn aggregated_public_key_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("aggregated-public-key")
+        .long("aggregated-public-key")
+        .required(true)
+        .takes_value(true)
+        .help("aggregated public key of all signers")
+}
+
+pub struct VerifyNodeVssCommand {}
+
+impl<'a> VerifyNodeVssCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let vss_vec = node_vss_of(matches)?;
+
+        let receiver = vss_vec[0].receiver_public_key;
+        if vss_vec.iter().any(|v| v.receiver_public_key != receiver) {
+            return Err(Error::VerificationFailed(
+                "the node VSSs have different receivers.".to_string(),
+            ));
+        }
+        let threshold = vss_vec[0].positive_commitments.len();
+        if threshold == 0 || threshold > vss_vec.len() {
+            return Err(Error::VerificationFailed(format!(
+                "the number of commitments({}) is invalid.",
+                threshold
+            )));
+        }
+
+        let mut senders: Vec<PublicKey> = vss_vec.iter().map(|v| v.sender_public_key).collect();
+        NodeParameters::<Rpc>::sort_publickey(&mut senders);
+        let index =
+            senders
+                .iter()
+                .position(|pk| *pk == receiver)
+                .ok_or(Error::VerificationFailed(
+                    "the receiver is not one of the senders.".to_string(),
+                ))?
+                + 1;
+
+        let parameters = ShamirSecretSharing {
+            threshold: threshold - 1,
+            share_count: vss_vec.len(),
+        };
+        for vss in &vss_vec {
+            let shares = [
+                (&vss.positive_commitments, vss.positive_secret),
+                (&vss.negative_commitments, vss.negative_secret),
+            ];
+            for (commitments, secret) in shares.iter() {
+                let verifiable_ss = VerifiableSS {
+                    parameters: parameters.clone(),
+                    commitments: commitments.iter().map(|c| c.to_point()).collect(),
+                };
+                if commitments.len() != threshold
+                    || verifiable_ss.validate_share(secret, index).is_err()
+                {
+                    return Err(Error::VerificationFailed(format!(
+                        "the node VSS from {} has invalid share.",
+                        vss.sender_public_key
+                    )));
+                }
+            }
+        }
+
+        Ok(Box::new(VerifyResponse::new(
+            format!(
+                "OK: {} node VSSs for {} are valid. threshold: {}",
+                vss_vec.len(),
+                receiver,
+                threshold
+            ),
+            serde_json::json!({
+                "receiver_public_key": receiver.to_string(),
+                "threshold": threshold,
+                "senders": senders.iter().map(|pk| pk.to_string()).collect::<Vec<String>>(),
+            }),
+            OutputFormat::from_matches(matches),
+        )))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("verifynodevss")
+            .about("verify the secret shares in the node VSSs against their commitments")
+            .args(&vss_args())
+    }
+}
+
+pub struct VerifyAggregateCommand {}
+
+impl<'a> VerifyAggregateCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let vss_vec = node_vss_of(matches)?;
+        let aggregated_public_key = aggregated_public_key_of(matches)?;
+
+        let points: Vec<GE> = vss_vec
+            .iter()
+            .map(|v| {
+                v.positive_commitments
+                    .first()
+                    .map(|c| c.to_point())
+                    .ok_or(Error::InvalidSS)
+            })
+            .collect::<Result<Vec<GE>, _>>()?;
+        let sum = PublicKey::from_slice(&sum_point(&points).pk_to_key_slice())
+            .map_err(|_| Error::InvalidKey)?;
+        if sum != aggregated_public_key {
+            return Err(Error::VerificationFailed(format!(
+                "the aggregated public key does not match. expected: {}, actual: {}",
+                sum, aggregated_public_key
+            )));
+        }
+
+        Ok(Box::new(VerifyResponse::new(
+            format!(
+                "OK: the aggregated public key {} matches the node VSSs.",
+                aggregated_public_key
+            ),
+            serde_json::json!({
+                "aggregated_public_key": aggregated_public_key.to_string(),
+            }),
+            OutputFormat::from_matches(matches),
+        )))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("verifyaggregate")
+            .about("confirm the aggregated public key equals the sum of the first commitments of the node VSSs")
+            .args(&vss_args())
+            .arg(aggregated_public_key_arg())
+    }
+}
+
+pub struct VerifyBlockCommand {}
+
+impl<'a> VerifyBlockCommand {
+    pub fn execute(matches: &ArgMatches) -> Result<Box<dyn Response>, Error> {
+        let block: Block = value_of(matches, "block")?
+            .and_then(|s| hex::decode(s).ok())
+            .and_then(|hex| deserialize::<Block>(&hex).ok())
+            .ok_or(Error::InvalidArgs("block".to_string()))?;
+        let aggregated_public_key = aggregated_public_key_of(matches)?;
+
+        let hash = block.header.signature_hash();
+        match Sign::verify_proof(&block.header, &aggregated_public_key) {
+            Ok(()) => {}
+            Err(Error::InvalidSig) => {
+                return Err(Error::VerificationFailed(format!(
+                    "the block proof is not valid for the aggregated public key {}.",
+                    aggregated_public_key
+                )))
+            }
+            Err(e) => return Err(e),
+        }
+
+        Ok(Box::new(VerifyResponse::new(
+            format!(
+                "OK: the block proof is valid for the aggregated public key {}.",
+                aggregated_public_key
+            ),
+            serde_json::json!({
+                "aggregated_public_key": aggregated_public_key.to_string(),
+                "sighash": hash.to_string(),
+            }),
+            OutputFormat::from_matches(matches),
+        )))
+    }
+
+    pub fn args<'b>() -> App<'a, 'b> {
+        SubCommand::with_name("verifyblock")
+            .about("verify the proof of the signed block against the aggregated public key")
+            .args(&[
+                Arg::with_name("block")
+                    .long("block")
+                    .required_unless("block-file")
+                    .takes_value(true)
+                    .help("signed block as a hex string format"),
+                Arg::with_name("block-file")
+                    .long("block-file")
+                    .takes_value(true)
+                    .value_name("PATH")
+                    .help("file which has the signed block as a hex string format"),
+                aggregated_public_key_arg(),
+            ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE_VSS: [&str; 3] = [
+        "03b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d03b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d0002b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d1bb2811fe36fa9e15b7afc0ecdb4c51cad86c2c9135607f38e4ae581983112734aab9f763d82eaa3cfe0792e4b8da3022f0f42b32ceb01757265fa99f29c76fd1675a7e28bf8f325b32f87e7c0c01503216f46f169ae7eebbcd7a2c0ac3f54e6660325ccf75e6b278364d1e71c48c5fcf1de97237e5cdbb2635980c0c77b1af9b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d1bb2811fe36fa9e15b7afc0ecdb4c51cad86c2c9135607f38e4ae581983112734aab9f763d82eaa3cfe0792e4b8da3022f0f42b32ceb01757265fa99f29c76fd1675a7e28bf8f325b32f87e7c0c01503216f46f169ae7eebbcd7a2c0ac3f54e6660325ccf75e6b278364d1e71c48c5fcf1de97237e5cdbb2635980c0c77b1af9",
+        "0313f2a73541e6d55a75a80a6da819885c6ed6e56ecff19f5e928c4ea202ca7c9003b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d000213f2a73541e6d55a75a80a6da819885c6ed6e56ecff19f5e928c4ea202ca7c90adbd69de8655fcc6ead8e771f9f31ead7a431e543bf8ac8d921c80ab301bc8d1ce3573e2158dd7d16a2783265a0d1f44612fb44b24f6cabfa71e9fad736137f59e1a7bfc38def0de39f3faf29421e15c1424aa0bbe275dd0ec832fd7c34fb2f89f1cf17ad25ab75c2c124179386e18105a3d907a6f80cca43851485ce4fbdce913f2a73541e6d55a75a80a6da819885c6ed6e56ecff19f5e928c4ea202ca7c90adbd69de8655fcc6ead8e771f9f31ead7a431e543bf8ac8d921c80ab301bc8d1ce3573e2158dd7d16a2783265a0d1f44612fb44b24f6cabfa71e9fad736137f59e1a7bfc38def0de39f3faf29421e15c1424aa0bbe275dd0ec832fd7c34fb2f89f1cf17ad25ab75c2c124179386e18105a3d907a6f80cca43851485ce4fbdce9",
+        "023cb7d6326e33332d04d026be1a04cdaf084703d8dc75322182d8fb314a03a87703b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d00023cb7d6326e33332d04d026be1a04cdaf084703d8dc75322182d8fb314a03a877be6e3e5cdfc8877c9f9b1a0bbee781019c55098025b03fcede5e4947d16f6140dc0f59ac7b7a5e00aa48b8cd214011edfd9706c97032a7e91207ed8ea7127c9955918c6a7dd61da49783c4013b49f7120ffa04586f9c91e5c938bcd3667442ca801b6775343b6f71791392d4ccbd84edec9cfc8c8d5f8bce817c8c39e6386ce63cb7d6326e33332d04d026be1a04cdaf084703d8dc75322182d8fb314a03a877be6e3e5cdfc8877c9f9b1a0bbee781019c55098025b03fcede5e4947d16f6140dc0f59ac7b7a5e00aa48b8cd214011edfd9706c97032a7e91207ed8ea7127c9955918c6a7dd61da49783c4013b49f7120ffa04586f9c91e5c938bcd3667442ca801b6775343b6f71791392d4ccbd84edec9cfc8c8d5f8bce817c8c39e6386ce6",
+    ];
+    const AGGREGATED_PUBLIC_KEY: &str =
+        "03addb2555f37abf8f28f11f498bec7bd1460e7243c1813847c49a7ae326a97d1c";
+
+    fn vss_args(vss: &[&'static str]) -> Vec<&'static str> {
+        vss.iter().flat_map(|v| vec!["--vss", *v]).collect()
+    }
+
+    #[test]
+    fn test_verify_node_vss() {
+        let mut args = vec!["verifynodevss"];
+        args.extend(vss_args(&NODE_VSS));
+        let matches = VerifyNodeVssCommand::args().get_matches_from(args);
+        let response = VerifyNodeVssCommand::execute(&matches).unwrap();
+        assert_eq!(
+            format!("{}", response),
+            "OK: 3 node VSSs for 03b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d are valid. threshold: 2"
+        );
+
+        // The share of the last VSS is modified.
+        let mut args = vec!["verifynodevss"];
+        args.extend(vss_args(&NODE_VSS[..2]));
+        args.extend(vec![
+            "--vss",
+            "023cb7d6326e33332d04d026be1a04cdaf084703d8dc75322182d8fb314a03a87703b8ad9e3271a20d5eb2b622e455fcffa5c9c90e38b192772b2e1b58f6b442e78d00023cb7d6326e33332d04d026be1a04cdaf084703d8dc75322182d8fb314a03a877be6e3e5cdfc8877c9f9b1a0bbee781019c55098025b03fcede5e4947d16f6140dc0f59ac7b7a5e00aa48b8cd214011edfd9706c97032a7e91207ed8ea7127c9955918c6a7dd61da49783c4013b49f7120ffa04586f9c91e5c938bcd3667442ca801b6775343b6f71791392d4ccbd84edec9cfc8c8d5f8bce817c8c39e6386ce63cb7d6326e33332d04d026be1a04cdaf084703d8dc75322182d8fb314a03a877be6e3e5cdfc8877c9f9b1a0bbee781019c55098025b03fcede5e4947d16f6140dc0f59ac7b7a5e00aa48b8cd214011edfd9706c97032a7e91207ed8ea7127c9955918c6a7dd61da49783c4013b49f7120ffa04586f9c91e5c938bcd3667442ca801b6775343b6f71791392d4ccbd84edec9cfc8c8d5f8bce817c8c39e6386ce0",
+        ]);
+        let matches = VerifyNodeVssCommand::args().get_matches_from(args);
+        match VerifyNodeVssCommand::execute(&matches) {
+            Err(Error::VerificationFailed(_)) => {}
+            _ => panic!("should fail"),
+        }
+    }
+
+    #[test]
+    fn test_verify_aggregate() {
+        let mut args = vec!["verifyaggregate"];
+        args.extend(vss_args(&NODE_VSS));
+        args.extend(vec!["--aggregated-public-key", AGGREGATED_PUBLIC_KEY]);
+        let matches = VerifyAggregateCommand::args().get_matches_from(args);
+        assert!(VerifyAggregateCommand::execute(&matches).is_ok());
+
+        // The aggregated public key doesn't include the last signer.
+        let mut args = vec!["verifyaggregate"];
+        args.extend(vss_args(&NODE_VSS[..2]));
+        args.extend(vec!["--aggregated-public-key", AGGREGATED_PUBLIC_KEY]);
+        let matches = VerifyAggregateCommand::args().get_matches_from(args);
+        match VerifyAggregateCommand::execute(&matches) {
+            Err(Error::VerificationFailed(_)) => {}
+            _ => panic!("should fail"),
+        }
+    }
+
+    #[test]
+    fn test_format_json() {
+        let response = VerifyResponse::new(
+            "OK".to_string(),
+            serde_json::json!({ "aggregated_public_key": AGGREGATED_PUBLIC_KEY }),
+            OutputFormat::Json,
+        );
+        let json: serde_json::Value = serde_json::from_str(&format!("{}", response)).unwrap();
+        assert_eq!(json["valid"], true);
+        assert_eq!(json["aggregated_public_key"], AGGREGATED_PUBLIC_KEY);
+    }
+}
@@ -4,11 +4,20 @@
 
 use std::str::FromStr;
 
-use crate::signer_node::{ROUND_INTERVAL_DEFAULT_SECS, ROUND_LIMIT_DEFAULT_SECS};
+use crate::block_policy::BlockPolicy;
+use crate::redis_client::{RedisOptions, RedisSentinel};
+use crate::rpc::RpcOptions;
+use crate::rpc_client::RpcAuth;
+use crate::signer_node::{
+    HEARTBEAT_INTERVAL_DEFAULT_SECS, ROUND_INTERVAL_DEFAULT_SECS, ROUND_LIMIT_DEFAULT_SECS,
+    TIP_CHECK_INTERVAL_DEFAULT_SECS,
+};
 use clap::{App, Arg};
 use log;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tapyrus::{Address, PublicKey};
 
 pub const OPTION_NAME_CONFIG: &str = "config";
@@ -17,21 +26,42 @@ pub const OPTION_NAME_CONFIG: &str = "config";
 pub const OPTION_NAME_TO_ADDRESS: &str = "coinbase_pay_to_address";
 pub const OPTION_NAME_PUBLIC_KEY: &str = "publickey";
 pub const OPTION_NAME_FEDERATIONS_FILE: &str = "federations-file";
+pub const OPTION_NAME_SIGNING_SERVICE: &str = "signing-service";
+pub const OPTION_NAME_NETWORK_ID: &str = "network-id";
 
 /// # RPC Config
 pub const OPTION_NAME_RPC_ENDPOINT_HOST: &str = "rpc_endpoint_host";
 pub const OPTION_NAME_RPC_ENDPOINT_PORT: &str = "rpc_endpoint_port";
 pub const OPTION_NAME_RPC_ENDPOINT_USER: &str = "rpc_endpoint_user";
 pub const OPTION_NAME_RPC_ENDPOINT_PASS: &str = "rpc_endpoint_pass";
+pub const OPTION_NAME_RPC_ENDPOINTS: &str = "rpc_endpoints";
+pub const OPTION_NAME_RPC_TIMEOUT: &str = "rpc_timeout";
+pub const OPTION_NAME_RPC_RETRIES: &str = "rpc_retries";
+pub const OPTION_NAME_RPC_DEADLINE: &str = "rpc_deadline";
+pub const OPTION_NAME_RPC_COOKIE_FILE: &str = "rpc_cookie_file";
+pub const OPTION_NAME_RPC_CA_CERT: &str = "rpc_ca_cert";
+pub const OPTION_NAME_ZMQ_PUB_HASHBLOCK: &str = "zmq_pub_hashblock";
 
 /// # Redis Config
 pub const OPTION_NAME_REDIS_HOST: &str = "redis_host";
 pub const OPTION_NAME_REDIS_PORT: &str = "redis_port";
+pub const OPTION_NAME_REDIS_USER: &str = "redis_user";
+pub const OPTION_NAME_REDIS_TLS: &str = "redis_tls";
+pub const OPTION_NAME_REDIS_CA_CERT: &str = "redis_ca_cert";
+pub const OPTION_NAME_REDIS_SENTINELS: &str = "redis_sentinels";
+pub const OPTION_NAME_REDIS_SENTINEL_MASTER: &str = "redis_sentinel_master";
+
+/// Environment variables of the Redis passwords, which are not given on the command line so that
+/// they don't appear in the process list.
+pub const ENV_NAME_REDIS_PASS: &str = "TAPYRUS_SIGNER_REDIS_PASS";
+pub const ENV_NAME_REDIS_SENTINEL_PASS: &str = "TAPYRUS_SIGNER_REDIS_SENTINEL_PASS";
 
 /// # General Config
 /// round category params.
 pub const OPTION_NAME_ROUND_DURATION: &str = "round_duration";
 pub const OPTION_NAME_ROUND_LIMIT: &str = "round_limit";
+pub const OPTION_NAME_HEARTBEAT_INTERVAL: &str = "heartbeat_interval";
+pub const OPTION_NAME_TIP_CHECK_INTERVAL: &str = "tip_check_interval";
 /// log category params.
 pub const OPTION_NAME_LOG_QUIET: &str = "log_quiet";
 pub const OPTION_NAME_LOG_LEVEL: &str = "log_level";
@@ -41,6 +71,7 @@ pub const OPTION_NAME_PID: &str = "pid";
 pub const OPTION_NAME_LOG_FILE: &str = "log_file";
 /// Others
 pub const OPTION_NAME_SKIP_WAITING_IBD: &str = "skip_waiting_ibd";
+pub const OPTION_NAME_CONTROL_SOCKET: &str = "control_socket";
 
 /// # Default Values
 pub const DEFAULT_RPC_HOST: &str = "127.0.0.1";
@@ -49,6 +80,7 @@ pub const DEFAULT_RPC_USERNAME: &str = "";
 pub const DEFAULT_RPC_PASSWORD: &str = "";
 pub const DEFAULT_REDIS_HOST: &str = "127.0.0.1";
 pub const DEFAULT_REDIS_PORT: &str = "6379";
+pub const DEFAULT_REDIS_SENTINEL_MASTER: &str = "mymaster";
 pub const DEFAULT_LOG_LEVEL: &str = "info";
 
 lazy_static! {
@@ -75,6 +107,10 @@ struct SignerToml {
     publickey: Option<String>,
     #[serde(rename = "federations-file")]
     federations_file: Option<String>,
+    #[serde(rename = "signing-service")]
+    signing_service: Option<String>,
+    #[serde(rename = "network-id")]
+    network_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -87,6 +123,22 @@ pub struct RpcToml {
     rpc_endpoint_user: Option<String>,
     #[serde(rename = "rpc-endpoint-pass")]
     rpc_endpoint_pass: Option<String>,
+    #[serde(rename = "rpc-endpoints")]
+    rpc_endpoints: Option<Vec<String>>,
+    #[serde(rename = "rpc-timeout")]
+    rpc_timeout: Option<u64>,
+    #[serde(rename = "rpc-method-timeouts")]
+    rpc_method_timeouts: Option<HashMap<String, u64>>,
+    #[serde(rename = "rpc-retries")]
+    rpc_retries: Option<u32>,
+    #[serde(rename = "rpc-deadline")]
+    rpc_deadline: Option<u64>,
+    #[serde(rename = "rpc-cookie-file")]
+    rpc_cookie_file: Option<String>,
+    #[serde(rename = "rpc-ca-cert")]
+    rpc_ca_cert: Option<String>,
+    #[serde(rename = "zmq-pub-hashblock")]
+    zmq_pub_hashblock: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,6 +147,20 @@ pub struct RedisToml {
     redis_host: Option<String>,
     #[serde(rename = "redis-port")]
     redis_port: Option<u32>,
+    #[serde(rename = "redis-user")]
+    redis_user: Option<String>,
+    #[serde(rename = "redis-pass")]
+    redis_pass: Option<String>,
+    #[serde(rename = "redis-tls")]
+    redis_tls: Option<bool>,
+    #[serde(rename = "redis-ca-cert")]
+    redis_ca_cert: Option<String>,
+    #[serde(rename = "redis-sentinels")]
+    redis_sentinels: Option<Vec<String>>,
+    #[serde(rename = "redis-sentinel-master")]
+    redis_sentinel_master: Option<String>,
+    #[serde(rename = "redis-sentinel-pass")]
+    redis_sentinel_pass: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -103,6 +169,10 @@ pub struct GeneralToml {
     round_duration: Option<u64>,
     #[serde(rename = "round-limit")]
     round_limit: Option<u64>,
+    #[serde(rename = "heartbeat-interval")]
+    heartbeat_interval: Option<u64>,
+    #[serde(rename = "tip-check-interval")]
+    tip_check_interval: Option<u64>,
     #[serde(rename = "log-level")]
     log_level: Option<String>,
     #[serde(rename = "log-quiet")]
@@ -113,6 +183,24 @@ pub struct GeneralToml {
     pid: Option<String>,
     #[serde(rename = "log-file")]
     log_file: Option<String>,
+    #[serde(rename = "control-socket")]
+    control_socket: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyToml {
+    #[serde(rename = "max-tx-count")]
+    max_tx_count: Option<usize>,
+    #[serde(rename = "max-block-size")]
+    max_block_size: Option<usize>,
+    #[serde(rename = "min-block-interval")]
+    min_block_interval: Option<u32>,
+    #[serde(rename = "max-block-interval")]
+    max_block_interval: Option<u32>,
+    #[serde(rename = "max-time-skew")]
+    max_time_skew: Option<u32>,
+    #[serde(rename = "coinbase-addresses")]
+    coinbase_addresses: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -121,6 +209,7 @@ struct ConfigToml {
     rpc: Option<RpcToml>,
     redis: Option<RedisToml>,
     general: Option<GeneralToml>,
+    policy: Option<PolicyToml>,
 }
 
 pub struct CommandArgs<'a> {
@@ -132,6 +221,8 @@ pub struct SignerCommandArgs<'a> {
     to_address: Option<&'a str>,
     public_key: Option<&'a str>,
     federations_file: Option<&'a str>,
+    signing_service: Option<&'a str>,
+    network_id: Option<&'a str>,
 }
 
 pub struct SignerConfig<'a> {
@@ -175,6 +266,25 @@ impl<'a> SignerConfig<'a> {
             .and_then(|s| Some(Path::new(s)))
             .expect("Must be specified federations-file.")
     }
+
+    /// Returns the path to the Unix domain socket of the signing service. The node secret share is
+    /// used in process if it is not specified.
+    pub fn signing_service(&self) -> Option<&str> {
+        let value_within_config: Option<&str> = self
+            .toml_config
+            .and_then(|config| config.signing_service.as_ref())
+            .map(|p| p as &str);
+        self.command_args.signing_service.or(value_within_config)
+    }
+    /// Returns the identifier of the signer network, which namespaces the Redis channels and the
+    /// messages. The signers of a federation must have the same one.
+    pub fn network_id(&self) -> Option<&str> {
+        let value_within_config: Option<&str> = self
+            .toml_config
+            .and_then(|config| config.network_id.as_ref())
+            .map(|p| p as &str);
+        self.command_args.network_id.or(value_within_config)
+    }
 }
 
 pub struct RpcCommandArgs<'a> {
@@ -182,6 +292,13 @@ pub struct RpcCommandArgs<'a> {
     pub port: Option<&'a str>,
     pub username: Option<&'a str>,
     pub password: Option<&'a str>,
+    pub endpoints: Option<&'a str>,
+    pub timeout: Option<&'a str>,
+    pub retries: Option<&'a str>,
+    pub deadline: Option<&'a str>,
+    pub cookie_file: Option<&'a str>,
+    pub ca_cert: Option<&'a str>,
+    pub zmq_pub_hashblock: Option<&'a str>,
 }
 
 pub struct RpcConfig<'a> {
@@ -222,11 +339,124 @@ impl<'a> RpcConfig<'a> {
             .map(|s| s as &str);
         self.command_args.password.or(toml_value)
     }
+    pub fn cookie_file(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.rpc_cookie_file.as_ref())
+            .map(|s| s as &str);
+        self.command_args.cookie_file.or(toml_value)
+    }
+    pub fn ca_cert(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.rpc_ca_cert.as_ref())
+            .map(|s| s as &str);
+        self.command_args.ca_cert.or(toml_value)
+    }
+    /// Returns the ZMQ endpoint which Tapyrus Core publishes new tips to.
+    pub fn zmq_pub_hashblock(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.zmq_pub_hashblock.as_ref())
+            .map(|s| s as &str);
+        self.command_args.zmq_pub_hashblock.or(toml_value)
+    }
+    /// Returns the credentials. The cookie file takes precedence over the user name and the
+    /// password.
+    pub fn auth(&'a self) -> RpcAuth {
+        if let Some(path) = self.cookie_file() {
+            return RpcAuth::CookieFile(PathBuf::from(path));
+        }
+        match self.user_name() {
+            Some(user) => RpcAuth::UserPass(user.to_string(), self.password().map(str::to_string)),
+            None => RpcAuth::None,
+        }
+    }
+    /// Returns URLs of Tapyrus Core RPC endpoints in the order of preference. If `rpc-endpoints`
+    /// is not specified, it has only the endpoint of the host and the port. An endpoint without
+    /// the scheme is connected with `http`.
+    pub fn endpoints(&'a self) -> Vec<String> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.rpc_endpoints.as_ref())
+            .map(|v| v.iter().map(|s| s as &str).collect::<Vec<&str>>());
+        let endpoints = self
+            .command_args
+            .endpoints
+            .map(|s| s.split(',').map(str::trim).collect::<Vec<&str>>())
+            .or(toml_value)
+            .filter(|v| !v.is_empty());
+        match endpoints {
+            Some(endpoints) => endpoints
+                .into_iter()
+                .map(|e| {
+                    if e.contains("://") {
+                        e.to_string()
+                    } else {
+                        format!("http://{}", e)
+                    }
+                })
+                .collect(),
+            None => vec![format!("http://{}:{}", self.host(), self.port())],
+        }
+    }
+    /// Returns timeouts and retries of RPC calls. Timeouts for each method can be set only in the
+    /// config file.
+    pub fn options(&'a self) -> RpcOptions {
+        let default = RpcOptions::default();
+        let toml_timeout = self.toml_config.and_then(|config| config.rpc_timeout);
+        let timeout = self
+            .command_args
+            .timeout
+            .and_then(|s| s.parse::<u64>().ok())
+            .or(toml_timeout)
+            .map(Duration::from_secs)
+            .unwrap_or(default.timeout);
+        let toml_retries = self.toml_config.and_then(|config| config.rpc_retries);
+        let retries = self
+            .command_args
+            .retries
+            .and_then(|s| s.parse::<u32>().ok())
+            .or(toml_retries)
+            .unwrap_or(default.retries);
+        let toml_deadline = self.toml_config.and_then(|config| config.rpc_deadline);
+        let deadline = self
+            .command_args
+            .deadline
+            .and_then(|s| s.parse::<u64>().ok())
+            .or(toml_deadline)
+            .map(Duration::from_secs)
+            .unwrap_or(default.deadline);
+        let method_timeouts = self
+            .toml_config
+            .and_then(|config| config.rpc_method_timeouts.as_ref())
+            .map(|timeouts| {
+                timeouts
+                    .iter()
+                    .map(|(method, secs)| (method.clone(), Duration::from_secs(*secs)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        RpcOptions {
+            timeout,
+            method_timeouts,
+            retries,
+            deadline,
+            ..default
+        }
+    }
 }
 
 pub struct RedisCommandArgs<'a> {
     pub host: Option<&'a str>,
     pub port: Option<&'a str>,
+    pub username: Option<&'a str>,
+    pub password: Option<String>,
+    pub tls: bool,
+    pub ca_cert: Option<&'a str>,
+    pub sentinels: Option<&'a str>,
+    pub sentinel_master: Option<&'a str>,
+    pub sentinel_password: Option<String>,
 }
 
 pub struct RedisConfig<'a> {
@@ -253,17 +483,101 @@ impl<'a> RedisConfig<'a> {
             .or(toml_value)
             .unwrap_or(DEFAULT_REDIS_PORT.parse().unwrap_or_default())
     }
+    pub fn user_name(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.redis_user.as_ref())
+            .map(|s| s as &str);
+        self.command_args.username.or(toml_value)
+    }
+    pub fn password(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.redis_pass.as_ref())
+            .map(|s| s as &str);
+        self.command_args.password.as_deref().or(toml_value)
+    }
+    pub fn tls(&'a self) -> bool {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.redis_tls)
+            .unwrap_or_default();
+        self.command_args.tls || toml_value
+    }
+    pub fn ca_cert(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.redis_ca_cert.as_ref())
+            .map(|s| s as &str);
+        self.command_args.ca_cert.or(toml_value)
+    }
+    /// Returns addresses of Redis Sentinels as `host:port`. If it is empty, Sentinel is not used.
+    pub fn sentinels(&'a self) -> Vec<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.redis_sentinels.as_ref())
+            .map(|v| v.iter().map(|s| s as &str).collect::<Vec<&str>>());
+        self.command_args
+            .sentinels
+            .map(|s| s.split(',').map(str::trim).collect::<Vec<&str>>())
+            .or(toml_value)
+            .unwrap_or_default()
+    }
+    pub fn sentinel_master(&'a self) -> &'a str {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.redis_sentinel_master.as_ref())
+            .map(|s| s as &str);
+        self.command_args
+            .sentinel_master
+            .or(toml_value)
+            .unwrap_or(DEFAULT_REDIS_SENTINEL_MASTER)
+    }
+    pub fn sentinel_password(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.redis_sentinel_pass.as_ref())
+            .map(|s| s as &str);
+        self.command_args
+            .sentinel_password
+            .as_deref()
+            .or(toml_value)
+    }
+    /// Returns the settings of the connection. The host and the port are ignored if Sentinels are
+    /// specified.
+    pub fn options(&'a self) -> RedisOptions {
+        let sentinels = self.sentinels();
+        RedisOptions {
+            username: self.user_name().map(str::to_string),
+            password: self.password().map(str::to_string),
+            tls: self.tls(),
+            ca_cert: self.ca_cert().map(PathBuf::from),
+            sentinel: if sentinels.is_empty() {
+                None
+            } else {
+                Some(RedisSentinel {
+                    master_name: self.sentinel_master().to_string(),
+                    addresses: sentinels.into_iter().map(str::to_string).collect(),
+                    password: self.sentinel_password().map(str::to_string),
+                })
+            },
+            ..RedisOptions::new(self.host().to_string(), self.port())
+        }
+    }
 }
 
 pub struct GeneralCommandArgs<'a> {
     round_duration: Option<&'a str>,
     round_limit: Option<&'a str>,
+    heartbeat_interval: Option<&'a str>,
+    tip_check_interval: Option<&'a str>,
     log_quiet: bool,
     log_level: Option<&'a str>,
     skip_waiting_ibd: bool,
     daemon: bool,
     pid: Option<&'a str>,
     log_file: Option<&'a str>,
+    control_socket: Option<&'a str>,
 }
 
 pub struct GeneralConfig<'a> {
@@ -288,6 +602,36 @@ impl<'a> GeneralConfig<'a> {
             .or(toml_value)
             .unwrap_or(ROUND_LIMIT_DEFAULT_SECS)
     }
+    /// Returns the heartbeat interval. It should be 1 or more, otherwise every peer is considered
+    /// to be dead as soon as it sends a heartbeat.
+    pub fn heartbeat_interval(&'a self) -> Result<u64, crate::errors::Error> {
+        let invalid = || {
+            crate::errors::Error::InvalidArgs(
+                "heartbeat-interval should be a positive integer.".to_string(),
+            )
+        };
+        let interval = match self.command_args.heartbeat_interval {
+            Some(d) => d.parse().map_err(|_| invalid())?,
+            None => self
+                .toml_config
+                .and_then(|config| config.heartbeat_interval)
+                .unwrap_or(HEARTBEAT_INTERVAL_DEFAULT_SECS),
+        };
+        if interval < 1 {
+            return Err(invalid());
+        }
+        Ok(interval)
+    }
+    pub fn tip_check_interval(&'a self) -> u64 {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.tip_check_interval);
+        self.command_args
+            .tip_check_interval
+            .and_then(|d| d.parse().ok())
+            .or(toml_value)
+            .unwrap_or(TIP_CHECK_INTERVAL_DEFAULT_SECS)
+    }
     pub fn log_level(&'a self) -> &'a str {
         let toml_value = self
             .toml_config
@@ -341,6 +685,15 @@ impl<'a> GeneralConfig<'a> {
                 .expect("Can't cast default log file PathBuf to &str"),
         )
     }
+    /// Returns the path to the Unix domain socket which the control endpoint listens on. The
+    /// endpoint is disabled if it is None.
+    pub fn control_socket(&'a self) -> Option<&'a str> {
+        let toml_value = self
+            .toml_config
+            .and_then(|config| config.control_socket.as_ref())
+            .map(|s| s as &str);
+        self.command_args.control_socket.or(toml_value)
+    }
 }
 
 impl<'a> CommandArgs<'a> {
@@ -380,6 +733,8 @@ impl<'a> CommandArgs<'a> {
                 to_address: self.matches.value_of(OPTION_NAME_TO_ADDRESS),
                 public_key: self.matches.value_of(OPTION_NAME_PUBLIC_KEY),
                 federations_file: self.matches.value_of(OPTION_NAME_FEDERATIONS_FILE),
+                signing_service: self.matches.value_of(OPTION_NAME_SIGNING_SERVICE),
+                network_id: self.matches.value_of(OPTION_NAME_NETWORK_ID),
             },
             toml_config: self.config.as_ref().and_then(|c| c.signer.as_ref()),
         }
@@ -392,6 +747,13 @@ impl<'a> CommandArgs<'a> {
                 port: self.matches.value_of(OPTION_NAME_RPC_ENDPOINT_PORT),
                 username: self.matches.value_of(OPTION_NAME_RPC_ENDPOINT_USER),
                 password: self.matches.value_of(OPTION_NAME_RPC_ENDPOINT_PASS),
+                endpoints: self.matches.value_of(OPTION_NAME_RPC_ENDPOINTS),
+                timeout: self.matches.value_of(OPTION_NAME_RPC_TIMEOUT),
+                retries: self.matches.value_of(OPTION_NAME_RPC_RETRIES),
+                deadline: self.matches.value_of(OPTION_NAME_RPC_DEADLINE),
+                cookie_file: self.matches.value_of(OPTION_NAME_RPC_COOKIE_FILE),
+                ca_cert: self.matches.value_of(OPTION_NAME_RPC_CA_CERT),
+                zmq_pub_hashblock: self.matches.value_of(OPTION_NAME_ZMQ_PUB_HASHBLOCK),
             },
             toml_config: self.config.as_ref().and_then(|c| c.rpc.as_ref()),
         }
@@ -402,21 +764,55 @@ impl<'a> CommandArgs<'a> {
             command_args: RedisCommandArgs {
                 host: self.matches.value_of(OPTION_NAME_REDIS_HOST),
                 port: self.matches.value_of(OPTION_NAME_REDIS_PORT),
+                username: self.matches.value_of(OPTION_NAME_REDIS_USER),
+                password: std::env::var(ENV_NAME_REDIS_PASS).ok(),
+                tls: self.matches.is_present(OPTION_NAME_REDIS_TLS),
+                ca_cert: self.matches.value_of(OPTION_NAME_REDIS_CA_CERT),
+                sentinels: self.matches.value_of(OPTION_NAME_REDIS_SENTINELS),
+                sentinel_master: self.matches.value_of(OPTION_NAME_REDIS_SENTINEL_MASTER),
+                sentinel_password: std::env::var(ENV_NAME_REDIS_SENTINEL_PASS).ok(),
             },
             toml_config: self.config.as_ref().and_then(|c| c.redis.as_ref()),
         }
     }
+    /// Returns the block policy in the `[policy]` section. The policy can be set only in the config
+    /// file.
+    pub fn block_policy(&self) -> BlockPolicy {
+        match self.config.as_ref().and_then(|c| c.policy.as_ref()) {
+            Some(policy) => BlockPolicy {
+                max_tx_count: policy.max_tx_count,
+                max_block_size: policy.max_block_size,
+                min_block_interval: policy.min_block_interval,
+                max_block_interval: policy.max_block_interval,
+                max_time_skew: policy.max_time_skew,
+                coinbase_addresses: policy
+                    .coinbase_addresses
+                    .iter()
+                    .flatten()
+                    .map(|s| {
+                        Address::from_str(s)
+                            .expect("coinbase-addresses includes an invalid address.")
+                    })
+                    .collect(),
+            },
+            None => BlockPolicy::default(),
+        }
+    }
+
     pub fn general_config(&self) -> GeneralConfig {
         GeneralConfig {
             command_args: GeneralCommandArgs {
                 round_duration: self.matches.value_of(OPTION_NAME_ROUND_DURATION),
                 round_limit: self.matches.value_of(OPTION_NAME_ROUND_LIMIT),
+                heartbeat_interval: self.matches.value_of(OPTION_NAME_HEARTBEAT_INTERVAL),
+                tip_check_interval: self.matches.value_of(OPTION_NAME_TIP_CHECK_INTERVAL),
                 log_level: self.matches.value_of(OPTION_NAME_LOG_LEVEL),
                 log_quiet: self.matches.is_present(OPTION_NAME_LOG_QUIET),
                 skip_waiting_ibd: self.matches.is_present(OPTION_NAME_SKIP_WAITING_IBD),
                 daemon: self.matches.is_present(OPTION_NAME_DAEMON),
                 pid: self.matches.value_of(OPTION_NAME_PID),
                 log_file: self.matches.value_of(OPTION_NAME_LOG_FILE),
+                control_socket: self.matches.value_of(OPTION_NAME_CONTROL_SOCKET),
             },
             toml_config: self.config.as_ref().and_then(|c| c.general.as_ref()),
         }
@@ -451,6 +847,14 @@ pub fn get_options<'a, 'b>() -> clap::App<'a, 'b> {
             .long("federations-file")
             .value_name("FILE")
             .help("The path to TOML file of the federations of the chain."))
+        .arg(Arg::with_name(OPTION_NAME_SIGNING_SERVICE)
+            .long("signing-service")
+            .value_name("SOCKET_PATH")
+            .help("The path to Unix domain socket of tapyrus-signing-service. If it is specified, local signatures are computed by the signing service instead of the node."))
+        .arg(Arg::with_name(OPTION_NAME_NETWORK_ID)
+            .long("network-id")
+            .value_name("ID")
+            .help("Identifier of the signer network, which is included in the Redis channel names and the messages, so that networks can share a Redis. Messages from another network are rejected. All signers of the federation must have the same one."))
         .arg(Arg::with_name(OPTION_NAME_RPC_ENDPOINT_HOST)
             .long("rpc-host")
             .value_name("HOST_NAME or IP")
@@ -467,6 +871,34 @@ pub fn get_options<'a, 'b>() -> clap::App<'a, 'b> {
             .long("rpc-pass")
             .value_name("PASS")
             .help("TapyrusCore RPC user password."))
+        .arg(Arg::with_name(OPTION_NAME_RPC_ENDPOINTS)
+            .long("rpc-endpoints")
+            .value_name("HOST:PORT,...")
+            .help("Comma separated TapyrusCore RPC endpoints in the order of preference. An endpoint can have the scheme like `https://host:port`. If the endpoint in use is unavailable, calls fail over to the next one. If it is specified, --rpc-host and --rpc-port are ignored."))
+        .arg(Arg::with_name(OPTION_NAME_RPC_TIMEOUT)
+            .long("rpc-timeout")
+            .value_name("SECs")
+            .help("Timeout(sec) of TapyrusCore RPC calls. The default is 10 sec."))
+        .arg(Arg::with_name(OPTION_NAME_RPC_RETRIES)
+            .long("rpc-retries")
+            .value_name("NUMBER")
+            .help("Max number of retries of TapyrusCore RPC calls which don't change the state of TapyrusCore, like getblockchaininfo. Calls are retried only on transport errors like a timeout. The default is 2."))
+        .arg(Arg::with_name(OPTION_NAME_RPC_DEADLINE)
+            .long("rpc-deadline")
+            .value_name("SECs")
+            .help("Max time(sec) of a TapyrusCore RPC call including all retries and failovers. The default is 30 sec."))
+        .arg(Arg::with_name(OPTION_NAME_RPC_COOKIE_FILE)
+            .long("rpc-cookie-file")
+            .value_name("FILE")
+            .help("The path to the `.cookie` file of TapyrusCore. If it is specified, the credentials are read from the file instead of --rpc-user and --rpc-pass."))
+        .arg(Arg::with_name(OPTION_NAME_RPC_CA_CERT)
+            .long("rpc-ca-cert")
+            .value_name("FILE")
+            .help("The path to the PEM file of the CA certificate which `https` RPC endpoints are verified with."))
+        .arg(Arg::with_name(OPTION_NAME_ZMQ_PUB_HASHBLOCK)
+            .long("zmq-pub-hashblock")
+            .value_name("tcp://HOST:PORT")
+            .help("ZMQ endpoint which TapyrusCore publishes new tips to with `-zmqpubhashblock`. If it is specified, the node starts rounds on new tips without waiting for the timers."))
         .arg(Arg::with_name(OPTION_NAME_REDIS_HOST)
             .long("redis-host")
             .value_name("HOST_NAME or IP")
@@ -475,6 +907,25 @@ pub fn get_options<'a, 'b>() -> clap::App<'a, 'b> {
             .long("redis-port")
             .value_name("PORT")
             .help("Redis port."))
+        .arg(Arg::with_name(OPTION_NAME_REDIS_USER)
+            .long("redis-user")
+            .value_name("USER")
+            .help("Redis ACL user name."))
+        .arg(Arg::with_name(OPTION_NAME_REDIS_TLS)
+            .long("redis-tls")
+            .help("Connect to Redis with TLS, as `rediss://`."))
+        .arg(Arg::with_name(OPTION_NAME_REDIS_CA_CERT)
+            .long("redis-ca-cert")
+            .value_name("FILE")
+            .help("The path to the PEM file of the CA certificate which Redis is verified with. It is used with --redis-tls."))
+        .arg(Arg::with_name(OPTION_NAME_REDIS_SENTINELS)
+            .long("redis-sentinels")
+            .value_name("HOST:PORT,...")
+            .help("Comma separated Redis Sentinel addresses. If it is specified, the primary is looked up from the Sentinels and --redis-host and --redis-port are ignored."))
+        .arg(Arg::with_name(OPTION_NAME_REDIS_SENTINEL_MASTER)
+            .long("redis-sentinel-master")
+            .value_name("NAME")
+            .help("The name of the primary monitored by Redis Sentinels. The default is `mymaster`."))
         .arg(Arg::with_name(OPTION_NAME_LOG_QUIET)
             .long("quiet")
             .short("q")
@@ -497,6 +948,16 @@ pub fn get_options<'a, 'b>() -> clap::App<'a, 'b> {
             .takes_value(true)
             .value_name("SECs")
             .help("Round limit times(sec).If the communications for rounds spends time more than round limit, the round would be regarded as a failure round and the next round would be started."))
+        .arg(Arg::with_name(OPTION_NAME_HEARTBEAT_INTERVAL)
+            .long("heartbeat-interval")
+            .takes_value(true)
+            .value_name("SECs")
+            .help("Interval times(sec) of broadcasting heartbeat messages. A master node skips the round if it hasn't received heartbeats from enough signers to meet the threshold."))
+        .arg(Arg::with_name(OPTION_NAME_TIP_CHECK_INTERVAL)
+            .long("tip-check-interval")
+            .takes_value(true)
+            .value_name("SECs")
+            .help("Interval times(sec) of checking the best block of Tapyrus Core during a round. If the chain tip changes, the node abandons the round and starts the next round."))
         .arg(Arg::with_name(OPTION_NAME_SKIP_WAITING_IBD)
             .long("skip-waiting-ibd")
             .help("This flag make signer node don't waiting connected Tapyrus full node finishes Initial Block Download when signer node started. When block creation stopped much time, The status of Tapyrus full node changes to progressing Initial Block Download. In this case, block creation is never resume, because signer node waits the status is back to non-IBD. So you can use this flag to start signer node with ignore tapyrus full node status."))
@@ -513,6 +974,11 @@ pub fn get_options<'a, 'b>() -> clap::App<'a, 'b> {
             .takes_value(true)
             .value_name("file")
             .help("Specify where log file export to. This option is enable when the node fot '--daemon' flag. If not, logs are put on stdout and stderr."))
+        .arg(Arg::with_name(OPTION_NAME_CONTROL_SOCKET)
+            .long("control-socket")
+            .takes_value(true)
+            .value_name("SOCKET_PATH")
+            .help("The path to the Unix domain socket of the control endpoint which returns the node status as JSON and accepts operations from tapyrus-signer-cli. Only the owner of the process can connect to it. The endpoint is disabled if this option is not set."))
 }
 
 #[test]
@@ -555,6 +1021,11 @@ fn test_load_from_file() {
         args.signer_config().federations_file(),
         Path::new("/tmp/federations.toml")
     );
+    assert_eq!(
+        args.signer_config().signing_service(),
+        Some("/tmp/tapyrus-signing-service.sock")
+    );
+    assert_eq!(args.signer_config().network_id(), Some("tapyrus-dev"));
     assert_eq!(
         args.signer_config().to_address(),
         Address::from_str("1Co1dFUNuYXY4izSNM9t71VpuUaYdMfq3S").unwrap()
@@ -565,14 +1036,52 @@ fn test_load_from_file() {
     assert_eq!(args.rpc_config().port(), 12381);
     assert_eq!(args.rpc_config().user_name(), Some("user"));
     assert_eq!(args.rpc_config().password(), Some("pass"));
+    assert_eq!(
+        args.rpc_config().endpoints(),
+        vec!["http://localhost:12381", "https://192.168.0.64:12381"]
+    );
+    assert_eq!(
+        args.rpc_config().auth(),
+        RpcAuth::CookieFile(PathBuf::from("/var/lib/tapyrus/.cookie"))
+    );
+    assert_eq!(
+        args.rpc_config().ca_cert(),
+        Some("/etc/tapyrus-signer/ca.pem")
+    );
+    let options = args.rpc_config().options();
+    assert_eq!(options.timeout("getblockchaininfo"), Duration::from_secs(5));
+    assert_eq!(options.timeout("getnewblock"), Duration::from_secs(30));
+    assert_eq!(options.retries, 3);
+    assert_eq!(options.deadline, Duration::from_secs(40));
 
     // redis parameters are loaded from toml data.
     assert_eq!(args.redis_config().host(), "192.168.0.63");
     assert_eq!(args.redis_config().port(), 16379);
+    assert_eq!(
+        args.redis_config().options(),
+        RedisOptions {
+            host: "192.168.0.63".to_string(),
+            port: 16379,
+            username: Some("signer".to_string()),
+            password: Some("redispass".to_string()),
+            tls: true,
+            ca_cert: Some(PathBuf::from("/etc/tapyrus-signer/redis-ca.pem")),
+            sentinel: Some(RedisSentinel {
+                master_name: "signer-primary".to_string(),
+                addresses: vec![
+                    "192.168.0.63:26379".to_string(),
+                    "192.168.0.64:26379".to_string()
+                ],
+                password: None,
+            }),
+        }
+    );
 
     // general parameters are loaded from toml data.
     assert_eq!(args.general_config().round_duration(), 5);
     assert_eq!(args.general_config().round_limit(), 15);
+    assert_eq!(args.general_config().heartbeat_interval().unwrap(), 20);
+    assert_eq!(args.general_config().tip_check_interval(), 3);
     assert_eq!(args.general_config().log_level(), "debug");
     assert_eq!(args.general_config().log_quiet(), true);
     assert_eq!(args.general_config().daemon(), true);
@@ -582,6 +1091,22 @@ fn test_load_from_file() {
         "/var/log/tapyrus-signer.log"
     );
     assert_eq!(args.general_config().skip_waiting_ibd(), true);
+    assert_eq!(
+        args.general_config().control_socket(),
+        Some("/var/run/tapyrus-signer/control.sock")
+    );
+
+    // block policy is loaded from toml data.
+    let policy = args.block_policy();
+    assert_eq!(policy.max_tx_count, Some(1000));
+    assert_eq!(policy.max_block_size, Some(500000));
+    assert_eq!(policy.min_block_interval, Some(10));
+    assert_eq!(policy.max_block_interval, Some(300));
+    assert_eq!(policy.max_time_skew, Some(120));
+    assert_eq!(
+        policy.coinbase_addresses,
+        vec![Address::from_str("1Co1dFUNuYXY4izSNM9t71VpuUaYdMfq3S").unwrap()]
+    );
 }
 
 #[test]
@@ -591,18 +1116,30 @@ fn test_priority_commandline() {
         "-c=tests/resources/signer_config.toml",
         "--duration=999",
         "--round-limit=99",
+        "--heartbeat-interval=9",
+        "--tip-check-interval=7",
         "-p=033cfe7fa1be58191b9108883543e921d31dc7726e051ee773e0ea54786ce438f8",
         "--federations-file=/tmp/federations.toml",
+        "--signing-service=/tmp/signing.sock",
+        "--network-id=tapyrus-staging",
         "--rpc-host=tapyrus.dev.chaintope.com",
         "--rpc-port=12345",
         "--rpc-user=test",
         "--rpc-pass=test",
+        "--rpc-timeout=20",
+        "--rpc-retries=0",
+        "--rpc-deadline=15",
+        "--rpc-cookie-file=/tmp/tapyrus/.cookie",
+        "--zmq-pub-hashblock=tcp://192.168.0.64:28332",
         "--redis-host=redis.endpoint.dev.chaintope.com",
         "--redis-port=88888",
+        "--redis-user=test",
+        "--redis-sentinels=127.0.0.1:26379",
         "--daemon",
         "--pid=/tmp/test.pid",
         "--log-file=/tmp/tapyrus-signer.log",
         "--skip-waiting-ibd",
+        "--control-socket=/tmp/tapyrus-signer-test.sock",
     ]);
     let args = CommandArgs::load(matches).unwrap();
 
@@ -615,12 +1152,33 @@ fn test_priority_commandline() {
         args.signer_config().federations_file(),
         Path::new("/tmp/federations.toml")
     );
+    assert_eq!(
+        args.signer_config().signing_service(),
+        Some("/tmp/signing.sock")
+    );
+    assert_eq!(args.signer_config().network_id(), Some("tapyrus-staging"));
 
     // rpc parameters are loaded from toml data.
     assert_eq!(args.rpc_config().host(), "tapyrus.dev.chaintope.com");
     assert_eq!(args.rpc_config().port(), 12345);
     assert_eq!(args.rpc_config().user_name(), Some("test"));
     assert_eq!(args.rpc_config().password(), Some("test"));
+    assert_eq!(
+        args.rpc_config().endpoints(),
+        vec!["http://tapyrus.dev.chaintope.com:12345"]
+    );
+    assert_eq!(
+        args.rpc_config().auth(),
+        RpcAuth::CookieFile(PathBuf::from("/tmp/tapyrus/.cookie"))
+    );
+    assert_eq!(
+        args.rpc_config().zmq_pub_hashblock(),
+        Some("tcp://192.168.0.64:28332")
+    );
+    let options = args.rpc_config().options();
+    assert_eq!(options.timeout("getnewblock"), Duration::from_secs(20));
+    assert_eq!(options.retries, 0);
+    assert_eq!(options.deadline, Duration::from_secs(15));
 
     // redis parameters are loaded from toml data.
     assert_eq!(
@@ -628,13 +1186,28 @@ fn test_priority_commandline() {
         "redis.endpoint.dev.chaintope.com"
     );
     assert_eq!(args.redis_config().port(), 88888);
+    assert_eq!(args.redis_config().user_name(), Some("test"));
+    assert_eq!(args.redis_config().password(), None);
+    assert_eq!(args.redis_config().tls(), false);
+    assert_eq!(args.redis_config().sentinels(), vec!["127.0.0.1:26379"]);
+    assert_eq!(args.redis_config().sentinel_master(), "mymaster");
+    assert_eq!(args.redis_config().sentinel_password(), None);
 
     assert_eq!(args.general_config().round_duration(), 999);
     assert_eq!(args.general_config().round_limit(), 99);
+    assert_eq!(args.general_config().heartbeat_interval().unwrap(), 9);
+    assert_eq!(args.general_config().tip_check_interval(), 7);
     assert_eq!(args.general_config().daemon(), true);
     assert_eq!(args.general_config().pid(), "/tmp/test.pid");
     assert_eq!(args.general_config().log_file(), "/tmp/tapyrus-signer.log");
     assert_eq!(args.general_config().skip_waiting_ibd(), true);
+    assert_eq!(
+        args.general_config().control_socket(),
+        Some("/tmp/tapyrus-signer-test.sock")
+    );
+
+    // block policy is disabled without the config file.
+    assert_eq!(args.block_policy(), BlockPolicy::default());
 }
 
 #[test]
@@ -648,6 +1221,8 @@ fn test_invalid_public_key() {
                 to_address: None,
                 publickey: Some("aabbccdd".to_string()),
                 federations_file: None,
+                signing_service: None,
+                network_id: None,
             }),
             ..ConfigToml::default()
         }),
@@ -677,9 +1252,58 @@ fn test_invalid_to_address() {
                 to_address: Some("aabbccdd".to_string()),
                 publickey: None,
                 federations_file: None,
+                signing_service: None,
+                network_id: None,
             }),
             ..ConfigToml::default()
         }),
     };
     let _to_address = args.signer_config().to_address();
 }
+
+#[test]
+fn test_invalid_heartbeat_interval() {
+    for value in &["0", "x"] {
+        let matches = get_options().get_matches_from(vec!["node", "--heartbeat-interval", value]);
+        let args = CommandArgs {
+            matches,
+            config: Some(ConfigToml::default()),
+        };
+        match args.general_config().heartbeat_interval() {
+            Err(crate::errors::Error::InvalidArgs(_)) => assert!(true),
+            _ => assert!(false, "it should error"),
+        }
+    }
+
+    let matches = get_options().get_matches_from(vec!["node"]);
+    let args = CommandArgs {
+        matches,
+        config: Some(toml::from_str("[general]\nheartbeat-interval = 0").unwrap()),
+    };
+    match args.general_config().heartbeat_interval() {
+        Err(crate::errors::Error::InvalidArgs(_)) => assert!(true),
+        _ => assert!(false, "it should error"),
+    }
+}
+
+#[test]
+fn test_redis_passwords_from_env() {
+    let config = read_config("tests/resources/signer_config_sample.toml").unwrap();
+    let redis_config = RedisConfig {
+        command_args: RedisCommandArgs {
+            host: None,
+            port: None,
+            username: None,
+            password: Some("envpass".to_string()),
+            tls: false,
+            ca_cert: None,
+            sentinels: None,
+            sentinel_master: None,
+            sentinel_password: Some("envsentinelpass".to_string()),
+        },
+        toml_config: config.redis.as_ref(),
+    };
+    // The environment variables take precedence over the config file.
+    assert_eq!(redis_config.password(), Some("envpass"));
+    assert_eq!(redis_config.sentinel_password(), Some("envsentinelpass"));
+}
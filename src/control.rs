@@ -0,0 +1,445 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Local HTTP endpoint to inspect and operate a running signer node.
+//!
+//! The endpoint listens on a Unix domain socket which only the owner of the node process can
+//! connect to. The server runs on its own thread, handles each connection on another thread, and
+//! passes each request to the node through a channel. The node answers requests in its main loop,
+//! so that the response reflects the current state without any locks.
+
+use crate::errors::Error;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Time to wait for the node to answer a request.
+const RESPONSE_TIMEOUT_SECS: u64 = 5;
+/// Time to wait for a client to send a request or to receive a response.
+const CONNECTION_TIMEOUT_SECS: u64 = 5;
+/// Max number of connections which are handled at the same time.
+const MAX_CONNECTIONS: usize = 8;
+/// Max size of request body. It is enough for federations toml.
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+/// The socket which `tapyrus-signer-cli` connects to by default.
+pub const DEFAULT_CONTROL_SOCKET: &str = "/tmp/tapyrus-signer-control.sock";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Status,
+    Federations,
+    /// Replace federations with the toml string.
+    UpdateFederations(String),
+    Peers,
+    Pause,
+    Resume,
+    /// Journal for the block height. Returns the latest one if it is None.
+    Journal(Option<u64>),
+    /// Notify a new tip like `-blocknotify` of Tapyrus Core. The block hash is not trusted, and
+    /// the node reads the tip from Tapyrus Core again.
+    BlockNotify(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlResponse {
+    pub code: u16,
+    pub body: String,
+}
+
+impl ControlResponse {
+    pub fn ok(body: String) -> Self {
+        ControlResponse { code: 200, body }
+    }
+
+    pub fn error(code: u16, message: &str) -> Self {
+        ControlResponse {
+            code,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+
+    /// Returns the message of error response.
+    pub fn error_message(&self) -> String {
+        serde_json::from_str::<serde_json::Value>(&self.body)
+            .ok()
+            .and_then(|v| v["error"].as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| self.body.clone())
+    }
+}
+
+/// A request which is passed to the node. The node must send the response to `responder`.
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub responder: Sender<ControlResponse>,
+}
+
+pub struct ControlServer {
+    listener: UnixListener,
+}
+
+impl ControlServer {
+    /// Listens on the Unix domain socket at the path. The socket left by the previous process is
+    /// replaced. The permissions of the socket are 0600, so that only the owner of the process can
+    /// operate the node.
+    pub fn bind<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                return Err(Error::InvalidArgs(format!(
+                    "{} exists and is not a socket.",
+                    path.display()
+                )));
+            }
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        Ok(ControlServer { listener })
+    }
+
+    /// Start accepting connections. Returns the receiver which the node should poll requests from.
+    pub fn start(self) -> (JoinHandle<()>, Receiver<ControlRequest>) {
+        let (sender, receiver) = channel();
+        let handler = std::thread::Builder::new()
+            .name("ControlServerThread".to_string())
+            .spawn(move || {
+                let connections = Arc::new(AtomicUsize::new(0));
+                for stream in self.listener.incoming() {
+                    match stream {
+                        Ok(stream) => spawn_connection(stream, &sender, &connections),
+                        Err(e) => log::warn!("Control server failed to accept: {:?}", e),
+                    }
+                }
+            })
+            .unwrap();
+        (handler, receiver)
+    }
+}
+
+/// Handles the connection on a new thread, so that a slow client doesn't block others.
+fn spawn_connection(
+    stream: UnixStream,
+    sender: &Sender<ControlRequest>,
+    connections: &Arc<AtomicUsize>,
+) {
+    if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+        connections.fetch_sub(1, Ordering::SeqCst);
+        log::warn!("Control server rejected a connection. Too many connections.");
+        let _ = stream
+            .set_write_timeout(Some(Duration::from_secs(CONNECTION_TIMEOUT_SECS)))
+            .map_err(Error::from)
+            .and_then(|_| {
+                write_response(stream, ControlResponse::error(503, "Too many connections."))
+            });
+        return;
+    }
+
+    let sender = sender.clone();
+    let counter = connections.clone();
+    let spawned = std::thread::Builder::new()
+        .name("ControlConnectionThread".to_string())
+        .spawn(move || {
+            if let Err(e) = handle_connection(stream, &sender) {
+                log::warn!("Control server failed to handle a request: {:?}", e);
+            }
+            counter.fetch_sub(1, Ordering::SeqCst);
+        });
+    if let Err(e) = spawned {
+        connections.fetch_sub(1, Ordering::SeqCst);
+        log::warn!("Control server failed to spawn a thread: {:?}", e);
+    }
+}
+
+fn handle_connection(stream: UnixStream, sender: &Sender<ControlRequest>) -> Result<(), Error> {
+    stream.set_read_timeout(Some(Duration::from_secs(CONNECTION_TIMEOUT_SECS)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(CONNECTION_TIMEOUT_SECS)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let content_length = read_headers(&mut reader)?;
+    if content_length > MAX_BODY_SIZE {
+        return write_response(
+            stream,
+            ControlResponse::error(400, "Request body is too large."),
+        );
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    let response = match route(&request_line, String::from_utf8_lossy(&body).to_string()) {
+        Ok(command) => dispatch(command, sender),
+        Err(response) => response,
+    };
+    write_response(stream, response)
+}
+
+/// Read headers and returns the value of Content-Length.
+fn read_headers<R: BufRead>(reader: &mut R) -> Result<usize, Error> {
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let mut header = line.splitn(2, ':');
+        let name = header.next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = header.next().unwrap_or("").trim().parse().unwrap_or(0);
+        }
+    }
+    Ok(content_length)
+}
+
+/// Parse the request line like "GET /status HTTP/1.1" into a command.
+fn route(request_line: &str, body: String) -> Result<ControlCommand, ControlResponse> {
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("");
+    let mut target = target.splitn(2, '?');
+    let path = target.next().unwrap_or("");
+    let query = target.next();
+
+    match (method, path) {
+        ("GET", "/status") => Ok(ControlCommand::Status),
+        ("GET", "/federations") => Ok(ControlCommand::Federations),
+        ("PUT", "/federations") => Ok(ControlCommand::UpdateFederations(body)),
+        ("GET", "/peers") => Ok(ControlCommand::Peers),
+        ("POST", "/pause") => Ok(ControlCommand::Pause),
+        ("POST", "/resume") => Ok(ControlCommand::Resume),
+        ("POST", "/blocknotify") => Ok(ControlCommand::BlockNotify(body)),
+        ("GET", "/journal") => match query_value(query, "height") {
+            None => Ok(ControlCommand::Journal(None)),
+            Some(height) => height
+                .parse()
+                .map(|h| ControlCommand::Journal(Some(h)))
+                .map_err(|_| ControlResponse::error(400, "height is invalid.")),
+        },
+        (_, "/status")
+        | (_, "/federations")
+        | (_, "/peers")
+        | (_, "/pause")
+        | (_, "/resume")
+        | (_, "/blocknotify")
+        | (_, "/journal") => Err(ControlResponse::error(405, "Method Not Allowed")),
+        _ => Err(ControlResponse::error(404, "Not Found")),
+    }
+}
+
+fn query_value<'a>(query: Option<&'a str>, key: &str) -> Option<&'a str> {
+    query?.split('&').find_map(|pair| {
+        let mut pair = pair.splitn(2, '=');
+        if pair.next() == Some(key) {
+            pair.next()
+        } else {
+            None
+        }
+    })
+}
+
+fn dispatch(command: ControlCommand, sender: &Sender<ControlRequest>) -> ControlResponse {
+    let (responder, receiver) = channel();
+    if sender.send(ControlRequest { command, responder }).is_err() {
+        return ControlResponse::error(503, "The node is not running.");
+    }
+    match receiver.recv_timeout(Duration::from_secs(RESPONSE_TIMEOUT_SECS)) {
+        Ok(response) => response,
+        Err(_) => ControlResponse::error(503, "The node didn't respond."),
+    }
+}
+
+fn write_response(mut stream: UnixStream, response: ControlResponse) -> Result<(), Error> {
+    let reason = match response.code {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.code,
+        reason,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Client for the control endpoint.
+pub struct ControlClient {
+    path: String,
+}
+
+impl ControlClient {
+    pub fn new(path: &str) -> Self {
+        ControlClient {
+            path: path.to_string(),
+        }
+    }
+
+    pub fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<ControlResponse, Error> {
+        let mut stream = UnixStream::connect(&self.path)?;
+        stream.set_read_timeout(Some(Duration::from_secs(RESPONSE_TIMEOUT_SECS * 2)))?;
+        stream.set_write_timeout(Some(Duration::from_secs(CONNECTION_TIMEOUT_SECS)))?;
+        let body = body.unwrap_or("");
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line)?;
+        let code = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .ok_or(Error::ControlRequest(0, "Invalid response.".to_string()))?;
+        read_headers(&mut reader)?;
+        let mut body = String::new();
+        reader.read_to_string(&mut body)?;
+
+        let response = ControlResponse { code, body };
+        if response.code == 200 {
+            Ok(response)
+        } else {
+            Err(Error::ControlRequest(
+                response.code,
+                response.error_message(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        let route = |line: &str| route(line, "".to_string());
+        assert_eq!(
+            route("GET /status HTTP/1.1\r\n"),
+            Ok(ControlCommand::Status)
+        );
+        assert_eq!(
+            route("GET /journal?height=10 HTTP/1.1\r\n"),
+            Ok(ControlCommand::Journal(Some(10)))
+        );
+        assert_eq!(
+            route("GET /journal HTTP/1.1\r\n"),
+            Ok(ControlCommand::Journal(None))
+        );
+        assert_eq!(
+            route("GET /journal?height=x HTTP/1.1\r\n")
+                .unwrap_err()
+                .code,
+            400
+        );
+        assert_eq!(route("POST /status HTTP/1.1\r\n").unwrap_err().code, 405);
+        assert_eq!(route("GET /pause HTTP/1.1\r\n").unwrap_err().code, 405);
+        assert_eq!(
+            route("GET /blocknotify HTTP/1.1\r\n").unwrap_err().code,
+            405
+        );
+        assert_eq!(route("GET /unknown HTTP/1.1\r\n").unwrap_err().code, 404);
+        assert_eq!(route("").unwrap_err().code, 404);
+    }
+
+    fn socket_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "tapyrus-signer-control-{}-{}.sock",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_bind() {
+        let path = socket_path("bind");
+        let _ = std::fs::remove_file(&path);
+
+        // Only the owner can connect to the socket.
+        let server = ControlServer::bind(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // The socket left by the previous process is replaced.
+        drop(server);
+        ControlServer::bind(&path).unwrap();
+
+        // A file which is not a socket is never removed.
+        std::fs::remove_file(&path).unwrap();
+        std::fs::write(&path, "").unwrap();
+        match ControlServer::bind(&path) {
+            Err(Error::InvalidArgs(_)) => {}
+            r => panic!("unexpected result {:?}", r.map(|_| ())),
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_server_and_client() {
+        let path = socket_path("server");
+        let server = ControlServer::bind(&path).unwrap();
+        let address = path.to_str().unwrap().to_string();
+        let (_handler, receiver) = server.start();
+
+        // A client which never sends a request doesn't block others.
+        let _idle = UnixStream::connect(&path).unwrap();
+
+        std::thread::spawn(move || {
+            let request = receiver.recv().unwrap();
+            assert_eq!(
+                request.command,
+                ControlCommand::UpdateFederations("[[federation]]".to_string())
+            );
+            request
+                .responder
+                .send(ControlResponse::ok("[]".to_string()))
+                .unwrap();
+
+            let request = receiver.recv().unwrap();
+            assert_eq!(request.command, ControlCommand::Pause);
+            request
+                .responder
+                .send(ControlResponse::error(503, "The node is not ready."))
+                .unwrap();
+        });
+
+        let client = ControlClient::new(&address);
+        let response = client
+            .request("PUT", "/federations", Some("[[federation]]"))
+            .unwrap();
+        assert_eq!(response.body, "[]");
+
+        match client.request("POST", "/pause", None) {
+            Err(Error::ControlRequest(503, message)) => {
+                assert_eq!(message, "The node is not ready.")
+            }
+            r => panic!("Unexpected response: {:?}", r),
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -36,7 +36,7 @@ pub struct Keys {
 }
 
 pub struct KeyGenBroadcastMessage1 {
-    com: BigInt,
+    pub com: BigInt,
 }
 
 /// Secret sharing parmeters
@@ -124,33 +124,38 @@ impl Vss {
         shared_block_secrets: &BidirectionalSharedSecretMap,
         block: &Block,
     ) -> Result<(bool, SharedKeys, LocalSig), Error> {
-        let shared_keys_for_positive =
-            Sign::verify_vss_and_construct_key(&shared_block_secrets.for_positive(), &index)?;
-        let local_sig_for_positive = Sign::sign(
-            &shared_keys_for_positive,
+        let (is_positive, shared_keys) =
+            Vss::create_block_shared_keys(index, shared_block_secrets)?;
+        let local_sig = Sign::sign(
+            &shared_keys,
             &priv_shared_keys,
             block.header.signature_hash(),
         );
+        Ok((is_positive, shared_keys, local_sig))
+    }
 
+    /// Returns the block shared keys which should be used for the local signature. The keys for
+    /// positive are used if the y coordinate of aggregated point is quadratic residue. Otherwise,
+    /// the keys for negative are used.
+    pub fn create_block_shared_keys(
+        index: usize,
+        shared_block_secrets: &BidirectionalSharedSecretMap,
+    ) -> Result<(bool, SharedKeys), Error> {
+        let shared_keys_for_positive =
+            Sign::verify_vss_and_construct_key(&shared_block_secrets.for_positive(), &index)?;
         let shared_keys_for_negative =
             Sign::verify_vss_and_construct_key(&shared_block_secrets.for_negative(), &index)?;
-        let local_sig_for_negative = Sign::sign(
-            &shared_keys_for_negative,
-            &priv_shared_keys,
-            block.header.signature_hash(),
-        );
 
         let y = shared_keys_for_positive
             .y
             .y_coor()
             .expect("can not get y_coor");
         let is_positive = jacobi(&Converter::to_vec(&y)) == 1;
-        let (shared_keys, local_sig) = if is_positive {
-            (shared_keys_for_positive, local_sig_for_positive)
+        if is_positive {
+            Ok((is_positive, shared_keys_for_positive))
         } else {
-            (shared_keys_for_negative, local_sig_for_negative)
-        };
-        Ok((is_positive, shared_keys, local_sig))
+            Ok((is_positive, shared_keys_for_negative))
+        }
     }
 
     pub fn aggregate_and_verify_signature(
@@ -160,7 +165,7 @@ impl Vss {
         shared_secrets: &SharedSecretMap,
         block_shared_keys: &Option<(bool, FE, GE)>,
         shared_block_secrets: &BidirectionalSharedSecretMap,
-        priv_shared_keys: &SharedKeys,
+        public_key: &GE,
     ) -> Result<Signature, Error> {
         let parties = signatures
             .keys()
@@ -188,9 +193,8 @@ impl Vss {
             &parties[..],
             block_shared_keys.unwrap().2,
         );
-        let public_key = priv_shared_keys.y;
         let hash = block.header.signature_hash();
-        signature.verify(&hash[..], &public_key)?;
+        signature.verify(&hash[..], public_key)?;
         Ok(signature)
     }
 }
@@ -313,13 +317,13 @@ impl Decodable for Vss {
             .collect::<Vec<Commitment>>();
 
         let bytes: [u8; 32] = Decodable::consensus_decode(&mut d)?;
-        let positive_secret = ECScalar::from(&BigInt::from(&bytes[..]));
+        let positive_secret = to_secret(&bytes);
         let negative_commitments: Vec<Commitment> = (0..length)
             .flat_map(|_| Decodable::consensus_decode(&mut d))
             .collect::<Vec<Commitment>>();
 
         let bytes: [u8; 32] = Decodable::consensus_decode(&mut d)?;
-        let negative_secret = ECScalar::from(&BigInt::from(&bytes[..]));
+        let negative_secret = to_secret(&bytes);
         Ok(Vss::new(
             sender_public_key,
             receiver_public_key,
@@ -331,6 +335,15 @@ impl Decodable for Vss {
     }
 }
 
+/// Returns the secret of the bytes. The secret is zero if the VSS doesn't include it.
+fn to_secret(bytes: &[u8; 32]) -> FE {
+    if bytes.iter().all(|b| *b == 0) {
+        ECScalar::zero()
+    } else {
+        ECScalar::from(&BigInt::from(&bytes[..]))
+    }
+}
+
 impl Encodable for Commitment {
     fn consensus_encode<S: io::Write>(&self, mut s: S) -> Result<usize, encode::Error> {
         let mut x = [0u8; 32];
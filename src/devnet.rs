@@ -0,0 +1,562 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Signer network which runs a whole federation in a process for development.
+//!
+//! `Devnet` generates the keys and the federations of the signers, and runs a `SignerNode` for
+//! each signer on its own thread. The nodes exchange messages over `InMemoryNetwork` and produce
+//! blocks on `MockChain`, so that neither Redis nor Tapyrus Core is needed. Failures are injected
+//! by stopping nodes or disconnecting them from the network.
+
+use crate::cli::setup::create_genesis::genesis_block;
+use crate::control::{ControlCommand, ControlRequest, ControlResponse, ControlServer};
+use crate::crypto::vss::{Commitment, Vss};
+use crate::errors::Error;
+use crate::federation::{Federation, Federations};
+use crate::key::generate_key_pair;
+use crate::mock_core::MockChain;
+use crate::net::{InMemoryNetwork, SignerID};
+use crate::signer_node::{NodeParameters, SignerNode};
+use crate::util::sum_point;
+use curv::elliptic::curves::traits::ECPoint;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tapyrus::{Address, PrivateKey, PublicKey};
+
+/// Time to wait for a node to answer a control request.
+const RESPONSE_TIMEOUT_SECS: u64 = 5;
+
+/// A federation which starts at the block height.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FederationPlan {
+    pub block_height: u64,
+    pub threshold: u8,
+    /// Indices of the signers in the devnet which are members of the federation.
+    pub members: Vec<usize>,
+}
+
+impl FromStr for FederationPlan {
+    type Err = Error;
+
+    /// Parses `<block height>:<threshold>:<indices of the members separated by commas>` like
+    /// `100:2:0,1,3`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidArgs(format!("Invalid federation: {}", s));
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() != 3 {
+            return Err(invalid());
+        }
+        let members = parts[2]
+            .split(',')
+            .map(|i| i.trim().parse::<usize>().map_err(|_| invalid()))
+            .collect::<Result<Vec<usize>, _>>()?;
+        Ok(FederationPlan {
+            block_height: parts[0].parse().map_err(|_| invalid())?,
+            threshold: parts[1].parse().map_err(|_| invalid())?,
+            members,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DevnetOptions {
+    /// Round duration(sec), which is the interval of blocks.
+    pub round_duration: u64,
+    /// Round limit(sec).
+    pub round_limit: u64,
+    /// Interval(sec) of broadcasting heartbeat messages.
+    pub heartbeat_interval: u64,
+    /// Interval(sec) of checking the chain tip during a round.
+    pub tip_check_interval: u64,
+}
+
+impl Default for DevnetOptions {
+    fn default() -> Self {
+        DevnetOptions {
+            round_duration: 5,
+            round_limit: 5,
+            heartbeat_interval: 5,
+            tip_check_interval: 1,
+        }
+    }
+}
+
+struct DevnetSigner {
+    private_key: PrivateKey,
+    public_key: PublicKey,
+    federations: Federations,
+    /// The control channel of the running node. It is shared with control endpoints, so that they
+    /// follow the node across restarts.
+    control: Arc<Mutex<Option<Sender<ControlRequest>>>>,
+    node: Option<DevnetNode>,
+}
+
+struct DevnetNode {
+    stop: Sender<u32>,
+    thread: JoinHandle<()>,
+}
+
+pub struct Devnet {
+    chain: MockChain,
+    network: InMemoryNetwork,
+    options: DevnetOptions,
+    signers: Vec<DevnetSigner>,
+}
+
+impl Devnet {
+    /// Generates the keys of `signer_count` signers and their federations. The first plan must
+    /// start at the genesis block. The signers are ordered by their public keys, so that the
+    /// index of a signer is the same as its index in the federations.
+    pub fn new(
+        signer_count: usize,
+        plans: &[FederationPlan],
+        options: DevnetOptions,
+    ) -> Result<Self, Error> {
+        validate_plans(signer_count, plans)?;
+
+        let mut keys: Vec<(PrivateKey, PublicKey)> =
+            (0..signer_count).map(|_| generate_key_pair()).collect();
+        keys.sort_by(|a, b| Ord::cmp(&a.1.key.serialize()[..], &b.1.key.serialize()[..]));
+
+        let mut federations: Vec<Vec<Federation>> = vec![vec![]; signer_count];
+        let mut genesis_key = None;
+        for plan in plans {
+            let (aggregated_public_key, mut nodevss) = create_nodevss(&keys, plan)?;
+            genesis_key.get_or_insert(aggregated_public_key);
+            for (i, (_, public_key)) in keys.iter().enumerate() {
+                let nodevss = nodevss.remove(&i);
+                federations[i].push(Federation::new(
+                    *public_key,
+                    plan.block_height,
+                    nodevss.as_ref().map(|_| plan.threshold),
+                    nodevss,
+                    aggregated_public_key,
+                ));
+            }
+        }
+
+        let signers = keys
+            .into_iter()
+            .zip(federations)
+            .map(|((private_key, public_key), federations)| {
+                let federations = Federations::new(federations);
+                federations.validate()?;
+                Ok(DevnetSigner {
+                    private_key,
+                    public_key,
+                    federations,
+                    control: Arc::new(Mutex::new(None)),
+                    node: None,
+                })
+            })
+            .collect::<Result<Vec<DevnetSigner>, Error>>()?;
+
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time is before the unix epoch")
+            .as_secs() as u32;
+        let genesis = genesis_block(
+            genesis_key.expect("plans are not empty"),
+            &address(&signers[0].private_key),
+            time,
+        );
+        Ok(Devnet {
+            chain: MockChain::new(genesis)?,
+            network: InMemoryNetwork::new(),
+            options,
+            signers,
+        })
+    }
+
+    pub fn chain(&self) -> &MockChain {
+        &self.chain
+    }
+
+    pub fn len(&self) -> usize {
+        self.signers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty()
+    }
+
+    pub fn signer_id(&self, index: usize) -> SignerID {
+        SignerID::new(self.signers[index].public_key)
+    }
+
+    pub fn private_key(&self, index: usize) -> PrivateKey {
+        self.signers[index].private_key
+    }
+
+    pub fn federations(&self, index: usize) -> &Federations {
+        &self.signers[index].federations
+    }
+
+    pub fn is_running(&self, index: usize) -> bool {
+        self.signers[index].node.is_some()
+    }
+
+    /// Starts the nodes of all signers.
+    pub fn start(&mut self) -> Result<(), Error> {
+        for index in 0..self.signers.len() {
+            if !self.is_running(index) {
+                self.start_node(index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops the nodes of all signers.
+    pub fn stop(&mut self) {
+        for index in 0..self.signers.len() {
+            self.stop_node(index);
+        }
+    }
+
+    /// Starts the node of the signer on its own thread. The node starts from joining rounds as
+    /// `tapyrus-signerd` does after restart.
+    pub fn start_node(&mut self, index: usize) -> Result<(), Error> {
+        let signer = self
+            .signers
+            .get_mut(index)
+            .ok_or_else(|| Error::InvalidArgs(format!("No signer at {}", index)))?;
+        if signer.node.is_some() {
+            return Err(Error::InvalidArgs(format!(
+                "The node of signer {} is already running.",
+                index
+            )));
+        }
+
+        let (stop, stop_receiver) = channel();
+        let (control, control_receiver) = channel();
+        let private_key = signer.private_key;
+        let public_key = signer.public_key;
+        let federations = signer.federations.clone();
+        let chain = self.chain.clone();
+        let manager = self.network.manager();
+        let options = self.options.clone();
+        let thread = std::thread::Builder::new()
+            .name(format!("DevnetNode{}", index))
+            .spawn(move || {
+                let mut params = NodeParameters::new(
+                    address(&private_key),
+                    public_key,
+                    chain,
+                    options.round_duration,
+                    options.round_limit,
+                    true,
+                    federations,
+                );
+                params.heartbeat_interval = options.heartbeat_interval;
+                params.tip_check_interval = options.tip_check_interval;
+                let mut node = SignerNode::new(manager, params);
+                node.stop_handler(stop_receiver);
+                node.control_handler(control_receiver);
+                node.start();
+            })?;
+        *signer.control.lock().unwrap() = Some(control);
+        signer.node = Some(DevnetNode { stop, thread });
+        log::info!("Started the node of signer {}({})", index, public_key);
+        Ok(())
+    }
+
+    /// Stops the node of the signer like a crash. It does nothing if the node is not running.
+    pub fn stop_node(&mut self, index: usize) {
+        let signer = match self.signers.get_mut(index) {
+            Some(signer) => signer,
+            None => return,
+        };
+        if let Some(node) = signer.node.take() {
+            *signer.control.lock().unwrap() = None;
+            let _ = node.stop.send(1);
+            if let Err(e) = node.thread.join() {
+                log::warn!("The node of signer {} panicked: {:?}", index, e);
+            }
+            self.network.leave(&SignerID::new(signer.public_key));
+            log::info!("Stopped the node of signer {}", index);
+        }
+    }
+
+    /// Drops all messages from and to the signer until it is reconnected.
+    pub fn disconnect(&self, index: usize) {
+        self.network.disconnect(&self.signer_id(index));
+        log::info!("Disconnected signer {}", index);
+    }
+
+    pub fn reconnect(&self, index: usize) {
+        self.network.reconnect(&self.signer_id(index));
+        log::info!("Reconnected signer {}", index);
+    }
+
+    /// Sends the control command to the node of the signer and waits for the response.
+    pub fn request(&self, index: usize, command: ControlCommand) -> Result<ControlResponse, Error> {
+        let control = self
+            .signers
+            .get(index)
+            .ok_or_else(|| Error::InvalidArgs(format!("No signer at {}", index)))?
+            .control
+            .clone();
+        let (responder, receiver) = channel();
+        forward(&control, ControlRequest { command, responder });
+        receiver
+            .recv_timeout(Duration::from_secs(RESPONSE_TIMEOUT_SECS))
+            .map_err(|_| Error::ControlRequest(503, "The node didn't respond.".to_string()))
+    }
+
+    /// Serves the control endpoint of the node of the signer on the Unix domain socket at the
+    /// path, so that `tapyrus-signer-cli` can operate the node.
+    pub fn serve_control(&self, index: usize, path: &Path) -> Result<(), Error> {
+        let control = self
+            .signers
+            .get(index)
+            .ok_or_else(|| Error::InvalidArgs(format!("No signer at {}", index)))?
+            .control
+            .clone();
+        let (_handler, receiver) = ControlServer::bind(path)?.start();
+        std::thread::Builder::new()
+            .name(format!("DevnetControl{}", index))
+            .spawn(move || {
+                for request in receiver.iter() {
+                    forward(&control, request);
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Waits until the chain reaches the block height. Returns false on timeout.
+    pub fn wait_for_height(&self, block_height: u64, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while self.chain.height() < block_height {
+            if start.elapsed() > timeout {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        true
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Passes the request to the running node, or answers it if the node is stopped.
+fn forward(control: &Mutex<Option<Sender<ControlRequest>>>, request: ControlRequest) {
+    let request = match control.lock().unwrap().as_ref() {
+        Some(sender) => match sender.send(request) {
+            Ok(()) => return,
+            Err(e) => e.0,
+        },
+        None => request,
+    };
+    let _ = request
+        .responder
+        .send(ControlResponse::error(503, "The node is not running."));
+}
+
+fn validate_plans(signer_count: usize, plans: &[FederationPlan]) -> Result<(), Error> {
+    match plans.first() {
+        Some(plan) if plan.block_height == 0 => {}
+        _ => {
+            return Err(Error::InvalidArgs(
+                "The first federation must start at 0 height.".to_string(),
+            ))
+        }
+    }
+    for plan in plans {
+        if plan.members.iter().any(|i| *i >= signer_count) {
+            return Err(Error::InvalidArgs(format!(
+                "The federation at {} height has a member out of {} signers.",
+                plan.block_height, signer_count
+            )));
+        }
+        let mut members = plan.members.clone();
+        members.sort_unstable();
+        members.dedup();
+        if members.len() != plan.members.len() {
+            return Err(Error::InvalidArgs(format!(
+                "The federation at {} height has the same member twice.",
+                plan.block_height
+            )));
+        }
+        if plan.threshold == 0 || plan.threshold as usize > members.len() {
+            return Err(Error::InvalidArgs(format!(
+                "The threshold of the federation at {} height should be between 1 and the number of members.",
+                plan.block_height
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Creates the nodevss of the members of the federation as `tapyrus-setup createnodevss` does,
+/// and returns the aggregated public key and the nodevss of each member.
+fn create_nodevss(
+    keys: &[(PrivateKey, PublicKey)],
+    plan: &FederationPlan,
+) -> Result<(PublicKey, BTreeMap<usize, Vec<Vss>>), Error> {
+    let mut members = plan.members.clone();
+    members.sort_unstable();
+
+    let mut nodevss: BTreeMap<usize, Vec<Vss>> = members.iter().map(|i| (*i, vec![])).collect();
+    let mut points = vec![];
+    for sender in &members {
+        let (private_key, public_key) = &keys[*sender];
+        let (vss_scheme, secret_shares) =
+            Vss::create_node_shares(private_key, plan.threshold as usize, members.len());
+        points.push(vss_scheme.commitments[0]);
+        let commitments: Vec<Commitment> = vss_scheme
+            .commitments
+            .iter()
+            .map(Commitment::from)
+            .collect();
+        for (j, receiver) in members.iter().enumerate() {
+            nodevss.get_mut(receiver).unwrap().push(Vss::new(
+                *public_key,
+                keys[*receiver].1,
+                commitments.clone(),
+                secret_shares[j],
+                commitments.clone(),
+                secret_shares[j],
+            ));
+        }
+    }
+    let aggregated_public_key = PublicKey::from_slice(&sum_point(&points).pk_to_key_slice())
+        .map_err(|_| Error::InvalidKey)?;
+    Ok((aggregated_public_key, nodevss))
+}
+
+fn address(private_key: &PrivateKey) -> Address {
+    let secp = tapyrus::secp256k1::Secp256k1::new();
+    Address::p2pkh(&private_key.public_key(&secp), private_key.network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tapyrus::blockdata::block::XField;
+
+    fn plan(block_height: u64, threshold: u8, members: Vec<usize>) -> FederationPlan {
+        FederationPlan {
+            block_height,
+            threshold,
+            members,
+        }
+    }
+
+    #[test]
+    fn test_federation_plan_from_str() {
+        assert_eq!(
+            FederationPlan::from_str("100:2:0,1,3").unwrap(),
+            plan(100, 2, vec![0, 1, 3])
+        );
+        assert!(FederationPlan::from_str("100:2").is_err());
+        assert!(FederationPlan::from_str("100:2:0,a").is_err());
+        assert!(FederationPlan::from_str("-1:2:0,1").is_err());
+    }
+
+    #[test]
+    fn test_new() {
+        let plans = vec![plan(0, 2, vec![0, 1, 2]), plan(10, 3, vec![1, 2, 3])];
+        let devnet = Devnet::new(4, &plans, DevnetOptions::default()).unwrap();
+        assert_eq!(devnet.len(), 4);
+        for i in 0..3 {
+            assert!(devnet.signer_id(i) < devnet.signer_id(i + 1));
+        }
+
+        let aggregated = |i: usize, block_height: u64| {
+            devnet
+                .federations(i)
+                .get_by_block_height(block_height)
+                .aggregated_public_key()
+        };
+        for i in 0..4 {
+            assert_eq!(aggregated(i, 0), aggregated(0, 0));
+            assert_eq!(aggregated(i, 10), aggregated(0, 10));
+        }
+        assert_ne!(aggregated(0, 0), aggregated(0, 10));
+        assert_eq!(
+            devnet.chain().tip().header.xfield,
+            XField::AggregatePublicKey(aggregated(0, 0))
+        );
+
+        let federation = devnet.federations(0).get_by_block_height(0);
+        assert_eq!(federation.threshold(), Some(2));
+        assert_eq!(
+            federation.signers(),
+            (0..3).map(|i| devnet.signer_id(i)).collect::<Vec<_>>()
+        );
+        assert!(!devnet.federations(0).get_by_block_height(10).is_member());
+        assert!(!devnet.federations(3).get_by_block_height(0).is_member());
+        assert_eq!(
+            devnet.federations(3).get_by_block_height(10).threshold(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_invalid_plans() {
+        let options = DevnetOptions::default;
+        assert!(Devnet::new(3, &[], options()).is_err());
+        assert!(Devnet::new(3, &[plan(1, 2, vec![0, 1, 2])], options()).is_err());
+        assert!(Devnet::new(3, &[plan(0, 2, vec![0, 1, 3])], options()).is_err());
+        assert!(Devnet::new(3, &[plan(0, 2, vec![0, 1, 1])], options()).is_err());
+        assert!(Devnet::new(3, &[plan(0, 0, vec![0, 1, 2])], options()).is_err());
+        assert!(Devnet::new(3, &[plan(0, 4, vec![0, 1, 2])], options()).is_err());
+    }
+
+    #[test]
+    fn test_produce_blocks() {
+        // Rounds start right after the previous block, so that the test takes a few seconds.
+        let plans = vec![plan(0, 2, vec![0, 1, 2]), plan(3, 2, vec![1, 2])];
+        let options = DevnetOptions {
+            round_duration: 0,
+            round_limit: 2,
+            heartbeat_interval: 1,
+            tip_check_interval: 1,
+        };
+        let mut devnet = Devnet::new(3, &plans, options).unwrap();
+        devnet.start().unwrap();
+        assert!(devnet.wait_for_height(4, Duration::from_secs(60)));
+
+        // The new federation signs the blocks from 3 height.
+        let new_key = devnet
+            .federations(1)
+            .get_by_block_height(3)
+            .aggregated_public_key();
+        assert_eq!(
+            devnet.chain().block(2).unwrap().header.xfield,
+            XField::AggregatePublicKey(new_key)
+        );
+        assert_eq!(devnet.chain().aggregated_public_key(), new_key);
+
+        // The new federation needs both signers 1 and 2, so that no block is produced while
+        // signer 1 is disconnected. Wait for the round in progress to finish first.
+        devnet.disconnect(1);
+        std::thread::sleep(Duration::from_secs(3));
+        let height = devnet.chain().height();
+        assert!(!devnet.wait_for_height(height + 1, Duration::from_secs(3)));
+
+        // The blocks are produced again after reconnecting.
+        devnet.reconnect(1);
+        assert!(devnet.wait_for_height(height + 2, Duration::from_secs(60)));
+
+        // The stopped node doesn't answer.
+        devnet.stop_node(0);
+        assert!(!devnet.is_running(0));
+        match devnet.request(0, ControlCommand::Status) {
+            Ok(response) => assert_eq!(response.code, 503),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(devnet.request(1, ControlCommand::Status).unwrap().code, 200);
+        devnet.stop();
+    }
+}
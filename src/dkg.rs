@@ -0,0 +1,1093 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Distributed key generation ceremony over the signer network.
+//!
+//! The members of a proposed federation run the key generation phases of `multi_party_schnorr`:
+//! 1. Each member broadcasts a commitment to its public key share.
+//! 2. After all commitments arrive, each member broadcasts the decommitment.
+//! 3. After all decommitments are verified, each member sends a secret share and its VSS to each
+//!    other member.
+//! 4. After all shares are verified, each member constructs its nodevss and the aggregated public
+//!    key, and broadcasts `Completed`.
+//!
+//! `RefreshCeremony` re-randomizes the nodevss of an existing federation without changing the
+//! aggregated public key. Each member shares a random polynomial whose constant term is zero, and
+//! adds the shares to its current shares. Shares from before the refresh can't be combined with
+//! the refreshed ones.
+//!
+//! Messages are kept and sent again by the driver until the ceremony finishes, so that members can
+//! join late. Secret shares are masked with a key derived from ECDH between the sender and the
+//! receiver.
+//!
+//! Each run of a ceremony has a random session nonce, which one member creates and announces to
+//! the others. It is a part of the session id, so that a rerun gets new masks, and the messages
+//! left from an earlier run are ignored.
+
+use crate::crypto::multi_party_schnorr::{KeyGenBroadcastMessage1, Keys, Parameters};
+use crate::crypto::vss::{Commitment, Vss};
+use crate::errors::Error;
+use crate::federation::Federation;
+use crate::net::{ConnectionManager, Message, MessageType, SignerID};
+use crate::rpc::Rpc;
+use crate::sign::Sign;
+use crate::signer_node::NodeParameters;
+use curv::arithmetic::traits::Converter;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use curv::{BigInt, FE, GE};
+use redis::ControlFlow;
+use secp256k1::rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+use tapyrus::{PrivateKey, PublicKey};
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum DkgMessage {
+    /// Hash commitment to the public key share of the sender. It is hex string.
+    Commitment { session: String, commitment: String },
+    /// Public key share of the sender and the blind factor of the commitment.
+    Decommitment {
+        session: String,
+        y_i: GE,
+        blind_factor: String,
+    },
+    /// VSS of the sender and the masked secret share for the receiver.
+    Share {
+        session: String,
+        vss: VerifiableSS,
+        share: FE,
+    },
+    /// Commitments to the coefficients of the refresh polynomial of the sender except the constant
+    /// term, which is zero.
+    RefreshCommitments {
+        session: String,
+        commitments: Vec<GE>,
+    },
+    /// The masked refresh share for the receiver.
+    RefreshShare { session: String, share: FE },
+    /// The sender has constructed its nodevss.
+    Completed { session: String },
+}
+
+impl DkgMessage {
+    pub fn session(&self) -> &str {
+        match self {
+            DkgMessage::Commitment { session, .. } => session,
+            DkgMessage::Decommitment { session, .. } => session,
+            DkgMessage::Share { session, .. } => session,
+            DkgMessage::RefreshCommitments { session, .. } => session,
+            DkgMessage::RefreshShare { session, .. } => session,
+            DkgMessage::Completed { session } => session,
+        }
+    }
+}
+
+/// Length of the session nonce in bytes.
+pub const SESSION_NONCE_LEN: usize = 16;
+
+/// Returns a random session nonce for a new run of a ceremony.
+pub fn new_session_nonce() -> [u8; SESSION_NONCE_LEN] {
+    let mut nonce = [0u8; SESSION_NONCE_LEN];
+    thread_rng().fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Returns the identifier of the ceremony. Members must agree on the block height, the threshold,
+/// the public keys of the federation and the session nonce of the run.
+pub fn session_id(
+    block_height: u64,
+    threshold: u8,
+    public_keys: &Vec<PublicKey>,
+    nonce: &[u8; SESSION_NONCE_LEN],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(&block_height.to_be_bytes());
+    hasher.input(&[threshold]);
+    for public_key in public_keys {
+        hasher.input(&public_key.key.serialize()[..]);
+    }
+    hasher.input(&nonce[..]);
+    hex::encode(&hasher.result()[..])
+}
+
+/// Returns the identifier of the refresh ceremony for the federation. Members must agree on the
+/// federation to refresh, the block height of the refreshed federation and the session nonce of
+/// the run.
+pub fn refresh_session_id(
+    federation: &Federation,
+    block_height: u64,
+    nonce: &[u8; SESSION_NONCE_LEN],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input(b"refresh");
+    hasher.input(&federation.block_height().to_be_bytes());
+    hasher.input(&block_height.to_be_bytes());
+    hasher.input(&federation.aggregated_public_key().key.serialize()[..]);
+    for signer in federation.signers() {
+        hasher.input(&signer.pubkey.key.serialize()[..]);
+    }
+    hasher.input(&nonce[..]);
+    hex::encode(&hasher.result()[..])
+}
+
+/// A protocol between the members which is driven by `run_ceremony`.
+pub trait Ceremony {
+    fn signer_id(&self) -> SignerID;
+
+    /// Returns the messages which this member has sent. The driver should send them again until
+    /// the ceremony finishes.
+    fn messages(&self) -> &Vec<Message>;
+
+    /// Returns true if all members have constructed their keys.
+    fn is_finished(&self) -> bool;
+
+    /// Processes the message from other member. Messages of other ceremonies are ignored.
+    fn process(&mut self, message: &Message) -> Result<(), Error>;
+}
+
+fn from_hex(s: &str) -> Result<BigInt, Error> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::Dkg(format!("invalid hex string: {}", s)));
+    }
+    Ok(BigInt::from_hex(s))
+}
+
+fn to_point(public_key: &PublicKey) -> GE {
+    let bytes = public_key.key.serialize_uncompressed();
+    GE::from_bytes(&bytes[1..]).expect("failed to convert to point")
+}
+
+fn to_secret(private_key: &PrivateKey) -> Result<FE, Error> {
+    Sign::private_key_to_big_int(private_key.key)
+        .map(|i| ECScalar::from(&i))
+        .ok_or(Error::InvalidKey)
+}
+
+/// Returns the mask for the secret share between the member who has the secret and the other
+/// member.
+fn mask(session: &str, secret: &FE, other: &PublicKey) -> FE {
+    let point = &to_point(other) * secret;
+    let mut hasher = Sha256::new();
+    hasher.input(session.as_bytes());
+    hasher.input(&point.get_element().serialize()[..]);
+    ECScalar::from(&BigInt::from(&hasher.result()[..]))
+}
+
+fn insert<V: PartialEq>(
+    map: &mut BTreeMap<SignerID, V>,
+    sender: SignerID,
+    value: V,
+    name: &str,
+) -> Result<(), Error> {
+    match map.get(&sender) {
+        Some(v) if *v != value => Err(Error::Dkg(format!(
+            "{} received different {}.",
+            sender, name
+        ))),
+        Some(_) => Ok(()),
+        None => {
+            map.insert(sender, value);
+            Ok(())
+        }
+    }
+}
+
+pub struct DkgCeremony {
+    session: String,
+    block_height: u64,
+    threshold: u8,
+    secret: FE,
+    signer_id: SignerID,
+    /// Public keys of the members. It is sorted.
+    public_keys: Vec<PublicKey>,
+    keys: Keys,
+    commitments: BTreeMap<SignerID, BigInt>,
+    decommitments: BTreeMap<SignerID, (GE, BigInt)>,
+    shares: BTreeMap<SignerID, (VerifiableSS, FE)>,
+    completed: HashSet<SignerID>,
+    decommitted: bool,
+    distributed: bool,
+    /// Messages which this member has sent.
+    messages: Vec<Message>,
+    federation: Option<Federation>,
+}
+
+impl DkgCeremony {
+    pub fn new(
+        private_key: &PrivateKey,
+        public_keys: &Vec<PublicKey>,
+        threshold: u8,
+        block_height: u64,
+        nonce: &[u8; SESSION_NONCE_LEN],
+    ) -> Result<Self, Error> {
+        let secp = tapyrus::secp256k1::Secp256k1::new();
+        let public_key = PublicKey::from_private_key(&secp, private_key);
+
+        let mut public_keys = public_keys.clone();
+        NodeParameters::<Rpc>::sort_publickey(&mut public_keys);
+        public_keys.dedup();
+        let index =
+            public_keys
+                .iter()
+                .position(|pk| *pk == public_key)
+                .ok_or(Error::InvalidArgs(
+                    "public-key should include the key of the private-key".to_string(),
+                ))?;
+        if threshold < 1 || threshold as usize > public_keys.len() {
+            return Err(Error::InvalidArgs(format!(
+                "threshold should be between 1 and the number of public keys({})",
+                public_keys.len()
+            )));
+        }
+
+        let secret = to_secret(private_key)?;
+        let keys = Keys::phase1_create(index + 1);
+        let (commitment, blind_factor) = keys.phase1_broadcast();
+        let signer_id = SignerID::new(public_key);
+        let session = session_id(block_height, threshold, &public_keys, nonce);
+
+        let mut ceremony = DkgCeremony {
+            session: session.clone(),
+            block_height,
+            threshold,
+            secret,
+            signer_id,
+            public_keys,
+            keys,
+            commitments: BTreeMap::new(),
+            decommitments: BTreeMap::new(),
+            shares: BTreeMap::new(),
+            completed: HashSet::new(),
+            decommitted: false,
+            distributed: false,
+            messages: vec![],
+            federation: None,
+        };
+        ceremony
+            .commitments
+            .insert(signer_id, commitment.com.clone());
+        ceremony
+            .decommitments
+            .insert(signer_id, (ceremony.keys.y_i, blind_factor));
+        ceremony.push(
+            DkgMessage::Commitment {
+                session,
+                commitment: commitment.com.to_hex(),
+            },
+            None,
+        );
+        ceremony.advance()?;
+        Ok(ceremony)
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Returns the federation which has the nodevss and the aggregated public key, after the keys
+    /// are constructed.
+    pub fn federation(&self) -> Option<&Federation> {
+        self.federation.as_ref()
+    }
+
+    fn advance(&mut self) -> Result<(), Error> {
+        let n = self.public_keys.len();
+
+        // Phase 1: decommit after all commitments are received.
+        if !self.decommitted && self.commitments.len() == n {
+            self.decommitted = true;
+            let (y_i, blind_factor) = self.decommitments[&self.signer_id].clone();
+            self.push(
+                DkgMessage::Decommitment {
+                    session: self.session.clone(),
+                    y_i,
+                    blind_factor: blind_factor.to_hex(),
+                },
+                None,
+            );
+        }
+
+        // Phase 2: verify decommitments and distribute shares.
+        if self.decommitted && !self.distributed && self.decommitments.len() == n {
+            self.distributed = true;
+            let commitments = self.sorted(&self.commitments);
+            let decommitments = self.sorted(&self.decommitments);
+            let (vss, secret_shares, _) = self.keys.phase1_verify_com_phase2_distribute(
+                &self.parameters(),
+                &decommitments.iter().map(|d| d.1.clone()).collect(),
+                &decommitments.iter().map(|d| d.0).collect(),
+                &commitments
+                    .into_iter()
+                    .map(|com| KeyGenBroadcastMessage1 { com })
+                    .collect(),
+                &self.parties(),
+            )?;
+            for (public_key, share) in self.public_keys.clone().iter().zip(secret_shares) {
+                let receiver = SignerID::new(*public_key);
+                if receiver == self.signer_id {
+                    self.shares.insert(receiver, (vss.clone(), share));
+                } else {
+                    let share = share + mask(&self.session, &self.secret, public_key);
+                    self.push(
+                        DkgMessage::Share {
+                            session: self.session.clone(),
+                            vss: vss.clone(),
+                            share,
+                        },
+                        Some(receiver),
+                    );
+                }
+            }
+        }
+
+        // Phase 3: verify shares and construct the keys.
+        if self.distributed && self.federation.is_none() && self.shares.len() == n {
+            self.construct_keys()?;
+            self.completed.insert(self.signer_id);
+            self.push(
+                DkgMessage::Completed {
+                    session: self.session.clone(),
+                },
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    fn construct_keys(&mut self) -> Result<(), Error> {
+        let decommitments = self.sorted(&self.decommitments);
+        let shares = self.sorted(&self.shares);
+        let shared_keys = self.keys.phase2_verify_vss_construct_keypair(
+            &self.parameters(),
+            &decommitments.iter().map(|d| d.0).collect(),
+            &shares.iter().map(|s| s.1).collect(),
+            &shares.iter().map(|s| s.0.clone()).collect(),
+            &self.keys.party_index,
+        )?;
+
+        let nodevss = self
+            .public_keys
+            .iter()
+            .zip(shares)
+            .map(|(sender, (vss, share))| {
+                let commitments: Vec<Commitment> =
+                    vss.commitments.iter().map(Commitment::from).collect();
+                Vss::new(
+                    *sender,
+                    self.signer_id.pubkey,
+                    commitments.clone(),
+                    share,
+                    commitments,
+                    share,
+                )
+            })
+            .collect();
+        let aggregated_public_key = PublicKey::from_slice(&shared_keys.y.pk_to_key_slice())
+            .map_err(|_| Error::InvalidKey)?;
+
+        let federation = Federation::new(
+            self.signer_id.pubkey,
+            self.block_height,
+            Some(self.threshold),
+            Some(nodevss),
+            aggregated_public_key,
+        );
+        federation
+            .validate()
+            .map_err(|e| Error::Dkg(format!("constructed nodevss is invalid. {:?}", e)))?;
+        self.federation = Some(federation);
+        Ok(())
+    }
+
+    fn push(&mut self, message: DkgMessage, receiver_id: Option<SignerID>) {
+        self.messages.push(Message {
+            message_type: MessageType::Dkg(message),
+            sender_id: self.signer_id,
+            receiver_id,
+        });
+    }
+
+    /// Returns values ordered by the index of the members.
+    fn sorted<V: Clone>(&self, map: &BTreeMap<SignerID, V>) -> Vec<V> {
+        self.public_keys
+            .iter()
+            .map(|pk| map[&SignerID::new(*pk)].clone())
+            .collect()
+    }
+
+    fn parameters(&self) -> Parameters {
+        Parameters {
+            threshold: (self.threshold - 1) as usize,
+            share_count: self.public_keys.len(),
+        }
+    }
+
+    fn parties(&self) -> Vec<usize> {
+        (1..=self.public_keys.len()).collect()
+    }
+}
+
+impl Ceremony for DkgCeremony {
+    fn signer_id(&self) -> SignerID {
+        self.signer_id
+    }
+
+    fn messages(&self) -> &Vec<Message> {
+        &self.messages
+    }
+
+    fn is_finished(&self) -> bool {
+        self.federation.is_some() && self.completed.len() == self.public_keys.len()
+    }
+
+    fn process(&mut self, message: &Message) -> Result<(), Error> {
+        let dkg_message = match &message.message_type {
+            MessageType::Dkg(m) if m.session() == self.session => m,
+            _ => return Ok(()),
+        };
+        let sender = message.sender_id;
+        if sender == self.signer_id || !self.public_keys.contains(&sender.pubkey) {
+            return Ok(());
+        }
+
+        match dkg_message {
+            DkgMessage::Commitment { commitment, .. } => {
+                let commitment = from_hex(commitment)?;
+                insert(&mut self.commitments, sender, commitment, "commitment")?;
+            }
+            DkgMessage::Decommitment {
+                y_i, blind_factor, ..
+            } => {
+                let value = (*y_i, from_hex(blind_factor)?);
+                insert(&mut self.decommitments, sender, value, "decommitment")?;
+            }
+            DkgMessage::Share { vss, share, .. } => {
+                if message.receiver_id != Some(self.signer_id) {
+                    return Ok(());
+                }
+                let share =
+                    share.sub(&mask(&self.session, &self.secret, &sender.pubkey).get_element());
+                insert(&mut self.shares, sender, (vss.clone(), share), "share")?;
+            }
+            DkgMessage::Completed { .. } => {
+                self.completed.insert(sender);
+            }
+            _ => {}
+        }
+        self.advance()
+    }
+}
+
+pub struct RefreshCeremony {
+    session: String,
+    block_height: u64,
+    secret: FE,
+    signer_id: SignerID,
+    /// The federation to refresh.
+    current: Federation,
+    /// Coefficients of the refresh polynomial except the constant term.
+    coefficients: Vec<FE>,
+    commitments: BTreeMap<SignerID, Vec<GE>>,
+    shares: BTreeMap<SignerID, FE>,
+    completed: HashSet<SignerID>,
+    /// Messages which this member has sent.
+    messages: Vec<Message>,
+    federation: Option<Federation>,
+}
+
+impl RefreshCeremony {
+    /// Starts refreshing the nodevss of `current`. The refreshed federation gets started at the
+    /// block height.
+    pub fn new(
+        private_key: &PrivateKey,
+        current: &Federation,
+        block_height: u64,
+        nonce: &[u8; SESSION_NONCE_LEN],
+    ) -> Result<Self, Error> {
+        let secp = tapyrus::secp256k1::Secp256k1::new();
+        let public_key = PublicKey::from_private_key(&secp, private_key);
+        if !current.is_member()
+            || current
+                .nodevss()
+                .iter()
+                .any(|vss| vss.receiver_public_key != public_key)
+        {
+            return Err(Error::InvalidArgs(
+                "the signer of the private-key should be a member of the federation".to_string(),
+            ));
+        }
+        if block_height <= current.block_height() {
+            return Err(Error::InvalidArgs(format!(
+                "block-height should be greater than the block height of the federation({})",
+                current.block_height()
+            )));
+        }
+        let threshold = current
+            .threshold()
+            .expect("threshold should be set to members");
+        if threshold < 2 {
+            return Err(Error::InvalidArgs(
+                "the shares of the federation whose threshold is 1 can not be refreshed"
+                    .to_string(),
+            ));
+        }
+
+        let session = refresh_session_id(current, block_height, nonce);
+        let signer_id = SignerID::new(public_key);
+        let coefficients: Vec<FE> = (1..threshold).map(|_| ECScalar::new_random()).collect();
+        let commitments: Vec<GE> = coefficients.iter().map(|a| &GE::generator() * a).collect();
+
+        let mut ceremony = RefreshCeremony {
+            session: session.clone(),
+            block_height,
+            secret: to_secret(private_key)?,
+            signer_id,
+            current: current.clone(),
+            coefficients,
+            commitments: BTreeMap::new(),
+            shares: BTreeMap::new(),
+            completed: HashSet::new(),
+            messages: vec![],
+            federation: None,
+        };
+        ceremony.commitments.insert(signer_id, commitments.clone());
+        ceremony.push(
+            DkgMessage::RefreshCommitments {
+                session: session.clone(),
+                commitments,
+            },
+            None,
+        );
+        for (index, receiver) in current.signers().into_iter().enumerate() {
+            let share = ceremony.evaluate(index + 1);
+            if receiver == signer_id {
+                ceremony.shares.insert(receiver, share);
+            } else {
+                let share = share + mask(&session, &ceremony.secret, &receiver.pubkey);
+                ceremony.push(
+                    DkgMessage::RefreshShare {
+                        session: session.clone(),
+                        share,
+                    },
+                    Some(receiver),
+                );
+            }
+        }
+        Ok(ceremony)
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Returns the refreshed federation, after the keys are constructed.
+    pub fn federation(&self) -> Option<&Federation> {
+        self.federation.as_ref()
+    }
+
+    /// Returns the value of the refresh polynomial at the index of the member. The index is 1-based.
+    fn evaluate(&self, index: usize) -> FE {
+        let x: FE = ECScalar::from(&BigInt::from(index as u64));
+        let mut iter = self.coefficients.iter().rev();
+        let head = *iter.next().expect("coefficients should not be empty");
+        iter.fold(head, |acc, a| acc * x + a) * x
+    }
+
+    fn advance(&mut self) -> Result<(), Error> {
+        let n = self.current.signers().len();
+        if self.federation.is_none() && self.commitments.len() == n && self.shares.len() == n {
+            self.construct_keys()?;
+            self.completed.insert(self.signer_id);
+            self.push(
+                DkgMessage::Completed {
+                    session: self.session.clone(),
+                },
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    fn construct_keys(&mut self) -> Result<(), Error> {
+        let threshold = self.current.threshold().expect("threshold should be set");
+        let index = self.current.node_index() + 1;
+        let mut nodevss = vec![];
+        for vss in self.current.nodevss() {
+            let sender = SignerID::new(vss.sender_public_key);
+            let refresh_commitments = &self.commitments[&sender];
+            if refresh_commitments.len() != (threshold - 1) as usize {
+                return Err(Error::Dkg(format!(
+                    "{} sent wrong number of commitments.",
+                    sender
+                )));
+            }
+
+            // The constant term is kept, so that the aggregated public key is not changed.
+            let mut commitments = vec![vss.positive_commitments[0].to_point()];
+            for (c, d) in vss.positive_commitments[1..]
+                .iter()
+                .zip(refresh_commitments)
+            {
+                commitments.push(c.to_point() + d);
+            }
+            let share = vss.positive_secret + self.shares[&sender];
+            let refreshed = VerifiableSS {
+                parameters: ShamirSecretSharing {
+                    threshold: (threshold - 1) as usize,
+                    share_count: self.current.nodevss().len(),
+                },
+                commitments,
+            };
+            if refreshed.validate_share(&share, index).is_err() {
+                return Err(Error::Dkg(format!("{} sent invalid share.", sender)));
+            }
+
+            let commitments: Vec<Commitment> =
+                refreshed.commitments.iter().map(Commitment::from).collect();
+            nodevss.push(Vss::new(
+                vss.sender_public_key,
+                vss.receiver_public_key,
+                commitments.clone(),
+                share,
+                commitments,
+                share,
+            ));
+        }
+
+        let federation = Federation::new(
+            self.signer_id.pubkey,
+            self.block_height,
+            Some(threshold),
+            Some(nodevss),
+            self.current.aggregated_public_key(),
+        );
+        federation
+            .validate()
+            .map_err(|e| Error::Dkg(format!("refreshed nodevss is invalid. {:?}", e)))?;
+        self.federation = Some(federation);
+        Ok(())
+    }
+
+    fn push(&mut self, message: DkgMessage, receiver_id: Option<SignerID>) {
+        self.messages.push(Message {
+            message_type: MessageType::Dkg(message),
+            sender_id: self.signer_id,
+            receiver_id,
+        });
+    }
+}
+
+impl Ceremony for RefreshCeremony {
+    fn signer_id(&self) -> SignerID {
+        self.signer_id
+    }
+
+    fn messages(&self) -> &Vec<Message> {
+        &self.messages
+    }
+
+    fn is_finished(&self) -> bool {
+        self.federation.is_some() && self.completed.len() == self.current.signers().len()
+    }
+
+    fn process(&mut self, message: &Message) -> Result<(), Error> {
+        let dkg_message = match &message.message_type {
+            MessageType::Dkg(m) if m.session() == self.session => m,
+            _ => return Ok(()),
+        };
+        let sender = message.sender_id;
+        if sender == self.signer_id || !self.current.signers().contains(&sender) {
+            return Ok(());
+        }
+
+        match dkg_message {
+            DkgMessage::RefreshCommitments { commitments, .. } => {
+                insert(
+                    &mut self.commitments,
+                    sender,
+                    commitments.clone(),
+                    "commitments",
+                )?;
+            }
+            DkgMessage::RefreshShare { share, .. } => {
+                if message.receiver_id != Some(self.signer_id) {
+                    return Ok(());
+                }
+                let share =
+                    share.sub(&mask(&self.session, &self.secret, &sender.pubkey).get_element());
+                insert(&mut self.shares, sender, share, "share")?;
+            }
+            DkgMessage::Completed { .. } => {
+                self.completed.insert(sender);
+            }
+            _ => {}
+        }
+        self.advance()
+    }
+}
+
+/// Interval to send own messages again for members which join late.
+const RESEND_INTERVAL_SECS: u64 = 10;
+
+/// Runs the ceremony over the signer network until all members construct their keys.
+pub fn run_ceremony<T: Ceremony, C: ConnectionManager>(
+    ceremony: &mut T,
+    connection_manager: &C,
+    timeout: Duration,
+) -> Result<(), Error> {
+    connection_manager.test_connection()?;
+    let (sender, receiver): (Sender<Message>, Receiver<Message>) = channel();
+    let closure = move |message: Message| match sender.send(message) {
+        Ok(_) => ControlFlow::Continue,
+        Err(_) => ControlFlow::Break(()),
+    };
+    let _handler = connection_manager.start(closure, ceremony.signer_id());
+
+    let started_at = Instant::now();
+    let mut resent_at = Instant::now();
+    let mut sent = 0;
+    loop {
+        let messages = ceremony.messages();
+        if resent_at.elapsed() > Duration::from_secs(RESEND_INTERVAL_SECS) {
+            resent_at = Instant::now();
+            sent = 0;
+        }
+        for message in &messages[sent..] {
+            match message.receiver_id {
+                Some(_) => connection_manager.send_message(message.clone()),
+                None => connection_manager.broadcast_message(message.clone()),
+            }
+        }
+        sent = messages.len();
+
+        // Checked after sending, so that the messages which are pushed by the last step, like own
+        // completion, are sent too.
+        if ceremony.is_finished() {
+            return Ok(());
+        }
+        if started_at.elapsed() > timeout {
+            return Err(Error::Dkg(
+                "timed out. Some members might not join the ceremony.".to_string(),
+            ));
+        }
+
+        match receiver.recv_timeout(Duration::from_millis(300)) {
+            Ok(message) => ceremony.process(&message)?,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(Error::Dkg("connection to the network is lost.".to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::ConnectionManagerError;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use std::cell::RefCell;
+    use std::thread::{self, JoinHandle};
+
+    const NONCE: [u8; SESSION_NONCE_LEN] = [1; SESSION_NONCE_LEN];
+
+    fn ceremonies(threshold: u8) -> Vec<DkgCeremony> {
+        TEST_KEYS
+            .key
+            .iter()
+            .map(|key| DkgCeremony::new(key, &TEST_KEYS.pubkeys(), threshold, 100, &NONCE).unwrap())
+            .collect()
+    }
+
+    /// Delivers all messages to each member until no message is sent anymore.
+    fn run<T: Ceremony>(ceremonies: &mut Vec<T>) {
+        let mut delivered = 0;
+        loop {
+            let messages: Vec<Message> = ceremonies
+                .iter()
+                .flat_map(|c| c.messages().clone())
+                .collect();
+            if messages.len() == delivered {
+                break;
+            }
+            delivered = messages.len();
+            for message in &messages {
+                for ceremony in ceremonies.iter_mut() {
+                    ceremony.process(message).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ceremony() {
+        let mut ceremonies = ceremonies(3);
+        run(&mut ceremonies);
+
+        let aggregated = ceremonies[0].federation().unwrap().aggregated_public_key();
+        for ceremony in &ceremonies {
+            assert!(ceremony.is_finished());
+            let federation = ceremony.federation().unwrap();
+            assert_eq!(federation.aggregated_public_key(), aggregated);
+            assert_eq!(federation.block_height(), 100);
+            assert_eq!(federation.threshold(), Some(3));
+            assert_eq!(federation.nodevss().len(), 5);
+        }
+
+        // The secret which is recovered from threshold shares matches the aggregated public key.
+        let shares: Vec<FE> = ceremonies[..3]
+            .iter()
+            .map(|c| c.federation().unwrap().node_secret_share().x_i)
+            .collect();
+        let vss = &ceremonies[0].shares[&ceremonies[0].signer_id].0;
+        let secret = vss.reconstruct(&[0, 1, 2], &shares);
+        let point = &GE::generator() * &secret;
+        assert_eq!(
+            PublicKey::from_slice(&point.pk_to_key_slice()).unwrap(),
+            aggregated
+        );
+    }
+
+    #[test]
+    fn test_refresh() {
+        let mut ceremonies = ceremonies(3);
+        run(&mut ceremonies);
+        let federations: Vec<Federation> = ceremonies
+            .iter()
+            .map(|c| c.federation().unwrap().clone())
+            .collect();
+
+        let mut refreshes: Vec<RefreshCeremony> = TEST_KEYS
+            .key
+            .iter()
+            .zip(&federations)
+            .map(|(key, federation)| RefreshCeremony::new(key, federation, 200, &NONCE).unwrap())
+            .collect();
+        run(&mut refreshes);
+
+        for (refresh, federation) in refreshes.iter().zip(&federations) {
+            assert!(refresh.is_finished());
+            let refreshed = refresh.federation().unwrap();
+            assert_eq!(refreshed.block_height(), 200);
+            assert_eq!(refreshed.threshold(), Some(3));
+            assert_eq!(
+                refreshed.aggregated_public_key(),
+                federation.aggregated_public_key()
+            );
+            assert_eq!(refreshed.aggregated_point(), federation.aggregated_point());
+            assert_ne!(
+                refreshed.node_secret_share().x_i,
+                federation.node_secret_share().x_i
+            );
+        }
+
+        // The refreshed shares recover the same secret, but they can't be combined with the old
+        // shares.
+        let vss = &ceremonies[0].shares[&ceremonies[0].signer_id].0;
+        let aggregated = federations[0].aggregated_public_key();
+        let recover = |shares: Vec<FE>| {
+            let point = &GE::generator() * &vss.reconstruct(&[0, 1, 2], &shares);
+            PublicKey::from_slice(&point.pk_to_key_slice()).unwrap()
+        };
+        let shares: Vec<FE> = refreshes[..3]
+            .iter()
+            .map(|c| c.federation().unwrap().node_secret_share().x_i)
+            .collect();
+        assert_eq!(recover(shares), aggregated);
+        let shares: Vec<FE> = vec![
+            federations[0].node_secret_share().x_i,
+            refreshes[1].federation().unwrap().node_secret_share().x_i,
+            refreshes[2].federation().unwrap().node_secret_share().x_i,
+        ];
+        assert_ne!(recover(shares), aggregated);
+    }
+
+    #[test]
+    fn test_refresh_invalid_args() {
+        let mut ceremonies = ceremonies(3);
+        run(&mut ceremonies);
+        let federation = ceremonies[0].federation().unwrap();
+
+        // The private key is not the one of the signer.
+        assert!(RefreshCeremony::new(&TEST_KEYS.key[1], federation, 200, &NONCE).is_err());
+        // The block height is not after the federation.
+        assert!(RefreshCeremony::new(&TEST_KEYS.key[0], federation, 100, &NONCE).is_err());
+        assert!(RefreshCeremony::new(&TEST_KEYS.key[0], federation, 200, &NONCE).is_ok());
+    }
+
+    #[test]
+    fn test_refresh_ignores_earlier_run() {
+        let mut ceremonies = ceremonies(3);
+        run(&mut ceremonies);
+
+        // The messages left from an earlier run have another session nonce.
+        let earlier = RefreshCeremony::new(
+            &TEST_KEYS.key[0],
+            ceremonies[0].federation().unwrap(),
+            200,
+            &new_session_nonce(),
+        )
+        .unwrap();
+        let mut refresh = RefreshCeremony::new(
+            &TEST_KEYS.key[1],
+            ceremonies[1].federation().unwrap(),
+            200,
+            &NONCE,
+        )
+        .unwrap();
+        for message in earlier.messages() {
+            refresh.process(message).unwrap();
+        }
+        assert_eq!(refresh.commitments.len(), 1);
+        assert_eq!(refresh.shares.len(), 1);
+    }
+
+    #[test]
+    fn test_process() {
+        let mut ceremonies = ceremonies(3);
+
+        // A message of other ceremony is ignored.
+        let other =
+            DkgCeremony::new(&TEST_KEYS.key[0], &TEST_KEYS.pubkeys(), 2, 100, &NONCE).unwrap();
+        let message = other.messages()[0].clone();
+        ceremonies[1].process(&message).unwrap();
+        assert_eq!(ceremonies[1].commitments.len(), 1);
+
+        // A message left from an earlier run of the same ceremony has another session nonce, so
+        // that it is ignored instead of conflicting with the message of this run.
+        let earlier = DkgCeremony::new(
+            &TEST_KEYS.key[0],
+            &TEST_KEYS.pubkeys(),
+            3,
+            100,
+            &new_session_nonce(),
+        )
+        .unwrap();
+        assert_ne!(earlier.session(), ceremonies[0].session());
+        let message = earlier.messages()[0].clone();
+        ceremonies[1].process(&message).unwrap();
+        assert_eq!(ceremonies[1].commitments.len(), 1);
+
+        // The same message is accepted twice, but a different commitment from the same member is
+        // an error.
+        let message = ceremonies[0].messages()[0].clone();
+        ceremonies[1].process(&message).unwrap();
+        ceremonies[1].process(&message).unwrap();
+        assert_eq!(ceremonies[1].commitments.len(), 2);
+        let mut conflicted = message.clone();
+        conflicted.message_type = MessageType::Dkg(DkgMessage::Commitment {
+            session: ceremonies[0].session().to_string(),
+            commitment: "01".to_string(),
+        });
+        match ceremonies[1].process(&conflicted) {
+            Err(Error::Dkg(_)) => {}
+            _ => panic!("should be error"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_args() {
+        let pubkeys = TEST_KEYS.pubkeys()[1..].to_vec();
+        assert!(DkgCeremony::new(&TEST_KEYS.key[0], &pubkeys, 2, 100, &NONCE).is_err());
+        assert!(DkgCeremony::new(&TEST_KEYS.key[0], &TEST_KEYS.pubkeys(), 6, 100, &NONCE).is_err());
+        assert!(DkgCeremony::new(&TEST_KEYS.key[0], &TEST_KEYS.pubkeys(), 0, 100, &NONCE).is_err());
+    }
+
+    /// The ceremony which finishes when it receives a message, and sends one more message then.
+    struct FinishOnMessage {
+        signer_id: SignerID,
+        messages: Vec<Message>,
+        last: Message,
+        finished: bool,
+    }
+
+    impl Ceremony for FinishOnMessage {
+        fn signer_id(&self) -> SignerID {
+            self.signer_id
+        }
+
+        fn messages(&self) -> &Vec<Message> {
+            &self.messages
+        }
+
+        fn is_finished(&self) -> bool {
+            self.finished
+        }
+
+        fn process(&mut self, _message: &Message) -> Result<(), Error> {
+            self.messages.push(self.last.clone());
+            self.finished = true;
+            Ok(())
+        }
+    }
+
+    /// Delivers the incoming messages to the processor and records the broadcasted messages.
+    struct LoopbackConnectionManager {
+        incoming: Vec<Message>,
+        broadcasted: RefCell<Vec<Message>>,
+    }
+
+    impl ConnectionManager for LoopbackConnectionManager {
+        type ERROR = Error;
+
+        fn broadcast_message(&self, message: Message) {
+            self.broadcasted.borrow_mut().push(message);
+        }
+
+        fn send_message(&self, _message: Message) {}
+
+        fn start(
+            &self,
+            mut message_processor: impl FnMut(Message) -> ControlFlow<()> + Send + 'static,
+            _id: SignerID,
+        ) -> JoinHandle<()> {
+            let incoming = self.incoming.clone();
+            thread::spawn(move || {
+                for message in incoming {
+                    message_processor(message);
+                }
+            })
+        }
+
+        fn test_connection(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn take_error(
+            &mut self,
+        ) -> Result<ConnectionManagerError<Self::ERROR>, std::sync::mpsc::TryRecvError> {
+            let (_s, r) = channel();
+            r.try_recv()
+        }
+    }
+
+    #[test]
+    fn test_run_ceremony_sends_last_messages() {
+        let ceremonies = ceremonies(3);
+        let first = ceremonies[0].messages()[0].clone();
+        let last = ceremonies[1].messages()[0].clone();
+        let mut ceremony = FinishOnMessage {
+            signer_id: ceremonies[0].signer_id(),
+            messages: vec![first.clone()],
+            last: last.clone(),
+            finished: false,
+        };
+        let connection_manager = LoopbackConnectionManager {
+            incoming: vec![ceremonies[2].messages()[0].clone()],
+            broadcasted: RefCell::new(vec![]),
+        };
+
+        run_ceremony(&mut ceremony, &connection_manager, Duration::from_secs(10)).unwrap();
+        assert!(ceremony.is_finished());
+        assert_eq!(
+            connection_manager.broadcasted.into_inner(),
+            vec![first, last]
+        );
+    }
+}
@@ -34,6 +34,32 @@ pub enum Error {
     InvalidAggregatedPublicKey,
     /// xField is not supported by signer.
     UnsupportedXField,
+    /// Error when the parameter change schedule is invalid. This error has the block height of the change and message string.
+    InvalidParameterChange(Option<u64>, &'static str),
+    /// Error when the max block size included the candidate block doesn't match with the schedule.
+    InvalidMaxBlockSize,
+    /// Error response from the control endpoint. This error has the status code and message.
+    ControlRequest(u16, String),
+    /// Error from the signing service which computes local signatures.
+    SigningService(String),
+    /// Error in distributed key generation ceremony.
+    Dkg(String),
+    /// Verification by tapyrus-setup failed.
+    VerificationFailed(String),
+    /// A peer has a different federation from the node's one.
+    FederationMismatch(String),
+    /// The candidate block violates the local block policy. This error has the reason.
+    PolicyRejection(String),
+    /// Tapyrus Core didn't respond to the RPC call in time. This error has the method name.
+    RpcTimeout(String),
+    /// The RPC call failed before Tapyrus Core answered, like a connection failure. This error has the reason.
+    RpcTransport(String),
+    /// Tapyrus Core rejected the credentials. This error has the url of the endpoint.
+    RpcUnauthorized(String),
+    /// Error in the ZeroMQ connection to Tapyrus Core.
+    Zmq(String),
+    /// The message is from another signer network. This error has the network id of the message.
+    NetworkMismatch(String),
 }
 
 impl std::fmt::Display for Error {
@@ -4,9 +4,13 @@ use crate::errors::Error;
 use crate::net::SignerID;
 use crate::sign::Sign;
 use crate::signer_node::{SharedSecret, SharedSecretMap};
+use crate::util::sum_point;
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
     ShamirSecretSharing, VerifiableSS,
 };
+use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use curv::{BigInt, FE, GE};
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 use tapyrus::PublicKey;
 
@@ -33,12 +37,43 @@ impl Federations {
             .expect("Federations should not be empty.")
     }
 
+    /// Returns the federation at the block height like `get_by_block_height`, but skips the
+    /// federations which start at the `inactive` block heights.
+    pub fn get_active_by_block_height(
+        &self,
+        block_height: u64,
+        inactive: &HashSet<u64>,
+    ) -> &Federation {
+        self.federations
+            .iter()
+            .filter(|f| f.block_height <= block_height && !inactive.contains(&f.block_height))
+            .last()
+            .expect("Federations should not be empty.")
+    }
+
+    /// Returns federations which would get started after the block height.
+    pub fn upcoming(&self, block_height: u64) -> Vec<&Federation> {
+        self.federations
+            .iter()
+            .filter(|f| f.block_height > block_height)
+            .collect()
+    }
+
     pub fn last(&self) -> &Federation {
         self.federations
             .last()
             .expect("Federations should not be empty.")
     }
 
+    /// Returns the fingerprints of all federations, which are shared with the other nodes.
+    pub fn fingerprints(&self) -> Vec<FederationFingerprint> {
+        self.federations.iter().map(|f| f.fingerprint()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Federation> {
+        self.federations.iter()
+    }
+
     pub fn len(&self) -> usize {
         self.federations.len()
     }
@@ -80,6 +115,33 @@ impl Federations {
             .map(|i| Federation::from(*pubkey, i))
             .collect();
 
+        let r = Federations::new(vec);
+        r.validate()?;
+        if let Some(federation) = r.iter().find(|f| f.is_member() && !f.has_secret_shares()) {
+            return Err(Error::InvalidFederation(
+                Some(federation.block_height),
+                "The nodevss doesn't include the secret shares.",
+            ));
+        }
+
+        Ok(r)
+    }
+
+    /// Create Federations instance without the node secret shares. The secret shares in `toml`
+    /// are dropped, and they can be zero. It is used when the shares are held by the signing
+    /// service.
+    pub fn from_pubkey_and_toml_without_secret_shares(
+        pubkey: &PublicKey,
+        toml: &str,
+    ) -> Result<Self, Error> {
+        let ser: SerFederations = toml::from_str(toml)?;
+
+        let vec: Vec<Federation> = ser
+            .federation
+            .into_iter()
+            .map(|i| Federation::from(*pubkey, i).without_secret_shares())
+            .collect();
+
         let r = Federations::new(vec);
         r.validate()?;
 
@@ -104,6 +166,10 @@ pub struct Federation {
     nodevss: Option<Vec<Vss>>,
     /// The aggregated public key
     aggregated_public_key: PublicKey,
+    /// Whether the secret shares of block VSSs are masked for each receiver. All signers of the
+    /// federation must agree on it, because the receivers unmask the shares. It is required to use
+    /// the signing service.
+    masked_block_vss: bool,
 }
 
 impl Federation {
@@ -120,9 +186,16 @@ impl Federation {
             threshold,
             nodevss,
             aggregated_public_key,
+            masked_block_vss: false,
         }
     }
 
+    /// Returns the federation which masks the secret shares of block VSSs if `masked` is true.
+    pub fn with_masked_block_vss(mut self, masked: bool) -> Self {
+        self.masked_block_vss = masked;
+        self
+    }
+
     pub fn node_index(&self) -> usize {
         self.signers()
             .iter()
@@ -157,6 +230,9 @@ impl Federation {
     pub fn aggregated_public_key(&self) -> PublicKey {
         self.aggregated_public_key
     }
+    pub fn masked_block_vss(&self) -> bool {
+        self.masked_block_vss
+    }
 
     /// Returns Map collection of received shares from all each signers in Key Generation Protocol
     pub fn node_shared_secrets(&self) -> SharedSecretMap {
@@ -187,6 +263,54 @@ impl Federation {
         secret_shares
     }
 
+    /// Returns the point of the node secret share of the signer at the index. The index is
+    /// 1-based. It is computed from the commitments of nodevss, so that any member can compute it.
+    pub fn public_share(&self, index: usize) -> GE {
+        let x: FE = ECScalar::from(&BigInt::from(index as u64));
+        let points: Vec<GE> = self
+            .nodevss()
+            .iter()
+            .map(|vss| {
+                let mut iter = vss.positive_commitments.iter().rev().map(|c| c.to_point());
+                let head = iter.next().expect("commitments should not be empty");
+                iter.fold(head, |acc, c| acc * &x + &c)
+            })
+            .collect();
+        sum_point(&points)
+    }
+
+    /// Returns whether nodevss includes the secret shares of the node.
+    pub fn has_secret_shares(&self) -> bool {
+        let zero: FE = ECScalar::zero();
+        self.nodevss.as_ref().map_or(false, |nodevss| {
+            nodevss
+                .iter()
+                .any(|vss| vss.positive_secret != zero || vss.negative_secret != zero)
+        })
+    }
+
+    /// Returns the federation whose nodevss doesn't include the secret shares.
+    pub fn without_secret_shares(mut self) -> Self {
+        if let Some(nodevss) = self.nodevss.as_mut() {
+            for vss in nodevss.iter_mut() {
+                vss.positive_secret = ECScalar::zero();
+                vss.negative_secret = ECScalar::zero();
+            }
+        }
+        self
+    }
+
+    /// Returns the aggregated public key as a point. It is computed from the public commitments of
+    /// nodevss, so that the node secret share is not needed.
+    pub fn aggregated_point(&self) -> GE {
+        let points: Vec<GE> = self
+            .nodevss()
+            .iter()
+            .map(|vss| vss.positive_commitments[0].to_point())
+            .collect();
+        sum_point(&points)
+    }
+
     /// Returns an aggregated share of the node.
     pub fn node_secret_share(&self) -> SharedKeys {
         let secret_shares = self.node_shared_secrets();
@@ -230,22 +354,42 @@ impl Federation {
             return Err(Error::InvalidFederation(Some(self.block_height), "The nodevss has wrong receiver value. All VSS's receiver_public_key should be equal with publish key of the signer who runs the node."));
         }
 
+        // Check the threshold is between 1 and the number of signers.
+        let threshold = self.threshold.unwrap();
+        let signer_count = self.nodevss().len();
+        if threshold == 0 || threshold as usize > signer_count {
+            return Err(Error::InvalidFederation(
+                Some(self.block_height),
+                "The threshold should be between 1 and the number of signers.",
+            ));
+        }
+        if (threshold as usize) * 2 <= signer_count {
+            log::warn!(
+                "The threshold of the federation at {} height is not more than half of the signers. threshold: {}, signers: {}",
+                self.block_height,
+                threshold,
+                signer_count
+            );
+        }
+
         // Check all commitment length is correct.
-        if let Some(threshold) = self.threshold {
-            if self
-                .nodevss()
-                .iter()
-                .any(|vss| vss.positive_commitments.len() != threshold as usize)
-            {
-                return Err(Error::InvalidFederation(
-                    Some(self.block_height),
-                    "The nodevss has wrong vss which has wrong number of commitments.",
-                ));
-            }
+        if self
+            .nodevss()
+            .iter()
+            .any(|vss| vss.positive_commitments.len() != threshold as usize)
+        {
+            return Err(Error::InvalidFederation(
+                Some(self.block_height),
+                "The nodevss has wrong vss which has wrong number of commitments.",
+            ));
         }
 
-        // verify each vss.
-        if Sign::verify_vss_and_construct_key(&self.node_shared_secrets(), &(self.node_index() + 1))
+        // verify each vss, if the node has the secret shares.
+        if self.has_secret_shares()
+            && Sign::verify_vss_and_construct_key(
+                &self.node_shared_secrets(),
+                &(self.node_index() + 1),
+            )
             .is_err()
         {
             return Err(Error::InvalidFederation(
@@ -254,6 +398,21 @@ impl Federation {
             ));
         }
 
+        // Check the aggregated public key is the sum of the first commitments of all senders.
+        let aggregated_public_key =
+            PublicKey::from_slice(&self.aggregated_point().pk_to_key_slice()).map_err(|_| {
+                Error::InvalidFederation(
+                    Some(self.block_height),
+                    "The nodevss includes invalid commitment.",
+                )
+            })?;
+        if aggregated_public_key != self.aggregated_public_key {
+            return Err(Error::InvalidFederation(
+                Some(self.block_height),
+                "The aggregated public key doesn't match with the commitments of the nodevss.",
+            ));
+        }
+
         Ok(())
     }
 
@@ -271,6 +430,41 @@ impl Federation {
             ser.nodevss,
             ser.aggregated_public_key,
         )
+        .with_masked_block_vss(ser.masked_block_vss.unwrap_or(false))
+    }
+
+    /// Returns the federation as an entry of federations toml.
+    pub fn to_toml(&self) -> String {
+        let ser = SerFederations {
+            federation: vec![self.clone().to_ser()],
+        };
+        toml::to_string(&ser).expect("failed to serialize federation")
+    }
+
+    /// Returns the public part of the federation. The threshold, the signers and whether block VSSs
+    /// are masked are hashed and included only if the signer of the node is a member of the
+    /// federation.
+    pub fn fingerprint(&self) -> FederationFingerprint {
+        let members = if self.is_member() {
+            let mut hasher = Sha256::new();
+            hasher.input(&[self.threshold.unwrap()]);
+            for signer in self.signers() {
+                hasher.input(&signer.pubkey.key.serialize()[..]);
+            }
+            // Hashed only if it is set, so that the fingerprints of the existing federations don't
+            // change.
+            if self.masked_block_vss {
+                hasher.input(b"masked-block-vss");
+            }
+            Some(hex::encode(&hasher.result()[..]))
+        } else {
+            None
+        };
+        FederationFingerprint {
+            block_height: self.block_height,
+            aggregated_public_key: self.aggregated_public_key,
+            members,
+        }
     }
 
     pub fn to_ser(self) -> SerFederation {
@@ -279,6 +473,42 @@ impl Federation {
             threshold: self.threshold,
             nodevss: self.nodevss,
             aggregated_public_key: self.aggregated_public_key,
+            masked_block_vss: if self.masked_block_vss {
+                Some(true)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// The public part of a federation. Nodes share it in heartbeat messages to check that all of them
+/// have the same federations.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FederationFingerprint {
+    pub block_height: u64,
+    pub aggregated_public_key: PublicKey,
+    /// SHA256 of the threshold, the signers and masked-block-vss. It is None if the sender is not a
+    /// member.
+    pub members: Option<String>,
+}
+
+impl FederationFingerprint {
+    /// Returns the difference from the other fingerprint of the federation at the same height. The
+    /// members are compared only if both nodes are members.
+    pub fn diff(&self, other: &FederationFingerprint) -> Option<String> {
+        if self.aggregated_public_key != other.aggregated_public_key {
+            return Some(format!(
+                "aggregated public key {} != {}",
+                self.aggregated_public_key, other.aggregated_public_key
+            ));
+        }
+        match (&self.members, &other.members) {
+            (Some(a), Some(b)) if a != b => Some(format!(
+                "threshold, signers or masked-block-vss are different. fingerprint {} != {}",
+                a, b
+            )),
+            _ => None,
         }
     }
 }
@@ -297,6 +527,8 @@ pub struct SerFederation {
     nodevss: Option<Vec<Vss>>,
     #[serde(rename = "aggregated-public-key")]
     aggregated_public_key: PublicKey,
+    #[serde(rename = "masked-block-vss", skip_serializing_if = "Option::is_none")]
+    masked_block_vss: Option<bool>,
 }
 
 #[cfg(test)]
@@ -304,11 +536,12 @@ mod tests {
     use crate::errors::Error;
     use crate::federation::{Federation, Federations};
     use crate::net::SignerID;
+    use crate::signer_node::ToVerifiableSS;
     use crate::tests::helper::keys::TEST_KEYS;
     use crate::tests::helper::node_vss::node_vss;
     use curv::arithmetic::traits::Converter;
-    use curv::elliptic::curves::traits::ECScalar;
-    use curv::BigInt;
+    use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+    use curv::{BigInt, GE};
     use std::str::FromStr;
     use tapyrus::PublicKey;
 
@@ -464,6 +697,75 @@ mod tests {
         let mut federation = valid_federation();
         federation.threshold = None;
         assert!(federation.validate().is_err());
+
+        // the threshold is out of range
+        for threshold in &[0, 6] {
+            let mut federation = valid_federation();
+            federation.threshold = Some(*threshold);
+            match federation.validate() {
+                Err(Error::InvalidFederation(_, m)) => assert_eq!(
+                    m,
+                    "The threshold should be between 1 and the number of signers."
+                ),
+                _ => assert!(false, "it should error"),
+            }
+        }
+
+        // the aggregated public key doesn't match with the commitments
+        let mut federation = valid_federation();
+        federation.aggregated_public_key = TEST_KEYS.pubkeys()[0];
+        match federation.validate() {
+            Err(Error::InvalidFederation(Some(0), m)) => assert_eq!(
+                m,
+                "The aggregated public key doesn't match with the commitments of the nodevss."
+            ),
+            _ => assert!(false, "it should error"),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let federation = valid_federation();
+        let fingerprint = federation.fingerprint();
+        assert_eq!(fingerprint.block_height, 0);
+        assert_eq!(fingerprint.aggregated_public_key, TEST_KEYS.aggregated());
+        assert!(fingerprint.members.is_some());
+
+        // Another member has the same fingerprint.
+        let other = Federation::new(
+            TEST_KEYS.pubkeys()[1],
+            0,
+            Some(3),
+            Some(node_vss(1)),
+            TEST_KEYS.aggregated(),
+        );
+        assert_eq!(other.fingerprint(), fingerprint);
+        assert!(fingerprint.diff(&other.fingerprint()).is_none());
+
+        // A non-member doesn't know the threshold and the signers.
+        let non_member = Federation::new(
+            TEST_KEYS.pubkeys()[1],
+            0,
+            None,
+            None,
+            TEST_KEYS.aggregated(),
+        );
+        assert!(non_member.fingerprint().members.is_none());
+        assert!(fingerprint.diff(&non_member.fingerprint()).is_none());
+
+        let mut federation = valid_federation();
+        federation.threshold = Some(4);
+        assert!(fingerprint.diff(&federation.fingerprint()).is_some());
+
+        let mut federation = valid_federation();
+        federation.aggregated_public_key = TEST_KEYS.pubkeys()[0];
+        assert!(fingerprint.diff(&federation.fingerprint()).is_some());
+
+        let federation = valid_federation().with_masked_block_vss(true);
+        assert!(fingerprint.diff(&federation.fingerprint()).is_some());
+
+        let federations = Federations::new(vec![valid_federation()]);
+        assert_eq!(federations.fingerprints(), vec![fingerprint]);
     }
 
     #[test]
@@ -476,6 +778,15 @@ mod tests {
         let deserialized =
             Federation::from(federation.signer_id.pubkey, toml::from_str(&str).unwrap());
         assert_eq!(federation, deserialized);
+        assert!(!str.contains("masked-block-vss"));
+
+        let federation = valid_federation().with_masked_block_vss(true);
+        let str = toml::to_string(&federation.clone().to_ser()).unwrap();
+        assert!(str.contains("masked-block-vss = true"));
+        let deserialized =
+            Federation::from(federation.signer_id.pubkey, toml::from_str(&str).unwrap());
+        assert!(deserialized.masked_block_vss());
+        assert_eq!(federation, deserialized);
     }
 
     #[test]
@@ -501,7 +812,7 @@ mod tests {
         [[federation]]
         block-height = 100
         threshold = 2
-        aggregated-public-key = "030acd6af981c498ebf2ffd9a341d2a96bde5832c150e7d300fa3583eee0f964fe"
+        aggregated-public-key = "02427ddae5c4f1946fd2f51b425726a9f5cf0a64ba8efbe68691594cbd097dfcc6"
         node-vss = [
           "02472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b7250602472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b725060002472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b72506bb360eeb9d77cc606471ca455eb68331fbbdab6d009da456bef3920a61222f58df14e215a3883ff8c8def6bdce4d9d80282749b8056ec72373a246b3de5aa120b336d88a9b977a2f2ff5f26a1633f70f2d776363c495a02617bb7a88a2fea285a0a0e33e16dd90acb06b22fc70086f7eb12cdfeb7eb622d8a455de1f448fd30a472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b7250644c9f1146288339f9b8e35baa1497cce04425492ff625ba9410c6df49eddccd761c1af39b4fcbe4e3a240848bc90f41681ec105286684d4832efdc5b96a0e027286d9f7d22ad52194da7d522e5586ce268c7fde21220aca78e21d1d1a5d69f2468fff1b0f8a3a142cf0e1c7d29cbe3e509c437cd680ab21715cb5c1844d2eff8",
           "02ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b90002472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b725060002ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b90015ea9fee96aed6d9c0fc2fbe0bd1883dee223b3200246ff1e21976bdbc9a0fc81f4be7002fae5b70835ee7e51893f76dd840ea64010504b8c66197d9cad767236a786cc7b91146df5e8d1ff73ad220b3578359eda61c341a8b5c41de6b068f73696b619f9213a550d0bc159ba1ff43c4e90c85930904a6f7f582b52074e3d965ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b900ea156011695129263f03d041f42e77c211ddc4cdffdb900e1de689414365ec672ad0d285c094287a3e2cc42d88e0736a1e238f33cde1ef9bd1cd9a0e8fe4a37dec94b7927535da68f388e4d5f07e838b71c742302476336b68f47b2e802b8c88a0fd87b9acc67ab564501a9777cf6081f50798b2ae8f2dff8a5190a27cfaf0e3",
@@ -521,7 +832,7 @@ mod tests {
         [[federation]]
         block-height = 100
         threshold = 2
-        aggregated-public-key = "030acd6af981c498ebf2ffd9a341d2a96bde5832c150e7d300fa3583eee0f964fe"
+        aggregated-public-key = "02427ddae5c4f1946fd2f51b425726a9f5cf0a64ba8efbe68691594cbd097dfcc6"
         node-vss = [
           "02472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b7250602472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b725060002472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b72506bb360eeb9d77cc606471ca455eb68331fbbdab6d009da456bef3920a61222f58df14e215a3883ff8c8def6bdce4d9d80282749b8056ec72373a246b3de5aa120b336d88a9b977a2f2ff5f26a1633f70f2d776363c495a02617bb7a88a2fea285a0a0e33e16dd90acb06b22fc70086f7eb12cdfeb7eb622d8a455de1f448fd30a472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b7250644c9f1146288339f9b8e35baa1497cce04425492ff625ba9410c6df49eddccd761c1af39b4fcbe4e3a240848bc90f41681ec105286684d4832efdc5b96a0e027286d9f7d22ad52194da7d522e5586ce268c7fde21220aca78e21d1d1a5d69f2468fff1b0f8a3a142cf0e1c7d29cbe3e509c437cd680ab21715cb5c1844d2eff8",
           "02ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b90002472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b725060002ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b90015ea9fee96aed6d9c0fc2fbe0bd1883dee223b3200246ff1e21976bdbc9a0fc81f4be7002fae5b70835ee7e51893f76dd840ea64010504b8c66197d9cad767236a786cc7b91146df5e8d1ff73ad220b3578359eda61c341a8b5c41de6b068f73696b619f9213a550d0bc159ba1ff43c4e90c85930904a6f7f582b52074e3d965ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b900ea156011695129263f03d041f42e77c211ddc4cdffdb900e1de689414365ec672ad0d285c094287a3e2cc42d88e0736a1e238f33cde1ef9bd1cd9a0e8fe4a37dec94b7927535da68f388e4d5f07e838b71c742302476336b68f47b2e802b8c88a0fd87b9acc67ab564501a9777cf6081f50798b2ae8f2dff8a5190a27cfaf0e3",
@@ -576,7 +887,7 @@ mod tests {
         [[federation]]
         block-height = 100
         threshold = 2
-        aggregated-public-key = "030acd6af981c498ebf2ffd9a341d2a96bde5832c150e7d300fa3583eee0f964fe"
+        aggregated-public-key = "02427ddae5c4f1946fd2f51b425726a9f5cf0a64ba8efbe68691594cbd097dfcc6"
         node-vss = [
           "02472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b7250602472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b725060002472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b72506bb360eeb9d77cc606471ca455eb68331fbbdab6d009da456bef3920a61222f58df14e215a3883ff8c8def6bdce4d9d80282749b8056ec72373a246b3de5aa120b336d88a9b977a2f2ff5f26a1633f70f2d776363c495a02617bb7a88a2fea285a0a0e33e16dd90acb06b22fc70086f7eb12cdfeb7eb622d8a455de1f448fd30a472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b7250644c9f1146288339f9b8e35baa1497cce04425492ff625ba9410c6df49eddccd761c1af39b4fcbe4e3a240848bc90f41681ec105286684d4832efdc5b96a0e027286d9f7d22ad52194da7d522e5586ce268c7fde21220aca78e21d1d1a5d69f2468fff1b0f8a3a142cf0e1c7d29cbe3e509c437cd680ab21715cb5c1844d2eff8",
           "02ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b90002472012cf49fca573ca1f63deafe59df842f0bbe77e9ac7e67b211bb074b725060002ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b90015ea9fee96aed6d9c0fc2fbe0bd1883dee223b3200246ff1e21976bdbc9a0fc81f4be7002fae5b70835ee7e51893f76dd840ea64010504b8c66197d9cad767236a786cc7b91146df5e8d1ff73ad220b3578359eda61c341a8b5c41de6b068f73696b619f9213a550d0bc159ba1ff43c4e90c85930904a6f7f582b52074e3d965ce7edc292d7b747fab2f23584bbafaffde5c8ff17cf689969614441e0527b900ea156011695129263f03d041f42e77c211ddc4cdffdb900e1de689414365ec672ad0d285c094287a3e2cc42d88e0736a1e238f33cde1ef9bd1cd9a0e8fe4a37dec94b7927535da68f388e4d5f07e838b71c742302476336b68f47b2e802b8c88a0fd87b9acc67ab564501a9777cf6081f50798b2ae8f2dff8a5190a27cfaf0e3",
@@ -589,4 +900,50 @@ mod tests {
             _ => assert!(false, "it should error"),
         }
     }
+    #[test]
+    fn test_public_share() {
+        for index in 0..5 {
+            let federation = Federation::new(
+                TEST_KEYS.pubkeys()[index],
+                0,
+                Some(3),
+                Some(node_vss(index)),
+                TEST_KEYS.aggregated(),
+            );
+            let expected = GE::generator() * &federation.node_secret_share().x_i;
+
+            // Every member computes the same public share of the signer.
+            for other in 0..5 {
+                let federation = Federation::new(
+                    TEST_KEYS.pubkeys()[other],
+                    0,
+                    Some(3),
+                    Some(node_vss(other)),
+                    TEST_KEYS.aggregated(),
+                );
+                assert_eq!(federation.public_share(index + 1), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_without_secret_shares() {
+        let federation = Federation::new(
+            TEST_KEYS.pubkeys()[0],
+            0,
+            Some(3),
+            Some(node_vss(0)),
+            TEST_KEYS.aggregated(),
+        );
+        assert!(federation.has_secret_shares());
+
+        let without = federation.clone().without_secret_shares();
+        assert!(!without.has_secret_shares());
+        assert!(without.validate().is_ok());
+        assert_eq!(
+            without.node_shared_secrets().to_vss(),
+            federation.node_shared_secrets().to_vss()
+        );
+        assert_eq!(without.public_share(2), federation.public_share(2));
+    }
 }
@@ -18,17 +18,27 @@ extern crate lazy_static;
 extern crate derive_builder;
 extern crate sha2;
 
+pub mod block_notify;
+pub mod block_policy;
 pub mod cli;
 pub mod command_args;
+pub mod control;
 pub mod crypto;
+pub mod devnet;
+pub mod dkg;
 pub mod errors;
 pub mod federation;
 pub mod key;
+pub mod mock_core;
 pub mod net;
+pub mod parameter_schedule;
+pub mod redis_client;
 pub mod rpc;
+pub mod rpc_client;
 pub mod serialize;
 pub mod sign;
 pub mod signer_node;
+pub mod signing_service;
 pub mod timer;
 pub mod util;
 
@@ -0,0 +1,661 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Fake Tapyrus Core for local end-to-end tests.
+//!
+//! `MockChain` keeps a chain in memory and accepts blocks whose proof is valid for the current
+//! aggregated public key. The xfield of an accepted block takes effect from the next block as
+//! Tapyrus Core does: a new aggregated public key is used to verify the following proofs and a
+//! new max block size limits the following blocks. It implements `TapyrusApi`, so that it can be
+//! used in the same process, and `MockCoreServer` serves it over JSON-RPC for `tapyrus-signerd`.
+//!
+//! The mempool is always empty and the blocks have only the coinbase transaction.
+
+use crate::errors::Error;
+use crate::rpc::{GetBlockchainInfoResult, GetMempoolInfoResult, TapyrusApi};
+use crate::sign::Sign;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tapyrus::blockdata::block::{Block, BlockHeader, XField};
+use tapyrus::blockdata::script::Builder;
+use tapyrus::blockdata::transaction::{OutPoint, Transaction, TxIn, TxOut};
+use tapyrus::consensus::encode::{deserialize, serialize};
+use tapyrus::hash_types::{BlockHash, TxMerkleNode};
+use tapyrus::hashes::hex::FromHex;
+use tapyrus::{Address, PublicKey};
+
+/// The max block size until a block changes it, which is the default of Tapyrus Core.
+pub const DEFAULT_MAX_BLOCK_SIZE: u32 = 1_000_000;
+/// The address which `tapyrus-signerd` connects to by default.
+pub const DEFAULT_RPC_ADDRESS: &str = "127.0.0.1:12381";
+/// Max difference(sec) of the block time ahead of the local clock.
+const MAX_FUTURE_BLOCK_TIME: u64 = 2 * 60 * 60;
+/// Number of blocks for the median time past.
+const MEDIAN_TIME_SPAN: usize = 11;
+/// Max size of request body. It is enough for a block of the default max block size in hex.
+const MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+// Error codes of Tapyrus Core RPC.
+const RPC_INVALID_ADDRESS_OR_KEY: i32 = -5;
+const RPC_INVALID_PARAMETER: i32 = -8;
+const RPC_DESERIALIZATION_ERROR: i32 = -22;
+const RPC_VERIFY_ERROR: i32 = -25;
+const RPC_METHOD_NOT_FOUND: i32 = -32601;
+
+struct ChainState {
+    blocks: Vec<Block>,
+    aggregated_public_key: PublicKey,
+    max_block_size: u32,
+}
+
+/// In-memory chain. Clones share the same chain.
+#[derive(Clone)]
+pub struct MockChain {
+    state: Arc<Mutex<ChainState>>,
+}
+
+impl MockChain {
+    /// Starts the chain with the genesis block, which must have the aggregated public key in the
+    /// xfield. The proof of the genesis block is verified only if it has one, so that a chain can
+    /// start with an unsigned genesis block from `tapyrus-setup creategenesis`.
+    pub fn new(genesis: Block) -> Result<Self, Error> {
+        let aggregated_public_key = match genesis.header.xfield {
+            XField::AggregatePublicKey(key) => key,
+            _ => return Err(Error::InvalidAggregatedPublicKey),
+        };
+        if genesis.header.proof.is_some() {
+            Sign::verify_proof(&genesis.header, &aggregated_public_key)?;
+        }
+        Ok(MockChain {
+            state: Arc::new(Mutex::new(ChainState {
+                blocks: vec![genesis],
+                aggregated_public_key,
+                max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+            })),
+        })
+    }
+
+    /// Returns the height of the tip.
+    pub fn height(&self) -> u64 {
+        self.state.lock().unwrap().blocks.len() as u64 - 1
+    }
+
+    pub fn tip(&self) -> Block {
+        self.state.lock().unwrap().blocks.last().unwrap().clone()
+    }
+
+    pub fn block(&self, height: u64) -> Option<Block> {
+        self.state
+            .lock()
+            .unwrap()
+            .blocks
+            .get(height as usize)
+            .cloned()
+    }
+
+    /// Returns the aggregated public key which verifies the next block.
+    pub fn aggregated_public_key(&self) -> PublicKey {
+        self.state.lock().unwrap().aggregated_public_key
+    }
+
+    /// Returns the max block size of the next block.
+    pub fn max_block_size(&self) -> u32 {
+        self.state.lock().unwrap().max_block_size
+    }
+
+    fn find(&self, hash: &BlockHash) -> Option<(u64, Block)> {
+        let state = self.state.lock().unwrap();
+        state
+            .blocks
+            .iter()
+            .position(|b| b.block_hash() == *hash)
+            .map(|height| (height as u64, state.blocks[height].clone()))
+    }
+
+    /// Handles a JSON-RPC request and returns the response.
+    pub fn handle_request(&self, request: &serde_json::Value) -> serde_json::Value {
+        let method = request["method"].as_str().unwrap_or("");
+        let params = request["params"].as_array().cloned().unwrap_or_default();
+        let result = match self.call(method, &params) {
+            Ok(result) => result,
+            // Tapyrus Core returns the reason of the rejected block as the result of submitblock.
+            Err(Error::InvalidRequest(e))
+                if method == "submitblock" && e.code == RPC_VERIFY_ERROR =>
+            {
+                serde_json::Value::String(e.message)
+            }
+            Err(e) => {
+                let (code, message) = match e {
+                    Error::InvalidRequest(e) => (e.code, e.message),
+                    e => (RPC_VERIFY_ERROR, e.to_string()),
+                };
+                return serde_json::json!({
+                    "result": null,
+                    "error": { "code": code, "message": message },
+                    "id": request["id"],
+                });
+            }
+        };
+        serde_json::json!({ "result": result, "error": null, "id": request["id"] })
+    }
+
+    fn call(&self, method: &str, params: &[serde_json::Value]) -> Result<serde_json::Value, Error> {
+        match method {
+            "getnewblock" => {
+                let address = params
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| Address::from_str(s).ok())
+                    .ok_or_else(|| rpc_error(RPC_INVALID_ADDRESS_OR_KEY, "Invalid address"))?;
+                Ok(hex::encode(serialize(&self.getnewblock(&address)?)).into())
+            }
+            "testproposedblock" => Ok(self.testproposedblock(&block_param(params)?)?.into()),
+            "submitblock" => {
+                self.submitblock(&block_param(params)?)?;
+                Ok(serde_json::Value::Null)
+            }
+            "getblockchaininfo" => {
+                let info = self.getblockchaininfo()?;
+                Ok(serde_json::json!({
+                    "chain": info.chain,
+                    "blocks": info.blocks,
+                    "headers": info.headers,
+                    "bestblockhash": info.bestblockhash,
+                    "mediantime": info.mediantime,
+                    "initialblockdownload": info.initialblockdownload,
+                }))
+            }
+            "getblockhash" => {
+                let height = params
+                    .get(0)
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| rpc_error(RPC_INVALID_PARAMETER, "Invalid block height"))?;
+                let block = self
+                    .block(height)
+                    .ok_or_else(|| rpc_error(RPC_INVALID_PARAMETER, "Block height out of range"))?;
+                Ok(block.block_hash().to_string().into())
+            }
+            "getblockheader" => {
+                let (height, block) = params
+                    .get(0)
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| BlockHash::from_hex(s).ok())
+                    .and_then(|hash| self.find(&hash))
+                    .ok_or_else(|| rpc_error(RPC_INVALID_ADDRESS_OR_KEY, "Block not found"))?;
+                if params.get(1).and_then(|v| v.as_bool()) == Some(false) {
+                    return Ok(hex::encode(serialize(&block.header)).into());
+                }
+                let mut header = serde_json::json!({
+                    "hash": block.block_hash().to_string(),
+                    "confirmations": self.height() - height + 1,
+                    "height": height,
+                    "version": block.header.version,
+                    "merkleroot": block.header.merkle_root.to_string(),
+                    "immutablemerkleroot": block.header.im_merkle_root.to_string(),
+                    "time": block.header.time,
+                    "nTx": block.txdata.len(),
+                });
+                if height > 0 {
+                    header["previousblockhash"] = block.header.prev_blockhash.to_string().into();
+                }
+                Ok(header)
+            }
+            "getmempoolinfo" => {
+                let info = self.getmempoolinfo()?;
+                Ok(serde_json::json!({ "size": info.size, "bytes": info.bytes }))
+            }
+            _ => Err(rpc_error(RPC_METHOD_NOT_FOUND, "Method not found")),
+        }
+    }
+}
+
+impl TapyrusApi for MockChain {
+    /// Creates the next block which has only the coinbase paying to the address.
+    fn getnewblock(&self, address: &Address) -> Result<Block, Error> {
+        let state = self.state.lock().unwrap();
+        let height = state.blocks.len() as u32;
+        let tip = state.blocks.last().unwrap();
+        let coinbase = Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Default::default(), height),
+                script_sig: Builder::new().push_int(height as i64).into_script(),
+                sequence: 0xffffffff,
+                witness: vec![],
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: address.script_pubkey(),
+            }],
+        };
+        let mut block = Block {
+            header: BlockHeader {
+                version: 1,
+                prev_blockhash: tip.block_hash(),
+                merkle_root: TxMerkleNode::default(),
+                im_merkle_root: TxMerkleNode::default(),
+                time: std::cmp::max(now() as u32, median_time(&state.blocks) + 1),
+                xfield: XField::None,
+                proof: None,
+            },
+            txdata: vec![coinbase],
+        };
+        block.header.merkle_root = block.merkle_root();
+        block.header.im_merkle_root = block.immutable_merkle_root();
+        Ok(block)
+    }
+
+    fn testproposedblock(&self, block: &Block) -> Result<bool, Error> {
+        check_block(&self.state.lock().unwrap(), block)?;
+        Ok(true)
+    }
+
+    /// Appends the block to the chain if its proof is valid for the current aggregated public key.
+    fn submitblock(&self, block: &Block) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        check_block(&state, block)?;
+        if Sign::verify_proof(&block.header, &state.aggregated_public_key).is_err() {
+            return Err(rpc_error(RPC_VERIFY_ERROR, "bad-proof"));
+        }
+
+        match block.header.xfield {
+            XField::AggregatePublicKey(key) => state.aggregated_public_key = key,
+            XField::MaxBlockSize(size) => state.max_block_size = size,
+            _ => {}
+        }
+        state.blocks.push(block.clone());
+        log::info!(
+            "Accepted block {} at height {}",
+            block.block_hash(),
+            state.blocks.len() - 1
+        );
+        Ok(())
+    }
+
+    fn getblockchaininfo(&self) -> Result<GetBlockchainInfoResult, Error> {
+        let state = self.state.lock().unwrap();
+        let height = state.blocks.len() as u64 - 1;
+        Ok(GetBlockchainInfoResult {
+            chain: "dev".to_string(),
+            blocks: height,
+            headers: height,
+            bestblockhash: state.blocks.last().unwrap().block_hash().to_string(),
+            mediantime: median_time(&state.blocks) as u64,
+            initialblockdownload: false,
+        })
+    }
+
+    fn getblockheader(&self, block_height: u64) -> Result<BlockHeader, Error> {
+        self.block(block_height)
+            .map(|b| b.header)
+            .ok_or_else(|| rpc_error(RPC_INVALID_PARAMETER, "Block height out of range"))
+    }
+
+    fn getmempoolinfo(&self) -> Result<GetMempoolInfoResult, Error> {
+        Ok(GetMempoolInfoResult { size: 0, bytes: 0 })
+    }
+}
+
+/// Checks the block except the proof, like testproposedblock of Tapyrus Core.
+fn check_block(state: &ChainState, block: &Block) -> Result<(), Error> {
+    let tip = state.blocks.last().unwrap();
+    if block.header.prev_blockhash != tip.block_hash() {
+        return Err(rpc_error(RPC_VERIFY_ERROR, "inconclusive-not-best-prevblk"));
+    }
+    match block.txdata.first() {
+        Some(tx) if tx.is_coin_base() => {}
+        _ => return Err(rpc_error(RPC_VERIFY_ERROR, "bad-cb-missing")),
+    }
+    if block.header.merkle_root != block.merkle_root() {
+        return Err(rpc_error(RPC_VERIFY_ERROR, "bad-txnmrklroot"));
+    }
+    if block.header.im_merkle_root != block.immutable_merkle_root() {
+        return Err(rpc_error(RPC_VERIFY_ERROR, "bad-immutablemrklroot"));
+    }
+    if serialize(block).len() > state.max_block_size as usize {
+        return Err(rpc_error(RPC_VERIFY_ERROR, "bad-blk-length"));
+    }
+    if block.header.time <= median_time(&state.blocks) {
+        return Err(rpc_error(RPC_VERIFY_ERROR, "time-too-old"));
+    }
+    if block.header.time as u64 > now() + MAX_FUTURE_BLOCK_TIME {
+        return Err(rpc_error(RPC_VERIFY_ERROR, "time-too-new"));
+    }
+    match block.header.xfield {
+        XField::None => Ok(()),
+        XField::AggregatePublicKey(_) => Ok(()),
+        XField::MaxBlockSize(size) if size > 0 => Ok(()),
+        _ => Err(rpc_error(RPC_VERIFY_ERROR, "bad-xfield")),
+    }
+}
+
+/// Returns the median time of the last blocks.
+fn median_time(blocks: &[Block]) -> u32 {
+    let start = blocks.len().saturating_sub(MEDIAN_TIME_SPAN);
+    let mut times: Vec<u32> = blocks[start..].iter().map(|b| b.header.time).collect();
+    times.sort_unstable();
+    times[times.len() / 2]
+}
+
+fn block_param(params: &[serde_json::Value]) -> Result<Block, Error> {
+    params
+        .get(0)
+        .and_then(|v| v.as_str())
+        .and_then(|s| hex::decode(s).ok())
+        .and_then(|bytes| deserialize::<Block>(&bytes).ok())
+        .ok_or_else(|| rpc_error(RPC_DESERIALIZATION_ERROR, "Block decode failed"))
+}
+
+fn rpc_error(code: i32, message: &str) -> Error {
+    Error::InvalidRequest(jsonrpc::error::RpcError {
+        code,
+        message: message.to_string(),
+        data: None,
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the unix epoch")
+        .as_secs()
+}
+
+/// JSON-RPC server of the mock chain.
+pub struct MockCoreServer {
+    listener: TcpListener,
+    chain: MockChain,
+    /// The user name and the password which clients must send. Any client is accepted if None.
+    credentials: Option<(String, String)>,
+}
+
+impl MockCoreServer {
+    pub fn bind(address: &str, chain: MockChain) -> Result<Self, Error> {
+        let listener = TcpListener::bind(address)?;
+        Ok(MockCoreServer {
+            listener,
+            chain,
+            credentials: None,
+        })
+    }
+
+    pub fn credentials(mut self, user: String, password: String) -> Self {
+        self.credentials = Some((user, password));
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<std::net::SocketAddr, Error> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Start accepting connections. Each connection is handled on its own thread.
+    pub fn start(self) -> JoinHandle<()> {
+        let authorization = self.credentials.as_ref().map(|(user, password)| {
+            format!(
+                "Basic {}",
+                base64::encode(&format!("{}:{}", user, password))
+            )
+        });
+        std::thread::Builder::new()
+            .name("MockCoreServerThread".to_string())
+            .spawn(move || {
+                for stream in self.listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            let chain = self.chain.clone();
+                            let authorization = authorization.clone();
+                            std::thread::spawn(move || {
+                                if let Err(e) =
+                                    handle_connection(stream, &chain, authorization.as_deref())
+                                {
+                                    log::warn!("Mock core failed to handle a request: {:?}", e);
+                                }
+                            });
+                        }
+                        Err(e) => log::warn!("Mock core failed to accept: {:?}", e),
+                    }
+                }
+            })
+            .unwrap()
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    chain: &MockChain,
+    authorization: Option<&str>,
+) -> Result<(), Error> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut content_length = 0;
+    let mut authorized = authorization.is_none();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let mut header = line.splitn(2, ':');
+        let name = header.next().unwrap_or("").trim();
+        let value = header.next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().unwrap_or(0);
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorized = authorized || Some(value) == authorization;
+        }
+    }
+
+    if !authorized {
+        return write_response(stream, 401, "Unauthorized", "");
+    }
+    if content_length > MAX_BODY_SIZE {
+        return write_response(stream, 400, "Bad Request", "");
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+    let request: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(_) => return write_response(stream, 400, "Bad Request", ""),
+    };
+
+    let response = chain.handle_request(&request);
+    // Tapyrus Core answers an error with the status 500 and the error in the body.
+    let (code, reason) = if response["error"].is_null() {
+        (200, "OK")
+    } else {
+        (500, "Internal Server Error")
+    };
+    write_response(stream, code, reason, &response.to_string())
+}
+
+fn write_response(mut stream: TcpStream, code: u16, reason: &str, body: &str) -> Result<(), Error> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::setup::create_genesis::genesis_block;
+    use crate::crypto::multi_party_schnorr::{LocalSig, SharedKeys, Signature};
+    use crate::rpc::Rpc;
+    use crate::tests::helper::address;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use curv::arithmetic::traits::Converter;
+    use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+    use curv::{FE, GE};
+    use tapyrus::secp256k1::Secp256k1;
+    use tapyrus::util::prime::jacobi;
+    use tapyrus::PrivateKey;
+
+    /// Signs the block with the private key alone, like a federation of a signer.
+    fn sign(block: &Block, private_key: &PrivateKey) -> Block {
+        let x: FE = ECScalar::from(&Sign::private_key_to_big_int(private_key.key).unwrap());
+        let y = GE::generator() * &x;
+        let (k, r) = loop {
+            let k: FE = ECScalar::new_random();
+            let r = GE::generator() * &k;
+            if jacobi(&Converter::to_vec(&r.y_coor().unwrap())) == 1 {
+                break (k, r);
+            }
+        };
+        let hash = block.header.signature_hash();
+        let local_sig = LocalSig::compute(
+            &hash[..],
+            &SharedKeys { y: r, x_i: k },
+            &SharedKeys { y, x_i: x },
+        );
+        let signature = Signature {
+            sigma: local_sig.gamma_i,
+            v: r,
+        };
+        let mut block = block.clone();
+        block.header.proof =
+            Some(deserialize(&hex::decode(Sign::format_signature(&signature)).unwrap()).unwrap());
+        block
+    }
+
+    fn public_key(private_key: &PrivateKey) -> PublicKey {
+        private_key.public_key(&Secp256k1::new())
+    }
+
+    fn chain() -> MockChain {
+        let key = &TEST_KEYS.key[0];
+        let genesis = genesis_block(public_key(key), &address(key), now() as u32 - 600);
+        MockChain::new(sign(&genesis, key)).unwrap()
+    }
+
+    fn assert_rejected<T: std::fmt::Debug>(result: Result<T, Error>, reason: &str) {
+        match result {
+            Err(Error::InvalidRequest(e)) => assert_eq!(e.message, reason),
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let key = &TEST_KEYS.key[0];
+        let genesis = genesis_block(public_key(key), &address(key), 1_600_000_000);
+        assert!(MockChain::new(genesis.clone()).is_ok());
+        assert!(MockChain::new(sign(&genesis, key)).is_ok());
+        assert!(MockChain::new(sign(&genesis, &TEST_KEYS.key[1])).is_err());
+
+        let mut genesis = genesis;
+        genesis.header.xfield = XField::None;
+        assert!(MockChain::new(genesis).is_err());
+    }
+
+    #[test]
+    fn test_submitblock() {
+        let chain = chain();
+        let key = &TEST_KEYS.key[0];
+        let block = chain.getnewblock(&address(key)).unwrap();
+        assert!(chain.testproposedblock(&block).unwrap());
+
+        assert_rejected(chain.submitblock(&block), "bad-proof");
+        assert_rejected(
+            chain.submitblock(&sign(&block, &TEST_KEYS.key[1])),
+            "bad-proof",
+        );
+
+        let signed = sign(&block, key);
+        chain.submitblock(&signed).unwrap();
+        assert_eq!(chain.height(), 1);
+        assert_eq!(chain.tip(), signed);
+        assert_eq!(chain.getblockheader(1).unwrap(), signed.header);
+
+        // The block is not on the tip anymore.
+        assert_rejected(chain.submitblock(&signed), "inconclusive-not-best-prevblk");
+
+        let mut block = chain.getnewblock(&address(key)).unwrap();
+        block.txdata[0].output[0].value = 1;
+        assert_rejected(chain.testproposedblock(&block), "bad-txnmrklroot");
+    }
+
+    #[test]
+    fn test_xfield() {
+        let chain = chain();
+        let old_key = &TEST_KEYS.key[0];
+        let new_key = &TEST_KEYS.key[1];
+
+        // The new aggregated public key verifies the blocks after the block which has it.
+        let mut block = chain.getnewblock(&address(old_key)).unwrap();
+        block.header.xfield = XField::AggregatePublicKey(public_key(new_key));
+        chain.submitblock(&sign(&block, old_key)).unwrap();
+        assert_eq!(chain.aggregated_public_key(), public_key(new_key));
+
+        let block = chain.getnewblock(&address(old_key)).unwrap();
+        assert_rejected(chain.submitblock(&sign(&block, old_key)), "bad-proof");
+        chain.submitblock(&sign(&block, new_key)).unwrap();
+
+        // The new max block size limits the blocks after the block which has it.
+        let mut block = chain.getnewblock(&address(old_key)).unwrap();
+        let size = serialize(&block).len() as u32 + 64;
+        block.header.xfield = XField::MaxBlockSize(size - 1);
+        chain.submitblock(&sign(&block, new_key)).unwrap();
+        assert_eq!(chain.max_block_size(), size - 1);
+
+        let block = chain.getnewblock(&address(old_key)).unwrap();
+        assert_rejected(
+            chain.testproposedblock(&sign(&block, new_key)),
+            "bad-blk-length",
+        );
+        assert!(chain.testproposedblock(&block).unwrap());
+
+        let mut block = chain.getnewblock(&address(old_key)).unwrap();
+        block.header.xfield = XField::MaxBlockSize(0);
+        assert_rejected(chain.testproposedblock(&block), "bad-xfield");
+    }
+
+    #[test]
+    fn test_server() {
+        let chain = chain();
+        let server = MockCoreServer::bind("127.0.0.1:0", chain.clone())
+            .unwrap()
+            .credentials("user".to_string(), "pass".to_string());
+        let url = format!("http://{}", server.local_addr().unwrap());
+        server.start();
+
+        let rpc = Rpc::new(
+            url.clone(),
+            Some("user".to_string()),
+            Some("pass".to_string()),
+        );
+        let key = &TEST_KEYS.key[0];
+        let info = rpc.getblockchaininfo().unwrap();
+        assert_eq!(info.blocks, 0);
+        assert!(!info.initialblockdownload);
+
+        let block = rpc.getnewblock(&address(key)).unwrap();
+        assert!(rpc.testproposedblock(&block).unwrap());
+        assert!(rpc.submitblock(&block).is_err());
+        rpc.submitblock(&sign(&block, key)).unwrap();
+        assert_eq!(rpc.getblockheader(1).unwrap(), chain.tip().header);
+        assert_eq!(rpc.getmempoolinfo().unwrap().size, 0);
+        match rpc.getblockheader(2) {
+            Err(Error::InvalidRequest(e)) => assert_eq!(e.code, RPC_INVALID_PARAMETER),
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        let rpc = Rpc::new(url, Some("user".to_string()), Some("wrong".to_string()));
+        match rpc.getblockchaininfo() {
+            Err(Error::RpcUnauthorized(_)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+}
@@ -2,9 +2,12 @@
 // Distributed under the MIT software license, see the accompanying
 // file COPYING or http://www.opensource.org/licenses/mit-license.php.
 
+use crate::dkg::DkgMessage;
 use crate::errors;
+use crate::federation::FederationFingerprint;
+use crate::redis_client::{RedisConnector, RedisOptions};
 use crate::serialize::{ByteBufVisitor, HexStrVisitor};
-use redis::{Client, Commands, ControlFlow, PubSubCommands, RedisError};
+use redis::{Commands, ControlFlow, PubSubCommands, RedisError};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
 use std::fmt::{Debug, Display};
@@ -12,7 +15,7 @@ use std::sync::mpsc::{channel, Receiver, Sender};
 /// メッセージを受け取って、それを処理するためのモジュール
 /// メッセージの処理は、メッセージの種類とラウンドの状態に依存する。
 /// ラウンドの状態は 誰が master であるか（自身がmaster であるか）。ラウンドが実行中であるか、開始待ちであるか。などで変わる
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
 use std::time::Duration;
@@ -22,7 +25,7 @@ use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 use curv::FE;
 use serde::export::fmt::Error;
 use serde::export::Formatter;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::sync::mpsc::TryRecvError;
 use tapyrus::blockdata::block::Block;
 use tapyrus::hash_types::BlockSigHash;
@@ -98,13 +101,15 @@ impl<'de> Deserialize<'de> for SignerID {
 }
 
 /// Messages which are sent to and received from other signer nodes
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum MessageType {
     Candidateblock(Block),
     Completedblock(Block),
     Blockvss(BlockSigHash, VerifiableSS, FE, VerifiableSS, FE),
     Blockparticipants(BlockSigHash, HashSet<SignerID>),
     Blocksig(BlockSigHash, FE, FE),
+    Heartbeat(Heartbeat),
+    Dkg(DkgMessage),
 }
 
 impl Display for MessageType {
@@ -115,17 +120,91 @@ impl Display for MessageType {
             MessageType::Blockvss(_, _, _, _, _) => write!(f, "Blockvss"),
             MessageType::Blockparticipants(_, _) => write!(f, "Blockparticipants"),
             MessageType::Blocksig(_, _, _) => write!(f, "Blocksig"),
+            MessageType::Heartbeat(_) => write!(f, "Heartbeat"),
+            MessageType::Dkg(_) => write!(f, "Dkg"),
         }
     }
 }
 
-#[derive(PartialEq, Debug, Serialize, Deserialize)]
+/// Payload of heartbeat message. Each node broadcasts it periodically so that other nodes can
+/// know which signers are online.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Heartbeat {
+    /// Version of tapyrus-signer which the sender runs.
+    pub version: String,
+    /// A height at the tip block which the sender knows.
+    pub tip_height: u64,
+    /// The kind of the sender's current node state. e.g. "Master", "Member"
+    pub state: String,
+    /// Fingerprints of the federations which the sender has. It is empty if the sender runs an
+    /// older version.
+    #[serde(default)]
+    pub federations: Vec<FederationFingerprint>,
+}
+
+impl Heartbeat {
+    pub fn new(tip_height: u64, state: &str) -> Self {
+        Heartbeat {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            tip_height,
+            state: state.to_string(),
+            federations: vec![],
+        }
+    }
+
+    pub fn with_federations(mut self, federations: Vec<FederationFingerprint>) -> Self {
+        self.federations = federations;
+        self
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Message {
     pub message_type: MessageType,
     pub sender_id: SignerID,
     pub receiver_id: Option<SignerID>,
 }
 
+/// Message on the wire of a named signer network. The message is bound to the network, so that
+/// messages from another network sharing the message broker are rejected.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct NetworkMessage {
+    network_id: String,
+    message: Message,
+}
+
+/// Serializes the message to send. Messages in the network without id are not wrapped, as older
+/// versions send.
+pub fn encode_message(network_id: Option<&str>, message: &Message) -> String {
+    match network_id {
+        Some(network_id) => serde_json::to_string(&NetworkMessage {
+            network_id: network_id.to_string(),
+            message: message.clone(),
+        }),
+        None => serde_json::to_string(message),
+    }
+    .expect("Message must be serialized.")
+}
+
+/// Deserializes the received message. Returns `NetworkMismatch` if the message is from another
+/// network.
+pub fn decode_message(network_id: Option<&str>, payload: &str) -> Result<Message, errors::Error> {
+    match network_id {
+        Some(network_id) => {
+            let received: NetworkMessage = serde_json::from_str(payload)?;
+            if received.network_id == network_id {
+                Ok(received.message)
+            } else {
+                Err(errors::Error::NetworkMismatch(received.network_id))
+            }
+        }
+        None => match serde_json::from_str::<NetworkMessage>(payload) {
+            Ok(received) => Err(errors::Error::NetworkMismatch(received.network_id)),
+            Err(_) => Ok(serde_json::from_str(payload)?),
+        },
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Signature(pub secp256k1::Signature);
 
@@ -202,23 +281,54 @@ impl From<RedisError> for ConnectionManagerError<RedisError> {
 }
 
 pub struct RedisManager {
-    pub client: Arc<Client>,
+    pub client: Arc<RedisConnector>,
     error_sender: Sender<ConnectionManagerError<RedisError>>,
     pub error_receiver: Receiver<ConnectionManagerError<RedisError>>,
+    network_id: Option<String>,
 }
 
 impl RedisManager {
     pub fn new(host: String, port: String) -> Self {
-        let url: &str = &format!("redis://{}:{}", host, port);
-        let client = Arc::new(Client::open(url).unwrap());
+        let port = port.parse().expect("Redis port is invalid.");
+        RedisManager::with_options(RedisOptions::new(host, port)).unwrap()
+    }
+
+    /// Creates the manager which connects to Redis with the credentials, TLS and Sentinel in the
+    /// options.
+    pub fn with_options(options: RedisOptions) -> Result<Self, errors::Error> {
+        let client = Arc::new(RedisConnector::new(options)?);
         let (s, r): (
             Sender<ConnectionManagerError<RedisError>>,
             Receiver<ConnectionManagerError<RedisError>>,
         ) = channel();
-        RedisManager {
+        Ok(RedisManager {
             client,
             error_sender: s,
             error_receiver: r,
+            network_id: None,
+        })
+    }
+
+    /// Namespaces the channels and the messages with the network identifier, so that signer
+    /// networks can share a Redis.
+    pub fn network_id(mut self, network_id: Option<String>) -> Self {
+        self.network_id = network_id;
+        self
+    }
+
+    /// Returns the channel which all signers subscribe.
+    fn broadcast_channel(&self) -> String {
+        match &self.network_id {
+            Some(network_id) => format!("tapyrus-signer:{}", network_id),
+            None => "tapyrus-signer".to_string(),
+        }
+    }
+
+    /// Returns the channel which only the signer subscribes.
+    fn signer_channel(&self, id: &SignerID) -> String {
+        match &self.network_id {
+            Some(network_id) => format!("tapyrus-signer:{}:{}", network_id, id.pubkey.key),
+            None => format!("tapyrus-signer-{}", id.pubkey.key),
         }
     }
 
@@ -228,26 +338,34 @@ impl RedisManager {
     {
         let client = Arc::clone(&self.client);
         let error_sender = self.error_sender.clone();
-        let channel_name = format!("tapyrus-signer-{}", id.pubkey.key);
+        let channel_names = [self.broadcast_channel(), self.signer_channel(&id)];
+        let network_id = self.network_id.clone();
         thread::Builder::new()
             .name("RedisManagerThread".to_string())
             .spawn(move || {
                 fn inner_subscribe<F2>(
                     id: SignerID,
-                    client: Arc<Client>,
+                    client: Arc<RedisConnector>,
                     mut message_processor: F2,
-                    channel_name: &str,
+                    channel_names: &[String],
+                    network_id: Option<&str>,
                 ) -> Result<(), ConnectionManagerError<RedisError>>
                 where
                     F2: FnMut(Message) -> ControlFlow<()> + Send + 'static,
                 {
                     let mut conn = client.get_connection()?;
-                    conn.subscribe(&["tapyrus-signer", channel_name], |msg| {
+                    conn.subscribe(channel_names, |msg| {
                         let _ch = msg.get_channel_name();
                         let payload: String = msg.get_payload().unwrap();
                         log::trace!("receive message. payload: {}", payload);
 
-                        let message: Message = serde_json::from_str(&payload).unwrap();
+                        let message = match decode_message(network_id, &payload) {
+                            Ok(message) => message,
+                            Err(e) => {
+                                log::warn!("Reject the message. error: {:?}", e);
+                                return ControlFlow::Continue;
+                            }
+                        };
                         if id == message.sender_id {
                             // Ignore the message when the sender is myself.
                             ControlFlow::Continue
@@ -257,7 +375,13 @@ impl RedisManager {
                     })?;
                     Ok(())
                 }
-                if let Err(e) = inner_subscribe(id, client, message_processor, &channel_name) {
+                if let Err(e) = inner_subscribe(
+                    id,
+                    client,
+                    message_processor,
+                    &channel_names,
+                    network_id.as_deref(),
+                ) {
                     let _ = error_sender.send(e);
                 }
             })
@@ -267,17 +391,17 @@ impl RedisManager {
     fn process_message(&self, message: Message, to: String) {
         let client = Arc::clone(&self.client);
         let error_sender = self.error_sender.clone();
-        let message_in_thread = serde_json::to_string(&message).unwrap();
+        let message_in_thread = encode_message(self.network_id.as_deref(), &message);
 
         let thread = thread::Builder::new()
             .name("RedisBroadcastThread".to_string())
             .spawn(move || {
                 fn inner_process_message(
-                    client: Arc<Client>,
+                    client: Arc<RedisConnector>,
                     message: &str,
                     to: &str,
                 ) -> Result<(), ConnectionManagerError<RedisError>> {
-                    let conn = client.get_connection()?;
+                    let mut conn = client.get_connection()?;
                     thread::sleep(Duration::from_millis(500));
 
                     conn.set_write_timeout(Some(Duration::from_secs(5)))?;
@@ -319,7 +443,7 @@ impl ConnectionManager for RedisManager {
 
     fn broadcast_message(&self, message: Message) {
         assert!(message.receiver_id.is_none());
-        let channel_name = "tapyrus-signer".to_string();
+        let channel_name = self.broadcast_channel();
         log::debug!(
             "broadcast_message channel_name: {}, message: {:?}",
             channel_name,
@@ -330,7 +454,7 @@ impl ConnectionManager for RedisManager {
 
     fn send_message(&self, message: Message) {
         assert!(message.receiver_id.is_some());
-        let channel_name = format!("tapyrus-signer-{}", message.receiver_id.unwrap().pubkey.key);
+        let channel_name = self.signer_channel(&message.receiver_id.unwrap());
         log::debug!(
             "send_message channel_name: {}, message: {:?}",
             channel_name,
@@ -362,6 +486,119 @@ impl ConnectionManager for RedisManager {
     }
 }
 
+/// Signer network in the memory of a process. Managers created from the network deliver messages
+/// to each other through channels without a message broker, so that a whole federation can run in
+/// a process.
+#[derive(Clone, Default)]
+pub struct InMemoryNetwork {
+    inner: Arc<Mutex<InMemoryNetworkState>>,
+}
+
+#[derive(Default)]
+struct InMemoryNetworkState {
+    subscribers: BTreeMap<SignerID, Sender<Message>>,
+    /// Signers whose messages are dropped, to simulate network failures.
+    disconnected: BTreeSet<SignerID>,
+}
+
+impl InMemoryNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn manager(&self) -> InMemoryManager {
+        InMemoryManager {
+            network: self.clone(),
+        }
+    }
+
+    /// Drops all messages from and to the signer until it is reconnected.
+    pub fn disconnect(&self, id: &SignerID) {
+        self.inner.lock().unwrap().disconnected.insert(*id);
+    }
+
+    pub fn reconnect(&self, id: &SignerID) {
+        self.inner.lock().unwrap().disconnected.remove(id);
+    }
+
+    /// Removes the subscriber of the signer. Its receiving thread ends.
+    pub fn leave(&self, id: &SignerID) {
+        self.inner.lock().unwrap().subscribers.remove(id);
+    }
+
+    fn deliver(&self, message: Message) {
+        let state = self.inner.lock().unwrap();
+        if state.disconnected.contains(&message.sender_id) {
+            log::trace!("Drop message from disconnected {:?}", message.sender_id);
+            return;
+        }
+        for (id, sender) in state.subscribers.iter() {
+            let is_receiver = match message.receiver_id {
+                Some(receiver_id) => receiver_id == *id,
+                // Redis delivers a broadcast back to its sender, and the subscriber of
+                // RedisManager drops it by sender_id. The sender doesn't get it here either.
+                None => message.sender_id != *id,
+            };
+            if is_receiver && !state.disconnected.contains(id) {
+                let _ = sender.send(message.clone());
+            }
+        }
+    }
+}
+
+pub struct InMemoryManager {
+    network: InMemoryNetwork,
+}
+
+impl ConnectionManager for InMemoryManager {
+    type ERROR = errors::Error;
+
+    fn broadcast_message(&self, message: Message) {
+        assert!(message.receiver_id.is_none());
+        self.network.deliver(message);
+    }
+
+    fn send_message(&self, message: Message) {
+        assert!(message.receiver_id.is_some());
+        self.network.deliver(message);
+    }
+
+    fn start(
+        &self,
+        mut message_processor: impl FnMut(Message) -> ControlFlow<()> + Send + 'static,
+        id: SignerID,
+    ) -> JoinHandle<()> {
+        let (sender, receiver) = channel();
+        self.network
+            .inner
+            .lock()
+            .unwrap()
+            .subscribers
+            .insert(id, sender);
+        thread::Builder::new()
+            .name("InMemoryManagerThread".to_string())
+            .spawn(move || {
+                for message in receiver.iter() {
+                    if let ControlFlow::Break(_) = message_processor(message) {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed create InMemoryManagerThread.")
+    }
+
+    fn test_connection(&self) -> Result<(), errors::Error> {
+        Ok(())
+    }
+
+    fn take_error(
+        &mut self,
+    ) -> Result<ConnectionManagerError<Self::ERROR>, std::sync::mpsc::TryRecvError> {
+        // Messages are delivered without any connection, so that nothing fails.
+        Err(TryRecvError::Empty)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -448,6 +685,32 @@ mod test {
         assert_eq!(expected, signer_id);
     }
 
+    #[test]
+    fn test_heartbeat_serialize() {
+        let message = Message {
+            message_type: MessageType::Heartbeat(Heartbeat::new(10, "Member")),
+            sender_id: SignerID::new(TEST_KEYS.pubkeys()[4]),
+            receiver_id: None,
+        };
+        let serialized = serde_json::to_string(&message).unwrap();
+        let deserialized: Message = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(message, deserialized);
+
+        match deserialized.message_type {
+            MessageType::Heartbeat(heartbeat) => {
+                assert_eq!(heartbeat.version, env!("CARGO_PKG_VERSION"));
+                assert_eq!(heartbeat.tip_height, 10);
+                assert_eq!(heartbeat.state, "Member");
+            }
+            m => panic!("Unexpected message type {:?}", m),
+        }
+
+        // Heartbeat from the older version which doesn't have federations.
+        let serialized = r#"{"version":"0.4.0","tip_height":10,"state":"Member"}"#;
+        let heartbeat: Heartbeat = serde_json::from_str(serialized).unwrap();
+        assert!(heartbeat.federations.is_empty());
+    }
+
     #[test]
     fn test_sort_signer_id() {
         let alice = SignerID::new(
@@ -480,4 +743,93 @@ mod test {
         let values: Vec<&str> = map.values().cloned().collect();
         assert_eq!(values, vec!["c", "b", "a"]);
     }
+
+    #[test]
+    fn test_encode_and_decode_message() {
+        let message = crate::tests::helper::create_message();
+
+        let payload = encode_message(Some("dev"), &message);
+        assert_eq!(decode_message(Some("dev"), &payload).unwrap(), message);
+        match decode_message(Some("staging"), &payload) {
+            Err(errors::Error::NetworkMismatch(id)) => assert_eq!(id, "dev"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        match decode_message(None, &payload) {
+            Err(errors::Error::NetworkMismatch(id)) => assert_eq!(id, "dev"),
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        // Messages without the network are the same as older versions send.
+        let payload = encode_message(None, &message);
+        assert_eq!(payload, serde_json::to_string(&message).unwrap());
+        assert_eq!(decode_message(None, &payload).unwrap(), message);
+        assert!(decode_message(Some("dev"), &payload).is_err());
+    }
+
+    #[test]
+    fn test_channel_names() {
+        let id = SignerID::new(TEST_KEYS.pubkeys()[4]);
+        let manager = RedisManager::new("127.0.0.1".to_string(), "6379".to_string());
+        assert_eq!(manager.broadcast_channel(), "tapyrus-signer");
+        assert_eq!(
+            manager.signer_channel(&id),
+            format!("tapyrus-signer-{}", id.pubkey.key)
+        );
+
+        let manager = manager.network_id(Some("dev".to_string()));
+        assert_eq!(manager.broadcast_channel(), "tapyrus-signer:dev");
+        assert_eq!(
+            manager.signer_channel(&id),
+            format!("tapyrus-signer:dev:{}", id.pubkey.key)
+        );
+    }
+
+    #[test]
+    fn test_in_memory_network() {
+        let network = InMemoryNetwork::new();
+        let ids: Vec<SignerID> = TEST_KEYS.signer_ids()[..3].to_vec();
+        let receivers: Vec<Receiver<Message>> = ids
+            .iter()
+            .map(|id| {
+                let (sender, receiver) = channel();
+                network.manager().start(
+                    move |message| match sender.send(message) {
+                        Ok(_) => ControlFlow::Continue,
+                        Err(_) => ControlFlow::Break(()),
+                    },
+                    *id,
+                );
+                receiver
+            })
+            .collect();
+        let message = |sender_id: SignerID, receiver_id: Option<SignerID>| Message {
+            message_type: MessageType::Candidateblock(get_block(0)),
+            sender_id,
+            receiver_id,
+        };
+        let recv = |i: usize| receivers[i].recv_timeout(Duration::from_millis(500));
+        let manager = network.manager();
+
+        // The own broadcast is not delivered to the sender, as RedisManager drops it.
+        manager.broadcast_message(message(ids[0], None));
+        assert_eq!(recv(1).unwrap(), message(ids[0], None));
+        assert_eq!(recv(2).unwrap(), message(ids[0], None));
+        assert!(recv(0).is_err());
+
+        manager.send_message(message(ids[0], Some(ids[2])));
+        assert_eq!(recv(2).unwrap(), message(ids[0], Some(ids[2])));
+        assert!(recv(1).is_err());
+
+        // Messages from and to the disconnected signer are dropped.
+        network.disconnect(&ids[1]);
+        manager.broadcast_message(message(ids[1], None));
+        manager.broadcast_message(message(ids[0], None));
+        assert_eq!(recv(2).unwrap(), message(ids[0], None));
+        assert!(recv(2).is_err());
+        assert!(recv(1).is_err());
+
+        network.reconnect(&ids[1]);
+        manager.send_message(message(ids[0], Some(ids[1])));
+        assert_eq!(recv(1).unwrap(), message(ids[0], Some(ids[1])));
+    }
 }
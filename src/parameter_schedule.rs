@@ -0,0 +1,160 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use crate::errors::Error;
+use crate::federation::Federations;
+use std::collections::HashSet;
+use tapyrus::blockdata::block::XField;
+
+/// A change of the chain parameters which takes effect from the block height. Like the aggregated
+/// public key of a federation, it is put into the xfield of the block one before the height.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParameterChange {
+    pub block_height: u64,
+    pub max_block_size: u32,
+}
+
+impl ParameterChange {
+    pub fn xfield(&self) -> XField {
+        XField::MaxBlockSize(self.max_block_size)
+    }
+}
+
+/// The schedule of the chain parameter changes. It is loaded from `[[parameter-change]]` entries
+/// in the federations toml file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParameterSchedule {
+    changes: Vec<ParameterChange>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SerParameterSchedule {
+    #[serde(rename = "parameter-change", default)]
+    parameter_change: Vec<ParameterChange>,
+}
+
+impl ParameterSchedule {
+    pub fn new(mut changes: Vec<ParameterChange>) -> Self {
+        changes.sort_by_key(|c| c.block_height);
+        ParameterSchedule { changes }
+    }
+
+    /// Returns the change which takes effect at the block height.
+    pub fn get(&self, block_height: u64) -> Option<&ParameterChange> {
+        self.changes.iter().find(|c| c.block_height == block_height)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<ParameterChange> {
+        self.changes.iter()
+    }
+
+    /// Checks the schedule. A block has only one xfield, so a change can't be at the same height
+    /// as a federation, which puts the aggregated public key into the xfield.
+    pub fn validate(&self, federations: &Federations) -> Result<(), Error> {
+        let unique_block_height: HashSet<u64> =
+            self.changes.iter().map(|c| c.block_height).collect();
+        if unique_block_height.len() != self.changes.len() {
+            return Err(Error::InvalidParameterChange(
+                None,
+                "The parameter changes include block height duplication.",
+            ));
+        }
+
+        for change in &self.changes {
+            if change.block_height == 0 {
+                return Err(Error::InvalidParameterChange(
+                    Some(change.block_height),
+                    "The parameters of the genesis block can not be changed.",
+                ));
+            }
+            if change.max_block_size == 0 {
+                return Err(Error::InvalidParameterChange(
+                    Some(change.block_height),
+                    "max-block-size should be greater than 0.",
+                ));
+            }
+            if federations
+                .iter()
+                .any(|f| f.block_height() == change.block_height)
+            {
+                return Err(Error::InvalidParameterChange(
+                    Some(change.block_height),
+                    "The parameter change is at the same block height as a federation.",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the schedule from the federations toml. It is empty if the toml has no
+    /// `[[parameter-change]]` entry.
+    pub fn from_toml(toml: &str, federations: &Federations) -> Result<Self, Error> {
+        let ser: SerParameterSchedule = toml::from_str(toml)?;
+        let schedule = ParameterSchedule::new(ser.parameter_change);
+        schedule.validate(federations)?;
+        Ok(schedule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::Federation;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use crate::tests::helper::node_vss::node_vss;
+
+    fn federations() -> Federations {
+        Federations::new(vec![Federation::new(
+            TEST_KEYS.pubkeys()[0],
+            100,
+            Some(3),
+            Some(node_vss(0)),
+            TEST_KEYS.aggregated(),
+        )])
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let toml = "[[parameter-change]]\nblock-height = 300\nmax-block-size = 2000000\n\n[[parameter-change]]\nblock-height = 200\nmax-block-size = 1000000\n";
+        let schedule = ParameterSchedule::from_toml(toml, &federations()).unwrap();
+        assert_eq!(schedule.iter().count(), 2);
+        assert_eq!(schedule.iter().next().unwrap().block_height, 200);
+        assert_eq!(
+            schedule.get(300).unwrap().xfield(),
+            XField::MaxBlockSize(2_000_000)
+        );
+        assert!(schedule.get(250).is_none());
+
+        // The federations toml without parameter changes.
+        let schedule = ParameterSchedule::from_toml("", &federations()).unwrap();
+        assert_eq!(schedule, ParameterSchedule::default());
+    }
+
+    #[test]
+    fn test_validate() {
+        let change = |block_height, max_block_size| ParameterChange {
+            block_height,
+            max_block_size,
+        };
+
+        let schedule = ParameterSchedule::new(vec![change(200, 1), change(200, 2)]);
+        assert!(schedule.validate(&federations()).is_err());
+
+        let schedule = ParameterSchedule::new(vec![change(100, 1)]);
+        match schedule.validate(&federations()) {
+            Err(Error::InvalidParameterChange(Some(100), _)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        let schedule = ParameterSchedule::new(vec![change(200, 0)]);
+        assert!(schedule.validate(&federations()).is_err());
+
+        let schedule = ParameterSchedule::new(vec![change(0, 1)]);
+        assert!(schedule.validate(&federations()).is_err());
+
+        let schedule = ParameterSchedule::new(vec![change(200, 1)]);
+        assert!(schedule.validate(&federations()).is_ok());
+    }
+}
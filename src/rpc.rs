@@ -5,10 +5,15 @@
 use log::Level::Trace;
 use log::{log_enabled, trace};
 use serde::Deserialize;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tapyrus::Address;
 
 use crate::errors::Error;
-use tapyrus::blockdata::block::Block;
+use crate::rpc_client::{RpcAuth, RpcClient};
+use tapyrus::blockdata::block::{Block, BlockHeader};
 use tapyrus::consensus::encode::{deserialize, serialize};
 
 #[derive(Debug, Deserialize, Clone)]
@@ -21,8 +26,98 @@ pub struct GetBlockchainInfoResult {
     pub initialblockdownload: bool,
 }
 
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct GetMempoolInfoResult {
+    /// Number of transactions in the mempool.
+    pub size: u64,
+    /// Total size of the transactions in bytes.
+    pub bytes: u64,
+}
+
+/// Interval of checking whether the endpoints preferred to the active one are back.
+pub const HEALTH_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Methods which don't change the state of Tapyrus Core, so that they can be retried.
+/// `getnewblock` is not included, because a timed out call may have created a template.
+pub const IDEMPOTENT_METHODS: [&str; 5] = [
+    "testproposedblock",
+    "getblockchaininfo",
+    "getmempoolinfo",
+    "getblockhash",
+    "getblockheader",
+];
+
+/// Timeouts and retries of RPC calls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcOptions {
+    /// Timeout of a call.
+    pub timeout: Duration,
+    /// Timeouts for each method, which take precedence over `timeout`.
+    pub method_timeouts: HashMap<String, Duration>,
+    /// Max number of retries of an idempotent call after a transport error.
+    pub retries: u32,
+    /// Wait before the first retry. It doubles on every retry.
+    pub retry_backoff: Duration,
+    /// Max time of a call including all retries and failovers. It also caps the timeout of each
+    /// attempt.
+    pub deadline: Duration,
+}
+
+impl Default for RpcOptions {
+    fn default() -> Self {
+        RpcOptions {
+            timeout: Duration::from_secs(10),
+            method_timeouts: HashMap::new(),
+            retries: 2,
+            retry_backoff: Duration::from_millis(200),
+            deadline: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RpcOptions {
+    pub fn timeout(&self, method: &str) -> Duration {
+        self.method_timeouts
+            .get(method)
+            .cloned()
+            .unwrap_or(self.timeout)
+    }
+
+    /// Returns the wait before the retry. It has a random jitter up to the half of the wait, so
+    /// that signers don't retry at the same time.
+    fn backoff(&self, retry: u32) -> Duration {
+        let wait = self.retry_backoff * 2u32.pow(retry);
+        let max_jitter = wait.as_millis() as u64 / 2;
+        if max_jitter == 0 {
+            return wait;
+        }
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or(0);
+        wait + Duration::from_millis(seed % max_jitter)
+    }
+}
+
+/// Returns true if the call failed before Tapyrus Core answered, like a connection failure or a
+/// timeout. Such calls can be retried or failed over. Rejections by Tapyrus Core, like an invalid
+/// block, are not transport errors.
+pub fn is_transport_error(error: &Error) -> bool {
+    match error {
+        Error::RpcTimeout(_) | Error::RpcTransport(_) => true,
+        Error::JsonRpc(jsonrpc::error::Error::Rpc(_))
+        | Error::JsonRpc(jsonrpc::error::Error::Json(_)) => false,
+        Error::JsonRpc(_) => true,
+        _ => false,
+    }
+}
+
 pub struct Rpc {
-    client: jsonrpc::client::Client,
+    url: String,
+    client: RpcClient,
+    options: RpcOptions,
+    /// The deadline which `FailoverRpc` sets for the calls over all endpoints.
+    deadline: Cell<Option<Instant>>,
 }
 
 pub trait TapyrusApi {
@@ -34,26 +129,98 @@ pub trait TapyrusApi {
     fn submitblock(&self, block: &Block) -> Result<(), Error>;
     /// Get block chain info
     fn getblockchaininfo(&self) -> Result<GetBlockchainInfoResult, Error>;
+    /// Get the header of the block at the height in the active chain.
+    fn getblockheader(&self, block_height: u64) -> Result<BlockHeader, Error>;
+    /// Get the state of the mempool.
+    fn getmempoolinfo(&self) -> Result<GetMempoolInfoResult, Error>;
+    /// Returns the endpoint of Tapyrus Core which serves calls.
+    fn endpoint(&self) -> Option<String> {
+        None
+    }
+    /// Bounds the following calls, including their retries, by the deadline. `None` removes it.
+    fn set_deadline(&self, _deadline: Option<Instant>) {}
 }
 
 impl Rpc {
     pub fn new(url: String, user: Option<String>, pass: Option<String>) -> Self {
         // Check that if we have a password, we have a username; other way around is ok
         debug_assert!(pass.is_none() || user.is_some());
-        Rpc {
-            client: jsonrpc::client::Client::new(url, user, pass),
-        }
+        let auth = match user {
+            Some(user) => RpcAuth::UserPass(user, pass),
+            None => RpcAuth::None,
+        };
+        Rpc::connect(url, auth, None).expect("Failed to create RPC client")
+    }
+
+    /// Creates with the credentials. `ca_cert` is the path to a PEM file of the CA certificate
+    /// which a `https` endpoint is verified with in addition to the system root certificates.
+    pub fn connect(url: String, auth: RpcAuth, ca_cert: Option<&Path>) -> Result<Self, Error> {
+        Ok(Rpc {
+            url: url.clone(),
+            client: RpcClient::new(url, auth, ca_cert)?,
+            options: RpcOptions::default(),
+            deadline: Cell::new(None),
+        })
     }
 
+    pub fn with_options(mut self, options: RpcOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Calls the method. Idempotent methods are retried on transport errors until the deadline.
     fn call<T>(&self, name: &str, params: &[serde_json::Value]) -> Result<T, Error>
     where
         T: serde::de::DeserializeOwned,
     {
-        let req = self.client.build_request(name, params);
+        let retries = if IDEMPOTENT_METHODS.contains(&name) {
+            self.options.retries
+        } else {
+            0
+        };
+        let deadline = self
+            .deadline
+            .get()
+            .unwrap_or_else(|| Instant::now() + self.options.deadline);
+        let mut retry = 0;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining == Duration::from_secs(0) {
+                return Err(Error::RpcTimeout(name.to_string()));
+            }
+            let timeout = std::cmp::min(self.options.timeout(name), remaining);
+            match self.call_once(name, params, timeout) {
+                Err(e) if retry < retries && is_transport_error(&e) => {
+                    let wait = self.options.backoff(retry);
+                    if Instant::now() + wait >= deadline {
+                        return Err(e);
+                    }
+                    warn!(
+                        "RPC {} failed. Retry after {:?}. reason={:?}",
+                        name, wait, e
+                    );
+                    std::thread::sleep(wait);
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
 
-        trace!("JSON-RPC request: {}", serde_json::to_string(&req).unwrap());
+    /// Calls the method once. The client bounds the connection, the request and the response by
+    /// the timeout, so that a hung Tapyrus Core doesn't block the caller longer than that.
+    fn call_once<T>(
+        &self,
+        name: &str,
+        params: &[serde_json::Value],
+        timeout: Duration,
+    ) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let resp = self.client.send_request(name, params, timeout);
 
-        match self.client.send_request(&req) {
+        match resp {
             Ok(resp) => {
                 if log_enabled!(Trace) {
                     trace!(
@@ -73,7 +240,7 @@ impl Rpc {
                     Err(e) => Err(Error::JsonRpc(e)),
                 }
             }
-            Err(e) => Err(Error::from(e)),
+            Err(e) => Err(e),
         }
     }
 
@@ -93,7 +260,7 @@ impl TapyrusApi for Rpc {
 
         match resp {
             Ok(v) => {
-                let raw_block = hex::decode(v).expect("Decoding block hex failed");
+                let raw_block = hex::decode(v).map_err(|_| Error::InvalidBlock)?;
                 deserialize(&raw_block).map_err(|_| Error::InvalidBlock)
             }
             Err(e) => Err(e),
@@ -113,11 +280,194 @@ impl TapyrusApi for Rpc {
     fn getblockchaininfo(&self) -> Result<GetBlockchainInfoResult, Error> {
         self.call::<GetBlockchainInfoResult>("getblockchaininfo", &[])
     }
+
+    fn getblockheader(&self, block_height: u64) -> Result<BlockHeader, Error> {
+        let hash = self.call::<String>("getblockhash", &[block_height.into()])?;
+        let resp = self.call::<String>("getblockheader", &[hash.into(), false.into()])?;
+        let raw_header = hex::decode(resp).map_err(|_| Error::InvalidBlock)?;
+        deserialize(&raw_header).map_err(|_| Error::InvalidBlock)
+    }
+
+    fn getmempoolinfo(&self) -> Result<GetMempoolInfoResult, Error> {
+        self.call::<GetMempoolInfoResult>("getmempoolinfo", &[])
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        Some(self.url.clone())
+    }
+
+    fn set_deadline(&self, deadline: Option<Instant>) {
+        self.deadline.set(deadline);
+    }
+}
+
+struct Backend<T: TapyrusApi> {
+    endpoint: String,
+    api: T,
+}
+
+/// `TapyrusApi` over an ordered list of Tapyrus Core endpoints. Calls are served by the active
+/// endpoint and fail over to the next one on transport errors. The active endpoint
+/// falls back to a preferred one once it passes the health check again.
+pub struct FailoverRpc<T: TapyrusApi = Rpc> {
+    backends: Vec<Backend<T>>,
+    active: Cell<usize>,
+    last_health_check: Cell<Instant>,
+    /// Max time of a call over all endpoints.
+    deadline: Duration,
+}
+
+impl FailoverRpc<Rpc> {
+    pub fn new(
+        urls: Vec<String>,
+        auth: RpcAuth,
+        ca_cert: Option<&Path>,
+        options: RpcOptions,
+    ) -> Result<Self, Error> {
+        let backends = urls
+            .into_iter()
+            .map(|url| {
+                let rpc =
+                    Rpc::connect(url.clone(), auth.clone(), ca_cert)?.with_options(options.clone());
+                Ok((url, rpc))
+            })
+            .collect::<Result<Vec<(String, Rpc)>, Error>>()?;
+        Ok(FailoverRpc::with_backends(backends).with_deadline(options.deadline))
+    }
+}
+
+impl<T: TapyrusApi> FailoverRpc<T> {
+    /// Creates with pairs of the endpoint and the api in the order of preference.
+    pub fn with_backends(backends: Vec<(String, T)>) -> Self {
+        assert!(!backends.is_empty(), "No RPC endpoints are specified.");
+        FailoverRpc {
+            backends: backends
+                .into_iter()
+                .map(|(endpoint, api)| Backend { endpoint, api })
+                .collect(),
+            active: Cell::new(0),
+            last_health_check: Cell::new(Instant::now()),
+            deadline: RpcOptions::default().deadline,
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Returns the endpoint which serves calls now.
+    pub fn active_endpoint(&self) -> &str {
+        &self.backends[self.active.get()].endpoint
+    }
+
+    /// Checks all endpoints with `getblockchaininfo` and makes the first healthy one active.
+    /// Returns each endpoint and the result.
+    pub fn health_check(&self) -> Vec<(String, Result<(), Error>)> {
+        self.last_health_check.set(Instant::now());
+        let results: Vec<(String, Result<(), Error>)> = self
+            .backends
+            .iter()
+            .map(|b| (b.endpoint.clone(), b.api.getblockchaininfo().map(|_| ())))
+            .collect();
+        if let Some(index) = results.iter().position(|(_, r)| r.is_ok()) {
+            self.activate(index);
+        }
+        results
+    }
+
+    fn activate(&self, index: usize) {
+        if index != self.active.get() {
+            log::warn!(
+                "Tapyrus Core RPC endpoint is switched from {} to {}.",
+                self.active_endpoint(),
+                self.backends[index].endpoint
+            );
+            self.active.set(index);
+        }
+    }
+
+    fn call<R, F>(&self, method: &str, f: F) -> Result<R, Error>
+    where
+        F: Fn(&T) -> Result<R, Error>,
+    {
+        if self.active.get() > 0
+            && self.last_health_check.get().elapsed()
+                >= Duration::from_secs(HEALTH_CHECK_INTERVAL_SECS)
+        {
+            self.health_check();
+        }
+
+        // One deadline bounds the retries on all endpoints, so that a call doesn't take the
+        // timeout times the retries times the endpoints.
+        let deadline = Instant::now() + self.deadline;
+        let start = self.active.get();
+        let mut last_error = None;
+        for i in 0..self.backends.len() {
+            if i > 0 && Instant::now() >= deadline {
+                log::warn!("RPC {} is not failed over after the deadline.", method);
+                break;
+            }
+            let index = (start + i) % self.backends.len();
+            let backend = &self.backends[index];
+            backend.api.set_deadline(Some(deadline));
+            let result = f(&backend.api);
+            backend.api.set_deadline(None);
+            match result {
+                Err(e) if is_transport_error(&e) => {
+                    log::warn!(
+                        "RPC {} failed on {}. reason={:?}",
+                        method,
+                        backend.endpoint,
+                        e
+                    );
+                    last_error = Some(e);
+                }
+                result => {
+                    log::debug!("RPC {} is served by {}.", method, backend.endpoint);
+                    self.activate(index);
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("No RPC endpoints are specified."))
+    }
+}
+
+impl<T: TapyrusApi> TapyrusApi for FailoverRpc<T> {
+    fn getnewblock(&self, address: &Address) -> Result<Block, Error> {
+        self.call("getnewblock", |api| api.getnewblock(address))
+    }
+
+    fn testproposedblock(&self, block: &Block) -> Result<bool, Error> {
+        self.call("testproposedblock", |api| api.testproposedblock(block))
+    }
+
+    fn submitblock(&self, block: &Block) -> Result<(), Error> {
+        self.call("submitblock", |api| api.submitblock(block))
+    }
+
+    fn getblockchaininfo(&self) -> Result<GetBlockchainInfoResult, Error> {
+        self.call("getblockchaininfo", |api| api.getblockchaininfo())
+    }
+
+    fn getblockheader(&self, block_height: u64) -> Result<BlockHeader, Error> {
+        self.call("getblockheader", |api| api.getblockheader(block_height))
+    }
+
+    fn getmempoolinfo(&self) -> Result<GetMempoolInfoResult, Error> {
+        self.call("getmempoolinfo", |api| api.getmempoolinfo())
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        Some(self.active_endpoint().to_string())
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::tests::helper::blocks::get_block;
     use crate::tests::helper::keys::TEST_KEYS;
     use tapyrus::secp256k1::Secp256k1;
 
@@ -207,6 +557,176 @@ pub mod tests {
                 initialblockdownload: false,
             })
         }
+
+        fn getblockheader(&self, _block_height: u64) -> Result<BlockHeader, Error> {
+            Ok(self.result()?.header)
+        }
+
+        fn getmempoolinfo(&self) -> Result<GetMempoolInfoResult, Error> {
+            Ok(GetMempoolInfoResult { size: 0, bytes: 0 })
+        }
+    }
+
+    #[test]
+    fn test_failover_rpc() {
+        use crate::tests::helper::rpc::MockRpc as QueueRpc;
+
+        let info = GetBlockchainInfoResult {
+            chain: "regtest".to_string(),
+            blocks: 0,
+            headers: 0,
+            bestblockhash: "xxx".to_string(),
+            mediantime: 0,
+            initialblockdownload: false,
+        };
+        let unavailable = || Error::JsonRpc(jsonrpc::error::Error::NonceMismatch);
+        let rejected = || {
+            Error::InvalidRequest(jsonrpc::error::RpcError {
+                code: -25,
+                message: "proposal was not based on our best chain".to_string(),
+                data: None,
+            })
+        };
+
+        let mut primary = QueueRpc::new();
+        primary.should_call_testproposedblock(Err(unavailable()));
+        primary.should_call_getblockchaininfo(Ok(info.clone()));
+        let mut secondary = QueueRpc::new();
+        secondary.should_call_testproposedblock(Ok(true));
+        secondary.should_call_testproposedblock(Err(rejected()));
+        secondary.should_call_getblockchaininfo(Ok(info.clone()));
+        let rpc = FailoverRpc::with_backends(vec![
+            ("primary".to_string(), primary),
+            ("secondary".to_string(), secondary),
+        ]);
+        let block = get_block(0);
+
+        // The call fails over to the secondary.
+        assert!(rpc.testproposedblock(&block).unwrap());
+        assert_eq!(rpc.active_endpoint(), "secondary");
+        assert_eq!(rpc.endpoint(), Some("secondary".to_string()));
+
+        // The rejection by Tapyrus Core is not failed over.
+        match rpc.testproposedblock(&block) {
+            Err(Error::InvalidRequest(_)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(rpc.active_endpoint(), "secondary");
+
+        // The primary is back.
+        let results = rpc.health_check();
+        assert!(results.iter().all(|(_, r)| r.is_ok()));
+        assert_eq!(rpc.active_endpoint(), "primary");
+
+        for backend in &rpc.backends {
+            backend.api.assert();
+        }
+    }
+
+    #[test]
+    fn test_is_transport_error() {
+        assert!(is_transport_error(&Error::RpcTimeout(
+            "getnewblock".to_string()
+        )));
+        assert!(is_transport_error(&Error::RpcTransport(
+            "connection refused".to_string()
+        )));
+        assert!(is_transport_error(&Error::JsonRpc(
+            jsonrpc::error::Error::NonceMismatch
+        )));
+        assert!(!is_transport_error(&Error::RpcUnauthorized(
+            "http://127.0.0.1:2377".to_string()
+        )));
+        assert!(!is_transport_error(&Error::JsonRpc(
+            jsonrpc::error::Error::Rpc(jsonrpc::error::RpcError {
+                code: -25,
+                message: "proposal was not based on our best chain".to_string(),
+                data: None,
+            })
+        )));
+        assert!(!is_transport_error(&Error::InvalidRequest(
+            jsonrpc::error::RpcError {
+                code: -8,
+                message: "Block height out of range".to_string(),
+                data: None,
+            }
+        )));
+        assert!(!is_transport_error(&Error::InvalidBlock));
+    }
+
+    #[test]
+    fn test_rpc_options() {
+        let mut options = RpcOptions::default();
+        options
+            .method_timeouts
+            .insert("getnewblock".to_string(), Duration::from_secs(30));
+        assert_eq!(options.timeout("getnewblock"), Duration::from_secs(30));
+        assert_eq!(
+            options.timeout("testproposedblock"),
+            Duration::from_secs(10)
+        );
+
+        for retry in 0..3 {
+            let wait = options.backoff(retry);
+            let base = Duration::from_millis(200) * 2u32.pow(retry);
+            assert!(wait >= base && wait < base + base / 2);
+        }
+    }
+
+    #[test]
+    fn test_call_timeout() {
+        // The listener accepts connections but never responds.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let rpc = Rpc::new(url, None, None).with_options(RpcOptions {
+            timeout: Duration::from_millis(100),
+            retries: 1,
+            retry_backoff: Duration::from_millis(10),
+            ..Default::default()
+        });
+
+        let started = Instant::now();
+        match rpc.getblockchaininfo() {
+            Err(Error::RpcTimeout(method)) => assert_eq!(method, "getblockchaininfo"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        // The call is retried once.
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_call_deadline() {
+        // The listeners accept connections but never respond.
+        let listeners: Vec<std::net::TcpListener> = (0..2)
+            .map(|_| std::net::TcpListener::bind("127.0.0.1:0").unwrap())
+            .collect();
+        let urls = listeners
+            .iter()
+            .map(|l| format!("http://{}", l.local_addr().unwrap()))
+            .collect();
+        let options = RpcOptions {
+            timeout: Duration::from_millis(200),
+            retries: 2,
+            retry_backoff: Duration::from_millis(10),
+            deadline: Duration::from_millis(300),
+            ..Default::default()
+        };
+        let rpc = FailoverRpc::new(urls, RpcAuth::None, None, options).unwrap();
+
+        // Without the deadline, the call would take 3 attempts on each of 2 endpoints, 1200 msec.
+        let started = Instant::now();
+        match rpc.getblockchaininfo() {
+            Err(Error::RpcTimeout(method)) => assert_eq!(method, "getblockchaininfo"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert!(started.elapsed() >= Duration::from_millis(250));
+        assert!(started.elapsed() < Duration::from_millis(800));
+
+        // The deadline is removed after the call.
+        for backend in &rpc.backends {
+            assert_eq!(backend.api.deadline.get(), None);
+        }
     }
 
     /// TODO: use rpc mock. Now this test needs tapyrus node process.
@@ -0,0 +1,458 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+use crate::errors::Error;
+use rustls::{ClientConfig, RootCertStore};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use ureq::{Agent, AgentBuilder};
+
+/// Credentials for Tapyrus Core RPC.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RpcAuth {
+    None,
+    /// The user name and the password.
+    UserPass(String, Option<String>),
+    /// The `.cookie` file which Tapyrus Core writes into its data directory on startup.
+    CookieFile(PathBuf),
+}
+
+/// HTTP(S) client which sends JSON-RPC requests to a Tapyrus Core endpoint. Each request,
+/// including the connection and the response, is bounded by the timeout of the call.
+pub struct RpcClient {
+    url: String,
+    agent: Agent,
+    auth: RpcAuth,
+    /// Credentials read from the cookie file. They are read again when Tapyrus Core rejects them.
+    cookie: Mutex<Option<(String, String)>>,
+    nonce: AtomicUsize,
+}
+
+impl RpcClient {
+    /// Creates the client for the url. The server certificate of a `https` url is verified with
+    /// the system root certificates and `ca_cert`, which is the path to a PEM file.
+    pub fn new(url: String, auth: RpcAuth, ca_cert: Option<&Path>) -> Result<Self, Error> {
+        let invalid = || Error::InvalidArgs(format!("Invalid RPC endpoint url: {}", url));
+        let request_url = ureq::post(&url).request_url().map_err(|_| invalid())?;
+        let agent = match request_url.scheme() {
+            "https" => AgentBuilder::new()
+                .tls_config(Arc::new(tls_config(ca_cert)?))
+                .build(),
+            "http" => AgentBuilder::new().build(),
+            _ => return Err(invalid()),
+        };
+
+        Ok(RpcClient {
+            url,
+            agent,
+            auth,
+            cookie: Mutex::new(None),
+            nonce: AtomicUsize::new(0),
+        })
+    }
+
+    /// Sends the request. If Tapyrus Core rejects the credentials from the cookie file, the file
+    /// is read again and the request is sent once more, because Tapyrus Core rewrites the file
+    /// when it restarts. Each attempt fails with `Error::RpcTimeout` if it doesn't finish within
+    /// the timeout.
+    pub fn send_request(
+        &self,
+        method: &str,
+        params: &[serde_json::Value],
+        timeout: Duration,
+    ) -> Result<jsonrpc::Response, Error> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": self.nonce.fetch_add(1, Ordering::Relaxed),
+        });
+        let body = serde_json::to_string(&request)?;
+        trace!("JSON-RPC request: {}", body);
+
+        if let Some(response) = self.post(method, &body, timeout)? {
+            return Ok(response);
+        }
+
+        if let RpcAuth::CookieFile(path) = &self.auth {
+            let reloaded = read_cookie_file(path)?;
+            let changed = {
+                let mut cookie = self.cookie.lock().unwrap();
+                let changed = cookie.as_ref() != Some(&reloaded);
+                *cookie = Some(reloaded);
+                changed
+            };
+            if changed {
+                info!("The RPC cookie file {} is reloaded.", path.display());
+                if let Some(response) = self.post(method, &body, timeout)? {
+                    return Ok(response);
+                }
+            }
+        }
+        Err(Error::RpcUnauthorized(self.url.clone()))
+    }
+
+    /// Posts the body. Returns None if the credentials are rejected.
+    fn post(
+        &self,
+        method: &str,
+        body: &str,
+        timeout: Duration,
+    ) -> Result<Option<jsonrpc::Response>, Error> {
+        let mut request = self
+            .agent
+            .post(&self.url)
+            .timeout(timeout)
+            .set("Content-Type", "application/json");
+        if let Some((username, password)) = self.credentials()? {
+            let credentials = format!("{}:{}", username, password.unwrap_or_default());
+            request = request.set(
+                "Authorization",
+                &format!("Basic {}", base64::encode(&credentials)),
+            );
+        }
+
+        let response = match request.send_string(body) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(401, _)) => return Ok(None),
+            // Tapyrus Core answers a failed call with an error status and the error in the body,
+            // so that the body is parsed regardless of the status.
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(e)) => {
+                let io_error = std::error::Error::source(&e)
+                    .and_then(|source| source.downcast_ref::<io::Error>());
+                return Err(match io_error {
+                    Some(io_error) if is_timeout(io_error) => timeout_error(method),
+                    _ => Error::RpcTransport(e.to_string()),
+                });
+            }
+        };
+
+        let status = response.status();
+        let mut text = String::new();
+        response
+            .into_reader()
+            .read_to_string(&mut text)
+            .map_err(|e| {
+                if is_timeout(&e) {
+                    timeout_error(method)
+                } else {
+                    Error::RpcTransport(e.to_string())
+                }
+            })?;
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|_| Error::RpcTransport(format!("Unexpected response {}: {}", status, text)))
+    }
+
+    fn credentials(&self) -> Result<Option<(String, Option<String>)>, Error> {
+        match &self.auth {
+            RpcAuth::None => Ok(None),
+            RpcAuth::UserPass(user, pass) => Ok(Some((user.clone(), pass.clone()))),
+            RpcAuth::CookieFile(path) => {
+                let mut cookie = self.cookie.lock().unwrap();
+                if cookie.is_none() {
+                    *cookie = Some(read_cookie_file(path)?);
+                }
+                Ok(cookie.clone().map(|(user, pass)| (user, Some(pass))))
+            }
+        }
+    }
+}
+
+/// Returns the TLS settings which verify servers with the system root certificates and the CA
+/// certificate in the PEM file.
+fn tls_config(ca_cert: Option<&Path>) -> Result<ClientConfig, Error> {
+    let mut roots = RootCertStore::empty();
+    match rustls_native_certs::load_native_certs() {
+        Ok(certificates) => {
+            roots.add_parsable_certificates(&certificates);
+        }
+        Err(e) => warn!("Failed to load the system root certificates: {}", e),
+    }
+    if let Some(path) = ca_cert {
+        let invalid = || Error::InvalidArgs(format!("Invalid CA certificate {}", path.display()));
+        let pem = std::fs::read(path)?;
+        let certificates = rustls_pemfile::certs(&mut pem.as_slice()).map_err(|_| invalid())?;
+        let (valid, invalid_count) = roots.add_parsable_certificates(&certificates);
+        if valid == 0 || invalid_count > 0 {
+            return Err(invalid());
+        }
+    }
+    Ok(ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock
+}
+
+fn timeout_error(method: &str) -> Error {
+    warn!("RPC {} timed out.", method);
+    Error::RpcTimeout(method.to_string())
+}
+
+/// Reads the user name and the password from the cookie file, which has `user:password`.
+/// Tapyrus Core removes the file while it is stopped, so that a failure to read is a transport
+/// error and the call can be retried.
+pub fn read_cookie_file(path: &Path) -> Result<(String, String), Error> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        Error::RpcTransport(format!(
+            "Failed to read the cookie file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let mut iter = content.trim().splitn(2, ':');
+    match (iter.next(), iter.next()) {
+        (Some(user), Some(pass)) if !user.is_empty() => Ok((user.to_string(), pass.to_string())),
+        _ => Err(Error::InvalidArgs(format!(
+            "Invalid cookie file {}",
+            path.display()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread::JoinHandle;
+    use std::time::Instant;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Serves the pairs of the status and the body in order, one for each connection. The server
+    /// returns the Authorization headers of the requests.
+    fn serve(responses: Vec<(&'static str, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|(status, body)| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let headers = read_request(&mut stream);
+                    write!(
+                        stream,
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                    headers
+                        .lines()
+                        .find(|l| l.starts_with("Authorization:"))
+                        .unwrap_or("")
+                        .to_string()
+                })
+                .collect()
+        });
+        (url, handle)
+    }
+
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut headers = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.is_empty() || line == "\r\n" {
+                break;
+            }
+            headers.push_str(&line);
+        }
+        let length = headers
+            .lines()
+            .find_map(|l| {
+                let l = l.to_lowercase();
+                if l.starts_with("content-length:") {
+                    l["content-length:".len()..].trim().parse::<usize>().ok()
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        headers
+    }
+
+    fn authorization(credentials: &str) -> String {
+        format!("Authorization: Basic {}", base64::encode(credentials))
+    }
+
+    fn cookie_file(name: &str, content: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("tapyrus-signer-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".cookie");
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_cookie_file() {
+        let path = cookie_file("read", "__cookie__:abc:def\n");
+        assert_eq!(
+            read_cookie_file(&path).unwrap(),
+            ("__cookie__".to_string(), "abc:def".to_string())
+        );
+
+        std::fs::write(&path, "invalid").unwrap();
+        match read_cookie_file(&path) {
+            Err(Error::InvalidArgs(_)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        std::fs::remove_file(&path).unwrap();
+        match read_cookie_file(&path) {
+            Err(Error::RpcTransport(_)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_reload_cookie_file() {
+        let path = cookie_file("reload", "__cookie__:old");
+        let (url, server) = serve(vec![
+            ("401 Unauthorized", ""),
+            ("200 OK", r#"{"result":true,"error":null,"id":0}"#),
+        ]);
+        let client = RpcClient::new(url, RpcAuth::CookieFile(path.clone()), None).unwrap();
+        assert_eq!(
+            client.credentials().unwrap(),
+            Some(("__cookie__".to_string(), Some("old".to_string())))
+        );
+
+        // Tapyrus Core restarted and rewrote the cookie file.
+        std::fs::write(&path, "__cookie__:new\n").unwrap();
+        let response = client
+            .send_request("getblockchaininfo", &[], TIMEOUT)
+            .unwrap();
+        assert!(response.result::<bool>().unwrap());
+        assert_eq!(
+            server.join().unwrap(),
+            vec![
+                authorization("__cookie__:old"),
+                authorization("__cookie__:new")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unauthorized() {
+        let (url, server) = serve(vec![("401 Unauthorized", "")]);
+        let client = RpcClient::new(
+            url.clone(),
+            RpcAuth::UserPass("user".to_string(), Some("pass".to_string())),
+            None,
+        )
+        .unwrap();
+        match client.send_request("getblockchaininfo", &[], TIMEOUT) {
+            Err(Error::RpcUnauthorized(u)) => assert_eq!(u, url),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(server.join().unwrap(), vec![authorization("user:pass")]);
+    }
+
+    #[test]
+    fn test_error_response() {
+        let (url, server) = serve(vec![
+            (
+                "500 Internal Server Error",
+                r#"{"result":null,"error":{"code":-8,"message":"Block height out of range"},"id":0}"#,
+            ),
+            ("502 Bad Gateway", "<html>Bad Gateway</html>"),
+        ]);
+        let client = RpcClient::new(url, RpcAuth::None, None).unwrap();
+
+        // The error of the call is returned in the response.
+        let response = client
+            .send_request("getblockhash", &[100.into()], TIMEOUT)
+            .unwrap();
+        match response.check_error() {
+            Err(jsonrpc::Error::Rpc(e)) => assert_eq!(e.code, -8),
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        // The response from a proxy is a transport error.
+        match client.send_request("getblockhash", &[100.into()], TIMEOUT) {
+            Err(Error::RpcTransport(_)) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert_eq!(server.join().unwrap(), vec!["".to_string(), "".to_string()]);
+    }
+
+    #[test]
+    fn test_invalid_ca_cert() {
+        let path = cookie_file("ca", "not a certificate");
+        match RpcClient::new(
+            "https://127.0.0.1:12381".to_string(),
+            RpcAuth::None,
+            Some(&path),
+        ) {
+            Err(Error::InvalidArgs(_)) => {}
+            r => panic!("unexpected result {:?}", r.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_timeout() {
+        // The listener accepts connections but never responds.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = RpcClient::new(url, RpcAuth::None, None).unwrap();
+
+        let started = Instant::now();
+        match client.send_request("getblockchaininfo", &[], Duration::from_millis(100)) {
+            Err(Error::RpcTimeout(method)) => assert_eq!(method, "getblockchaininfo"),
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_chunked_response() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/wallet/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n11\r\n{\"result\":true,\"e\r\n12\r\nrror\":null,\"id\":0}\r\n0\r\n\r\n")
+                .unwrap();
+        });
+        let client = RpcClient::new(url, RpcAuth::None, None).unwrap();
+        let response = client
+            .send_request("getblockchaininfo", &[], TIMEOUT)
+            .unwrap();
+        assert!(response.result::<bool>().unwrap());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        for url in &[
+            "127.0.0.1:2377",
+            "ftp://127.0.0.1:2377",
+            "http://:2377",
+            "http://host:port",
+            "http://[::1",
+        ] {
+            match RpcClient::new(url.to_string(), RpcAuth::None, None) {
+                Err(Error::InvalidArgs(_)) => {}
+                r => panic!("unexpected result {:?} for {}", r.map(|_| ()), url),
+            }
+        }
+        assert!(RpcClient::new("https://[::1]/wallet/".to_string(), RpcAuth::None, None).is_ok());
+    }
+}
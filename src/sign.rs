@@ -14,7 +14,11 @@ use crate::signer_node::ToShares;
 use crate::signer_node::ToVerifiableSS;
 use crate::util::*;
 use secp256k1::rand::thread_rng;
+use tapyrus::blockdata::block::BlockHeader;
+use tapyrus::consensus::encode::serialize;
 use tapyrus::hash_types::BlockSigHash;
+use tapyrus::util::prime::jacobi;
+use tapyrus::PublicKey;
 
 pub struct Sign;
 
@@ -96,6 +100,39 @@ impl Sign {
         let s_as_str = s_as_int.to_str_radix(16);
         format!("{:0>64}{:0>64}", v_as_str, s_as_str)
     }
+
+    /// Parses the signature which is formatted by `format_signature`. The point R is restored as
+    /// the one whose y coordinate is quadratic residue, as the signers choose.
+    pub fn parse_signature(bytes: &[u8]) -> Result<Signature, Error> {
+        if bytes.len() != 64 {
+            return Err(Error::InvalidSig);
+        }
+        for prefix in &[0x02u8, 0x03u8] {
+            let mut compressed = vec![*prefix];
+            compressed.extend_from_slice(&bytes[..32]);
+            let key = tapyrus::PublicKey::from_slice(&compressed).map_err(|_| Error::InvalidSig)?;
+            let v = GE::from_bytes(&key.key.serialize_uncompressed()[1..])
+                .map_err(|_| Error::InvalidSig)?;
+            let y = v.y_coor().ok_or(Error::InvalidSig)?;
+            if jacobi(&Converter::to_vec(&y)) == 1 {
+                let sigma: FE = ECScalar::from(&BigInt::from(&bytes[32..]));
+                return Ok(Signature { sigma, v });
+            }
+        }
+        Err(Error::InvalidSig)
+    }
+
+    /// Verifies the proof of the block header with the aggregated public key.
+    pub fn verify_proof(
+        header: &BlockHeader,
+        aggregated_public_key: &PublicKey,
+    ) -> Result<(), Error> {
+        let proof = header.proof.as_ref().ok_or(Error::IncompleteBlock)?;
+        let signature = Sign::parse_signature(&serialize(proof))?;
+        let bytes = aggregated_public_key.key.serialize_uncompressed();
+        let y = GE::from_bytes(&bytes[1..]).map_err(|_| Error::InvalidKey)?;
+        signature.verify(&header.signature_hash()[..], &y)
+    }
 }
 
 #[test]
@@ -171,3 +208,24 @@ fn test_format_signature() {
     };
     assert_eq!(Sign::format_signature(&sig_0), "c726149bfb2d4ab64823e0cfd8245645a7950e605ef9222735d821ae570b1e910000000000000000000000000000000000000000000000000000000000000001");
 }
+
+#[test]
+fn test_parse_signature() {
+    let v = loop {
+        let k: FE = ECScalar::new_random();
+        let v = GE::generator() * &k;
+        if jacobi(&Converter::to_vec(&v.y_coor().unwrap())) == 1 {
+            break v;
+        }
+    };
+    let signature = Signature {
+        sigma: ECScalar::new_random(),
+        v,
+    };
+    let bytes = hex::decode(Sign::format_signature(&signature)).unwrap();
+    let parsed = Sign::parse_signature(&bytes).unwrap();
+    assert_eq!(parsed.v, signature.v);
+    assert_eq!(parsed.sigma, signature.sigma);
+
+    assert!(Sign::parse_signature(&bytes[1..]).is_err());
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of block heights which the journal keeps records for.
+pub const JOURNAL_CAPACITY: usize = 100;
+
+/// An event which happened in a round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Unix time(sec) when the event happened.
+    pub time: u64,
+    pub event: String,
+}
+
+/// Events of all rounds for a block height. A block height has several rounds if some rounds
+/// were failed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub block_height: u64,
+    pub entries: Vec<JournalEntry>,
+}
+
+/// In-memory journal of round events. It keeps only the records for the latest
+/// `JOURNAL_CAPACITY` block heights.
+#[derive(Debug)]
+pub struct RoundJournal {
+    records: BTreeMap<u64, Vec<JournalEntry>>,
+    capacity: usize,
+}
+
+impl RoundJournal {
+    pub fn new(capacity: usize) -> Self {
+        RoundJournal {
+            records: BTreeMap::new(),
+            capacity,
+        }
+    }
+
+    pub fn record(&mut self, block_height: u64, event: String) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.records
+            .entry(block_height)
+            .or_insert_with(Vec::new)
+            .push(JournalEntry { time, event });
+
+        while self.records.len() > self.capacity {
+            let oldest = *self.records.keys().next().unwrap();
+            self.records.remove(&oldest);
+        }
+    }
+
+    pub fn get(&self, block_height: u64) -> Option<RoundRecord> {
+        self.records.get(&block_height).map(|entries| RoundRecord {
+            block_height,
+            entries: entries.clone(),
+        })
+    }
+
+    /// Returns the record for the highest block height.
+    pub fn latest(&self) -> Option<RoundRecord> {
+        self.records
+            .keys()
+            .next_back()
+            .and_then(|block_height| self.get(*block_height))
+    }
+}
+
+impl Default for RoundJournal {
+    fn default() -> Self {
+        RoundJournal::new(JOURNAL_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut journal = RoundJournal::new(2);
+        assert_eq!(journal.latest(), None);
+
+        journal.record(10, "Round started.".to_string());
+        journal.record(10, "Round timed out.".to_string());
+        journal.record(11, "Round started.".to_string());
+
+        let record = journal.get(10).unwrap();
+        assert_eq!(record.entries.len(), 2);
+        assert_eq!(record.entries[1].event, "Round timed out.");
+        assert_eq!(journal.latest().unwrap().block_height, 11);
+
+        // The oldest block height is removed when it exceeds the capacity.
+        journal.record(12, "Round started.".to_string());
+        assert_eq!(journal.get(10), None);
+        assert!(journal.get(11).is_some());
+        assert_eq!(journal.latest().unwrap().block_height, 12);
+    }
+}
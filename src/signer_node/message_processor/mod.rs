@@ -9,7 +9,6 @@ pub use process_blockvss::process_blockvss;
 pub use process_candidateblock::process_candidateblock;
 pub use process_completedblock::process_completedblock;
 
-use crate::crypto::multi_party_schnorr::Keys;
 use crate::crypto::multi_party_schnorr::{LocalSig, SharedKeys};
 use crate::crypto::vss::Vss;
 use crate::errors::Error;
@@ -19,7 +18,10 @@ use crate::net::MessageType;
 use crate::net::SignerID;
 use crate::rpc::TapyrusApi;
 use crate::signer_node::SharedSecret;
-use crate::signer_node::{BidirectionalSharedSecretMap, NodeParameters, NodeState};
+use crate::signer_node::{BidirectionalSharedSecretMap, NodeParameters, NodeState, StateSummary};
+use crate::signing_service::BlockVss;
+use curv::elliptic::curves::traits::ECScalar;
+use curv::FE;
 use tapyrus::blockdata::block::Block;
 use tapyrus::hash_types::BlockSigHash;
 
@@ -32,7 +34,7 @@ fn get_valid_block(state: &NodeState, blockhash: BlockSigHash) -> Result<&Block,
             candidate_block, ..
         } => candidate_block,
         _ => {
-            log::error!("Invalid node state: {:?}", state);
+            log::error!("Invalid node state: {}", StateSummary::from(state));
             return Err(Error::InvalidNodeState);
         }
     };
@@ -49,25 +51,31 @@ fn get_valid_block(state: &NodeState, blockhash: BlockSigHash) -> Result<&Block,
     }
 }
 
-/// Create own VSSs and send to each other signers.
+/// Creates own block VSS. If the federation masks block VSSs, the signing service creates it.
 /// Returns
-///     * own random key pair
-///     * a VSS for itself(for positive and negative)
-///     * own commitments
-pub fn create_block_vss<T, C>(
-    block: Block,
+///     * own random key, which is None if the signing service created the block VSS
+///     * own block VSS
+pub fn create_block_vss<T>(
+    block: &Block,
     params: &NodeParameters<T>,
-    conman: &C,
     block_height: u64,
-) -> (Keys, SharedSecret, SharedSecret)
+) -> Result<(Option<FE>, BlockVss), Error>
 where
     T: TapyrusApi,
-    C: ConnectionManager,
 {
-    let sharing_params = params.sharing_params(block_height);
-
-    let self_node_index = params.self_node_index(block_height);
+    let federation = params.get_federation_by_block_height(block_height);
+    if federation.masked_block_vss() {
+        let block_vss = params.signing_service.create_block_vss(federation, block)?;
+        return Ok((None, block_vss));
+    }
+    if !params.signing_service.needs_secret_shares() {
+        return Err(Error::SigningService(format!(
+            "The federation at block height {} doesn't mask the block VSSs.",
+            federation.block_height()
+        )));
+    }
 
+    let sharing_params = params.sharing_params(block_height);
     let (
         key,
         vss_scheme_for_positive,
@@ -75,12 +83,39 @@ where
         vss_scheme_for_negative,
         secret_shares_for_negative,
     ) = Vss::create_block_shares(
-        self_node_index + 1,
+        params.self_node_index(block_height) + 1,
         sharing_params.threshold + 1,
         sharing_params.share_count,
     );
+    Ok((
+        Some(key.u_i),
+        BlockVss {
+            vss_for_positive: vss_scheme_for_positive,
+            vss_for_negative: vss_scheme_for_negative,
+            secret_shares: secret_shares_for_positive
+                .into_iter()
+                .zip(secret_shares_for_negative)
+                .collect(),
+        },
+    ))
+}
 
-    for i in 0..params.pubkey_list(block_height).len() {
+/// Sends the secret shares of own block VSS to each other signer.
+/// Returns the VSS for itself(for positive and negative).
+pub fn send_block_vss<T, C>(
+    block: &Block,
+    block_vss: &BlockVss,
+    params: &NodeParameters<T>,
+    conman: &C,
+    block_height: u64,
+) -> (SharedSecret, SharedSecret)
+where
+    T: TapyrusApi,
+    C: ConnectionManager,
+{
+    let self_node_index = params.self_node_index(block_height);
+
+    for (i, pubkey) in params.pubkey_list(block_height).iter().enumerate() {
         // Skip broadcasting if it is vss for myself. Just return this.
         if i == self_node_index {
             continue;
@@ -89,27 +124,24 @@ where
         conman.send_message(Message {
             message_type: MessageType::Blockvss(
                 block.header.signature_hash(),
-                vss_scheme_for_positive.clone(),
-                secret_shares_for_positive[i],
-                vss_scheme_for_negative.clone(),
-                secret_shares_for_negative[i],
+                block_vss.vss_for_positive.clone(),
+                block_vss.secret_shares[i].0,
+                block_vss.vss_for_negative.clone(),
+                block_vss.secret_shares[i].1,
             ),
             sender_id: params.signer_id,
-            receiver_id: Some(SignerID {
-                pubkey: params.pubkey_list(block_height)[i],
-            }),
+            receiver_id: Some(SignerID { pubkey: *pubkey }),
         });
     }
 
     (
-        key,
         SharedSecret {
-            vss: vss_scheme_for_positive.clone(),
-            secret_share: secret_shares_for_positive[self_node_index],
+            vss: block_vss.vss_for_positive.clone(),
+            secret_share: block_vss.secret_shares[self_node_index].0,
         },
         SharedSecret {
-            vss: vss_scheme_for_negative.clone(),
-            secret_share: secret_shares_for_negative[self_node_index],
+            vss: block_vss.vss_for_negative.clone(),
+            secret_share: block_vss.secret_shares[self_node_index].1,
         },
     )
 }
@@ -131,6 +163,22 @@ where
     let block_height = prev_state.block_height();
     let federation = params.get_federation_by_block_height(block_height);
 
+    if federation.masked_block_vss() {
+        // The share of the block VSSs is kept in the signing service.
+        let (is_positive, y, local_sig) =
+            params
+                .signing_service
+                .sign(federation, block, shared_block_secrets)?;
+        return Ok((
+            is_positive,
+            SharedKeys {
+                y,
+                x_i: ECScalar::zero(),
+            },
+            local_sig,
+        ));
+    }
+
     Vss::create_local_sig_from_shares(
         &federation.node_secret_share(),
         params.self_node_index(block_height) + 1,
@@ -5,7 +5,7 @@ use crate::signer_node::message_processor::{
     broadcast_localsig, generate_local_sig, get_valid_block,
 };
 use crate::signer_node::node_state::builder::{Builder, Master, Member};
-use crate::signer_node::{NodeParameters, NodeState};
+use crate::signer_node::{NodeParameters, NodeState, StateSummary};
 use curv::{FE, GE};
 use std::collections::HashSet;
 use tapyrus::hash_types::BlockSigHash;
@@ -83,7 +83,7 @@ where
             ((is_positive, shared_keys.x_i, shared_keys.y), local_sig)
         }
         Err(e) => {
-            error!("Error: {:?}, state: {:?}", e, prev_state);
+            error!("Error: {:?}, state: {}", e, StateSummary::from(prev_state));
             return prev_state.clone();
         }
     };
@@ -138,7 +138,7 @@ where
         &federation.node_shared_secrets(),
         &block_shared_keys,
         &shared_block_secrets_by_participants,
-        &federation.node_secret_share(),
+        &federation.aggregated_point(),
     ) {
         Ok(sig) => sig,
         Err(e) => {
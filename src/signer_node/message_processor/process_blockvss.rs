@@ -6,7 +6,7 @@ use crate::signer_node::message_processor::{
 };
 use crate::signer_node::node_state::builder::{Builder, Master, Member};
 use crate::signer_node::NodeParameters;
-use crate::signer_node::{BidirectionalSharedSecretMap, NodeState, SharedSecret};
+use crate::signer_node::{BidirectionalSharedSecretMap, NodeState, SharedSecret, StateSummary};
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 use curv::FE;
 use std::collections::HashSet;
@@ -38,7 +38,7 @@ where
     ) {
         Ok(shared_block_secrets) => shared_block_secrets,
         Err(e) => {
-            error!("Error: {:?}, state: {:?}", e, prev_state);
+            error!("Error: {:?}, state: {}", e, StateSummary::from(prev_state));
             return prev_state.clone();
         }
     };
@@ -46,7 +46,7 @@ where
     let candidate_block = match get_valid_block(prev_state, blockhash) {
         Ok(b) => b,
         Err(e) => {
-            error!("Error: {:?}, state: {:?}", e, prev_state);
+            error!("Error: {:?}, state: {}", e, StateSummary::from(prev_state));
             return prev_state.clone();
         }
     };
@@ -82,7 +82,7 @@ where
                         ((is_positive, shared_keys.x_i, shared_keys.y), local_sig)
                     }
                     Err(e) => {
-                        error!("Error: {:?}, state: {:?}", e, prev_state);
+                        error!("Error: {:?}, state: {}", e, StateSummary::from(prev_state));
                         return prev_state.clone();
                     }
                 };
@@ -128,7 +128,7 @@ where
                         ((is_positive, shared_keys.x_i, shared_keys.y), local_sig)
                     }
                     Err(e) => {
-                        error!("Error: {:?}, state: {:?}", e, prev_state);
+                        error!("Error: {:?}, state: {}", e, StateSummary::from(prev_state));
                         return prev_state.clone();
                     }
                 };
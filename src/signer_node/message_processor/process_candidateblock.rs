@@ -1,10 +1,11 @@
 use crate::errors::Error;
 use crate::net::{ConnectionManager, SignerID};
 use crate::rpc::TapyrusApi;
-use crate::signer_node::message_processor::create_block_vss;
+use crate::signer_node::message_processor::{create_block_vss, send_block_vss};
 use crate::signer_node::node_state::builder::{Builder, Member};
 use crate::signer_node::utils::sender_index;
 use crate::signer_node::{NodeParameters, NodeState};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tapyrus::blockdata::block::{Block, XField};
 
 pub fn process_candidateblock<T, C>(
@@ -36,6 +37,34 @@ where
         return prev_state.clone();
     }
 
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    match params
+        .block_policy()
+        .check(block, block_height, params.rpc.as_ref(), now)
+    {
+        Ok(()) => {}
+        Err(Error::PolicyRejection(reason)) => {
+            log::warn!(
+                "Candidate block is rejected by the policy. sender: {}, reason: {}",
+                sender_id,
+                reason
+            );
+            return prev_state.clone();
+        }
+        Err(e) => {
+            // The block can't be checked, but it doesn't mean that the block violates the policy.
+            log::error!(
+                "Failed to check the candidate block with the policy. sender: {}, error: {:?}",
+                sender_id,
+                e
+            );
+            return prev_state.clone();
+        }
+    }
+
     log::info!(
         "candidateblock received. block hash for signing: {:?}",
         block.header.signature_hash()
@@ -50,12 +79,19 @@ where
         return prev_state.clone();
     }
 
-    let (key, shared_secret_for_positive, shared_secret_for_negative) =
-        create_block_vss(block.clone(), params, conman, block_height);
+    let (block_key, block_vss) = match create_block_vss(block, params, block_height) {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!("Failed to create block VSS. reason={:?}", e);
+            return prev_state.clone();
+        }
+    };
+    let (shared_secret_for_positive, shared_secret_for_negative) =
+        send_block_vss(block, &block_vss, params, conman, block_height);
 
     Member::default()
         .block_height(block_height)
-        .block_key(Some(key.u_i))
+        .block_key(block_key)
         .candidate_block(Some(block.clone()))
         .master_index(sender_index(sender_id, &params.pubkey_list(block_height)))
         .insert_shared_block_secrets(
@@ -78,7 +114,33 @@ where
         XField::Unknown(_, _) => return Err(Error::UnsupportedXField),
         _ => {}
     }
-    verify_aggregated_public_key(block, block_height, params)
+    verify_aggregated_public_key(block, block_height, params)?;
+    verify_max_block_size(block, block_height, params)
+}
+
+/// Checks the max block size xfield is set only to the block one before the scheduled change.
+fn verify_max_block_size<T>(
+    block: &Block,
+    block_height: u64,
+    params: &NodeParameters<T>,
+) -> Result<(), Error>
+where
+    T: TapyrusApi,
+{
+    let next_block_height = block_height + 1;
+    let expected = params
+        .parameter_schedule()
+        .get(next_block_height)
+        .map(|change| change.max_block_size);
+    let actual = match block.header.xfield {
+        XField::MaxBlockSize(size) => Some(size),
+        _ => None,
+    };
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::InvalidMaxBlockSize)
+    }
 }
 
 fn verify_aggregated_public_key<T>(
@@ -111,8 +173,10 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::block_policy::BlockPolicy;
     use crate::federation::{Federation, Federations};
     use crate::net::{Message, MessageType, SignerID};
+    use crate::parameter_schedule::{ParameterChange, ParameterSchedule};
     use crate::signer_node::node_state::builder::{Builder, Master, Member};
     use crate::signer_node::{master_index, NodeState};
     use crate::tests::helper::blocks::get_block;
@@ -194,6 +258,52 @@ mod tests {
         params.rpc.assert();
     }
 
+    #[test]
+    fn test_as_member_with_policy_rejection() {
+        let sender_id = sender_id();
+        let candidate_block = get_block(0);
+        let prev_state = Member::for_test().build();
+        let conman = TestConnectionManager::new();
+        let mut params = NodeParametersBuilder::new().rpc(MockRpc::new()).build();
+        params.set_block_policy(BlockPolicy {
+            max_tx_count: Some(0),
+            ..Default::default()
+        });
+
+        // It should not call testproposedblock RPC.
+        let next_state =
+            process_candidateblock(&sender_id, &candidate_block, &prev_state, &conman, &params);
+
+        assert_eq!(prev_state, next_state);
+        assert_eq!(conman.sent.borrow().len(), 0);
+
+        params.rpc.assert();
+    }
+
+    #[test]
+    fn test_as_member_with_policy_check_failure() {
+        let sender_id = sender_id();
+        let candidate_block = get_block(0);
+        let prev_state = Member::for_test().build();
+        let conman = TestConnectionManager::new();
+        let mut rpc = MockRpc::new();
+        rpc.should_call_getblockheader(Err(Error::JsonRpc(jsonrpc::error::Error::NonceMismatch)));
+        let mut params = NodeParametersBuilder::new().rpc(rpc).build();
+        params.set_block_policy(BlockPolicy {
+            min_block_interval: Some(30),
+            ..Default::default()
+        });
+
+        // It should not call testproposedblock RPC.
+        let next_state =
+            process_candidateblock(&sender_id, &candidate_block, &prev_state, &conman, &params);
+
+        assert_eq!(prev_state, next_state);
+        assert_eq!(conman.sent.borrow().len(), 0);
+
+        params.rpc.assert();
+    }
+
     #[test]
     fn test_as_member_with_invalid_block() {
         let sender_id = sender_id();
@@ -356,4 +466,28 @@ mod tests {
         let block = test_block_without_public_key();
         assert!(verify_aggregated_public_key(&block, 100, &params).is_ok());
     }
+
+    #[test]
+    fn test_verify_max_block_size() {
+        let params = NodeParametersBuilder::new()
+            .public_key(TEST_KEYS.pubkeys()[2])
+            .rpc(MockRpc::new())
+            .parameter_schedule(ParameterSchedule::new(vec![ParameterChange {
+                block_height: 100,
+                max_block_size: 2_000_000,
+            }]))
+            .build();
+
+        let block = test_block_without_public_key();
+        assert!(verify_max_block_size(&block, 98, &params).is_ok());
+        assert!(verify_max_block_size(&block, 99, &params).is_err());
+
+        let mut block = test_block_without_public_key();
+        block.header.xfield = XField::MaxBlockSize(2_000_000);
+        assert!(verify_max_block_size(&block, 99, &params).is_ok());
+        assert!(verify_max_block_size(&block, 100, &params).is_err());
+
+        block.header.xfield = XField::MaxBlockSize(1_000_000);
+        assert!(verify_max_block_size(&block, 99, &params).is_err());
+    }
 }
@@ -2,46 +2,73 @@
 // Distributed under the MIT software license, see the accompanying
 // file COPYING or http://www.opensource.org/licenses/mit-license.php.
 
+pub mod journal;
 mod message_processor;
 pub mod node_parameters;
 pub mod node_state;
+pub mod peers;
+pub mod status;
 pub mod utils;
 
+pub use crate::signer_node::journal::RoundJournal;
 pub use crate::signer_node::node_parameters::NodeParameters;
 pub use crate::signer_node::node_state::NodeState;
+pub use crate::signer_node::peers::PeerTable;
+pub use crate::signer_node::status::{NodeStatus, StateSummary};
 
+use crate::control::{ControlCommand, ControlRequest, ControlResponse};
 use crate::errors::Error;
-use crate::net::{ConnectionManager, ConnectionManagerError, Message, MessageType, SignerID};
+use crate::federation::{Federation, Federations};
+use crate::net::{
+    ConnectionManager, ConnectionManagerError, Heartbeat, Message, MessageType, SignerID,
+};
+use crate::parameter_schedule::ParameterSchedule;
 use crate::rpc::{GetBlockchainInfoResult, TapyrusApi};
-use crate::signer_node::message_processor::create_block_vss;
 use crate::signer_node::message_processor::process_blockparticipants;
 use crate::signer_node::message_processor::process_blocksig;
 use crate::signer_node::message_processor::process_blockvss;
 use crate::signer_node::message_processor::process_candidateblock;
 use crate::signer_node::message_processor::process_completedblock;
+use crate::signer_node::message_processor::{create_block_vss, send_block_vss};
 use crate::signer_node::node_state::builder::{Builder, Master, Member};
+use crate::signer_node::status::{ConnectionsStatus, FederationStatus, PeerInfo, TimersStatus};
 use crate::timer::RoundTimeOutObserver;
 
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 use curv::FE;
 use redis::ControlFlow;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tapyrus::blockdata::block::Block;
 use tapyrus::blockdata::block::XField;
+use tapyrus::hash_types::BlockHash;
+use tapyrus::hashes::hex::FromHex;
 
 /// Round interval.
 pub static ROUND_INTERVAL_DEFAULT_SECS: u64 = 60;
 /// Round time limit delta. Round timeout timer should be little longer than `ROUND_INTERVAL_DEFAULT_SECS`.
 pub static ROUND_LIMIT_DEFAULT_SECS: u64 = 15;
+/// Heartbeat interval.
+pub static HEARTBEAT_INTERVAL_DEFAULT_SECS: u64 = 10;
+/// A peer is regarded as offline if the node doesn't receive any heartbeat from the peer while
+/// this number of heartbeat intervals.
+pub static HEARTBEAT_TIMEOUT_INTERVALS: u64 = 3;
+/// Interval of checking the chain tip during a round.
+pub static TIP_CHECK_INTERVAL_DEFAULT_SECS: u64 = 5;
+/// Interval of checking whether the master can produce a block while it waits for transactions.
+pub static PRODUCTION_CHECK_INTERVAL_SECS: u64 = 1;
 
 pub struct SignerNode<T: TapyrusApi, C: ConnectionManager> {
     connection_manager: C,
     params: NodeParameters<T>,
     current_state: NodeState,
     stop_signal: Option<Receiver<u32>>,
+    control_requests: Option<Receiver<ControlRequest>>,
+    /// New tips notified by Tapyrus Core. The node starts the next round or abandons the round on
+    /// a new tip without waiting for the timers, which are kept as fallbacks.
+    block_notifications: Option<Receiver<BlockHash>>,
     /// ## Round Limit Timer
     /// If the round duration is over, notify it and go through next round.
     /// The round limit consists from round_interval and round_limit.
@@ -57,6 +84,34 @@ pub struct SignerNode<T: TapyrusApi, C: ConnectionManager> {
     /// If the timer was up, the node starts round communication with getting a block and sending
     /// candidateblock message.
     round_interval_timer: RoundTimeOutObserver,
+
+    /// ## Heartbeat Timer
+    /// The node broadcasts heartbeat message every time the timer is up.
+    heartbeat_timer: RoundTimeOutObserver,
+
+    /// ## Tip Check Timer
+    /// The node checks the best block of Tapyrus Core every time the timer is up, and abandons
+    /// the round if the chain tip has changed since the round started.
+    tip_check_timer: RoundTimeOutObserver,
+
+    /// ## Production Timer
+    /// The timer is started when the master waits before proposing a block because of the block
+    /// policy. The master checks the policy again every time the timer is up until the round ends.
+    production_timer: RoundTimeOutObserver,
+    /// The best block hash when the current round started.
+    tip: Option<BlockHash>,
+    /// The latest heartbeats from other signers.
+    peers: PeerTable,
+    /// Events in recent rounds.
+    journal: RoundJournal,
+    /// If true, the node doesn't propose or sign blocks. But it keeps following rounds to stay in
+    /// sync with other signers.
+    paused: bool,
+    /// Whether the last RPC call to get the chain tip succeeded. The status reports it instead of
+    /// calling RPC, so that a status request doesn't block the rounds.
+    rpc_connected: bool,
+    /// Whether the connection to Redis was alive when the node checked it last.
+    redis_connected: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -119,13 +174,29 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
     {
         let timer_limit = params.round_duration + params.round_limit;
         let round_interval = params.round_duration;
+        let heartbeat_interval = params.heartbeat_interval;
+        let tip_check_interval = params.tip_check_interval;
         SignerNode {
             connection_manager,
             params,
             current_state: NodeState::Joining,
             stop_signal: None,
+            control_requests: None,
+            block_notifications: None,
             round_limit_timer: RoundTimeOutObserver::new("round_limit_timer", timer_limit),
             round_interval_timer: RoundTimeOutObserver::new("round_interval_timer", round_interval),
+            heartbeat_timer: RoundTimeOutObserver::new("heartbeat_timer", heartbeat_interval),
+            tip_check_timer: RoundTimeOutObserver::new("tip_check_timer", tip_check_interval),
+            production_timer: RoundTimeOutObserver::new(
+                "production_timer",
+                PRODUCTION_CHECK_INTERVAL_SECS,
+            ),
+            tip: None,
+            peers: PeerTable::new(),
+            journal: RoundJournal::default(),
+            paused: false,
+            rpc_connected: false,
+            redis_connected: false,
         }
     }
 
@@ -133,6 +204,14 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
         self.stop_signal = Some(receiver);
     }
 
+    pub fn control_handler(&mut self, receiver: Receiver<ControlRequest>) {
+        self.control_requests = Some(receiver);
+    }
+
+    pub fn block_notification_handler(&mut self, receiver: Receiver<BlockHash>) {
+        self.block_notifications = Some(receiver);
+    }
+
     pub fn start(&mut self) {
         if !self.params.skip_waiting_ibd {
             self.wait_for_ibd_finish(std::time::Duration::from_secs(10));
@@ -144,9 +223,11 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             match self.connection_manager.test_connection() {
                 Ok(_) => {
                     log::debug!("Connection is established.");
+                    self.redis_connected = true;
                 }
                 Err(e) => {
                     log::debug!("Can't establish redis connection: {:?}", e);
+                    self.redis_connected = false;
                     std::thread::sleep(Duration::from_millis(5000));
                     continue;
                 }
@@ -167,6 +248,8 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             // Start First Round
             log::info!("Start block creation rounds.");
             self.start_next_round();
+            self.send_heartbeat();
+            self.tip_check_timer.restart().unwrap();
 
             loop {
                 match self.handle_signal() {
@@ -180,8 +263,18 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
                 // then change that state in main thread side.
                 self.handle_message(&receiver);
 
+                self.handle_block_notification();
+
                 self.handle_timer();
 
+                self.handle_heartbeat_timer();
+
+                self.handle_tip_check_timer();
+
+                self.handle_production_timer();
+
+                self.handle_control_request();
+
                 match self.handle_connection_error() {
                     Some(_) => break,
                     None => {}
@@ -205,6 +298,9 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
                 Ok(_) => {
                     log::warn!("Stop by Terminate Signal.");
                     self.round_limit_timer.stop();
+                    self.heartbeat_timer.stop();
+                    self.tip_check_timer.stop();
+                    self.production_timer.stop();
                     Some(())
                 }
                 Err(std::sync::mpsc::TryRecvError::Empty) => {
@@ -225,6 +321,22 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
     fn handle_message(&mut self, receiver: &Receiver<Message>) {
         // Receiving message.
         match receiver.try_recv() {
+            Ok(Message {
+                message_type: MessageType::Heartbeat(heartbeat),
+                sender_id,
+                ..
+            }) => {
+                log::trace!("Got heartbeat from {:?}. {:?}", sender_id, heartbeat);
+                self.peers.update(sender_id, heartbeat);
+            }
+            Ok(Message {
+                message_type: MessageType::Dkg(_),
+                sender_id,
+                ..
+            }) => {
+                // Key generation ceremonies run in `tapyrus-setup dkg`, not in the node.
+                log::trace!("Ignore Dkg message from {:?}.", sender_id);
+            }
             Ok(Message {
                 message_type,
                 sender_id,
@@ -237,14 +349,34 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
                     message_type
                 );
 
+                if self.paused {
+                    if let MessageType::Completedblock(_) = message_type {
+                        // Follow the completed block to stay in sync with other signers.
+                    } else {
+                        log::debug!(
+                            "Ignore {} message because the node is paused.",
+                            message_type
+                        );
+                        return;
+                    }
+                }
+
+                self.record_journal(format!(
+                    "Received {} message from {}.",
+                    message_type, sender_id
+                ));
                 let next = self.process_round_message(&sender_id, message_type);
                 self.current_state = next;
 
                 if let NodeState::RoundComplete { .. } = &self.current_state {
+                    self.record_journal("Round completed.".to_string());
                     self.start_next_round()
                 }
 
-                log::debug!("Current state updated as {:?}", self.current_state);
+                log::debug!(
+                    "Current state updated as {}",
+                    StateSummary::from(&self.current_state)
+                );
             }
             Err(TryRecvError::Empty) => {
                 // No new messages. Do nothing.
@@ -262,8 +394,12 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
         match self.round_limit_timer.receiver.try_recv() {
             Ok(_) => {
                 // Round duration is timeout. Starting next round.
+                self.record_journal("Round timed out.".to_string());
                 self.start_next_round();
-                log::debug!("Current state updated as {:?}", self.current_state);
+                log::debug!(
+                    "Current state updated as {}",
+                    StateSummary::from(&self.current_state)
+                );
             }
             Err(TryRecvError::Empty) => {
                 // Still waiting round duration interval. Do nothing.
@@ -280,8 +416,22 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             Ok(_) => {
                 // Round interval is timeout.
                 if let NodeState::Master { block_height, .. } = self.current_state {
-                    self.current_state = self.start_round_communication(block_height);
-                    log::debug!("Current state updated as {:?}", self.current_state);
+                    if self.paused {
+                        log::info!("Skip proposing a block because the node is paused.");
+                        self.record_journal(
+                            "Skip proposing a block because the node is paused.".to_string(),
+                        );
+                        return;
+                    }
+                    if !self.has_enough_alive_signers(block_height) {
+                        self.record_journal(
+                            "Skip proposing a block because of lack of alive signers.".to_string(),
+                        );
+                        // Behave as master without block. The round would be finished by the
+                        // round limit timer as same as other nodes.
+                        return;
+                    }
+                    self.propose_block(block_height, true);
                 }
             }
             Err(TryRecvError::Empty) => {
@@ -291,12 +441,509 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
         }
     }
 
+    /// Check if heartbeat timer elapsed
+    /// If elapsed, the node broadcasts heartbeat message.
+    fn handle_heartbeat_timer(&mut self) {
+        match self.heartbeat_timer.receiver.try_recv() {
+            Ok(_) => self.send_heartbeat(),
+            Err(TryRecvError::Empty) => {
+                // Still waiting heartbeat interval. Do nothing.
+            }
+            Err(e) => log::warn!("Heartbeat timer generates an error: {:?}", e),
+        }
+    }
+
+    /// Check if production timer elapsed
+    /// If elapsed, the master which is waiting for proposing a block checks the block policy again.
+    fn handle_production_timer(&mut self) {
+        match self.production_timer.receiver.try_recv() {
+            Ok(_) => {
+                if let NodeState::Master {
+                    block_height,
+                    candidate_block: None,
+                    ..
+                } = &self.current_state
+                {
+                    if !self.paused {
+                        let block_height = *block_height;
+                        self.propose_block(block_height, false);
+                    }
+                }
+            }
+            Err(TryRecvError::Empty) => {
+                // Still waiting production check interval. Do nothing.
+            }
+            Err(e) => log::warn!("Production timer generates an error: {:?}", e),
+        }
+    }
+
+    /// Proposes a block if the block policy allows the master to produce it now. Otherwise the
+    /// production timer is started to check it again. The reason of waiting is recorded in the
+    /// journal if `record` is true.
+    fn propose_block(&mut self, block_height: u64, record: bool) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        match self.params.block_policy().production_wait(
+            block_height,
+            self.params.rpc.as_ref(),
+            now,
+        ) {
+            Ok(Some(reason)) => {
+                self.wait_for_production(block_height, &reason, record);
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                // Propose anyway. The block is checked with the policy again.
+                log::warn!("Failed to check the block production. reason={:?}", e);
+            }
+        }
+
+        self.current_state = self.start_round_communication(block_height, record);
+        log::debug!(
+            "Current state updated as {}",
+            StateSummary::from(&self.current_state)
+        );
+    }
+
+    /// Starts the production timer to propose a block later. The reason is recorded in the journal
+    /// if `record` is true.
+    fn wait_for_production(&mut self, block_height: u64, reason: &str, record: bool) {
+        log::debug!("Wait for proposing a block. reason={}", reason);
+        if record {
+            self.journal.record(
+                block_height,
+                format!("Wait for proposing a block. {}", reason),
+            );
+        }
+        self.production_timer.restart().unwrap();
+    }
+
+    /// Check if tip check timer elapsed
+    /// If elapsed, the node checks the chain tip.
+    fn handle_tip_check_timer(&mut self) {
+        match self.tip_check_timer.receiver.try_recv() {
+            Ok(_) => {
+                self.check_tip();
+                self.tip_check_timer.restart().unwrap();
+            }
+            Err(TryRecvError::Empty) => {
+                // Still waiting tip check interval. Do nothing.
+            }
+            Err(e) => log::warn!("Tip check timer generates an error: {:?}", e),
+        }
+    }
+
+    /// Check if Tapyrus Core notified new tips.
+    /// A notification is only a signal, and the node reads the tip from Tapyrus Core again.
+    fn handle_block_notification(&mut self) {
+        let latest = match &self.block_notifications {
+            Some(receiver) => receiver.try_iter().last(),
+            None => return,
+        };
+        if let Some(hash) = latest {
+            log::debug!("Got new tip notification {}", hash);
+            self.follow_tip();
+        }
+    }
+
+    /// Checks the best block of Tapyrus Core, as the fallback of the block notification.
+    fn check_tip(&mut self) {
+        match self.current_state {
+            NodeState::Master { .. } | NodeState::Member { .. } => self.follow_tip(),
+            _ => {}
+        }
+    }
+
+    /// Reads the best block of Tapyrus Core and follows it. A round which has succeeded is not
+    /// abandoned, and the block of the current round is not treated as a new tip, because the
+    /// node moves to the next round when the round completes.
+    fn follow_tip(&mut self) {
+        match self.current_state {
+            NodeState::Master {
+                round_is_done: true,
+                ..
+            } => return,
+            NodeState::Master { .. } | NodeState::Member { .. } | NodeState::Idling { .. } => {}
+            NodeState::Joining | NodeState::RoundComplete { .. } => return,
+        }
+
+        let info = self.params.rpc.getblockchaininfo();
+        self.rpc_connected = info.is_ok();
+        match info {
+            Ok(GetBlockchainInfoResult {
+                blocks,
+                bestblockhash,
+                ..
+            }) => {
+                match self.current_state {
+                    NodeState::Master { block_height, .. }
+                    | NodeState::Member { block_height, .. }
+                        if blocks == block_height =>
+                    {
+                        return
+                    }
+                    _ => {}
+                }
+                if let Ok(best) = BlockHash::from_hex(&bestblockhash) {
+                    self.on_new_tip(best);
+                }
+            }
+            Err(e) => log::warn!("Failed to get the chain tip. reason={:?}", e),
+        }
+    }
+
+    /// Follows the best block of Tapyrus Core if it has changed since the round started. The
+    /// current round is abandoned because its candidate block is stale, and is restarted at the
+    /// new tip with the same master. An idling node starts the next round right away.
+    fn on_new_tip(&mut self, best: BlockHash) {
+        if Some(best) == self.tip {
+            return;
+        }
+
+        match self.current_state {
+            NodeState::Master { .. } | NodeState::Member { .. } => {
+                log::info!(
+                    "The chain tip changed from {:?} to {}. Abandon the round.",
+                    self.tip,
+                    best
+                );
+                self.record_journal("Round abandoned because the chain tip changed.".to_string());
+                self.start_round(true);
+            }
+            NodeState::Idling { .. } => {
+                log::info!("The chain tip changed to {}. Start next round.", best);
+                self.start_next_round();
+            }
+            NodeState::Joining | NodeState::RoundComplete { .. } => return,
+        }
+        log::debug!(
+            "Current state updated as {}",
+            StateSummary::from(&self.current_state)
+        );
+    }
+
+    /// Broadcast heartbeat message and restart the heartbeat timer.
+    fn send_heartbeat(&mut self) {
+        let tip_height = match self.current_state {
+            NodeState::Joining => 0,
+            _ => self.current_state.block_height().saturating_sub(1),
+        };
+        self.connection_manager.broadcast_message(Message {
+            message_type: MessageType::Heartbeat(
+                Heartbeat::new(tip_height, self.current_state.kind())
+                    .with_federations(self.params.federations().fingerprints()),
+            ),
+            sender_id: self.params.signer_id,
+            receiver_id: None,
+        });
+        self.heartbeat_timer.restart().unwrap();
+    }
+
+    /// Checks all alive peers in the current federation have the same federation as the node if the
+    /// next block starts a new federation. Peers are alive if they sent heartbeats within the same
+    /// timeout as `has_enough_alive_signers`. An alive peer which doesn't send fingerprints, like a
+    /// node of an older version, is regarded as disagreeing, because the node can't confirm that
+    /// the peer accepts the new federation.
+    fn check_federation_consensus(&self, block_height: u64) -> Result<(), Error> {
+        let next_block_height = block_height + 1;
+        let next = self
+            .params
+            .get_federation_by_block_height(next_block_height);
+        if next.block_height() != next_block_height {
+            return Ok(());
+        }
+
+        let fingerprint = next.fingerprint();
+        let signers = self
+            .params
+            .get_federation_by_block_height(block_height)
+            .signers();
+        let timeout =
+            Duration::from_secs(self.params.heartbeat_interval * HEARTBEAT_TIMEOUT_INTERVALS);
+        for (signer_id, status) in self.peers.iter() {
+            if !signers.contains(signer_id) || !self.peers.is_alive(signer_id, timeout) {
+                continue;
+            }
+            let diff = if status.heartbeat.federations.is_empty() {
+                Some("the peer doesn't send fingerprints of federations".to_string())
+            } else {
+                match status
+                    .heartbeat
+                    .federations
+                    .iter()
+                    .find(|f| f.block_height == next_block_height)
+                {
+                    Some(other) => fingerprint.diff(other),
+                    None => Some("the peer doesn't have the federation".to_string()),
+                }
+            };
+            if let Some(diff) = diff {
+                return Err(Error::FederationMismatch(format!(
+                    "The federation at {} height is different from the one of {}: {}",
+                    next_block_height, signer_id, diff
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks through Tapyrus Core that the aggregated public keys of the federations until the
+    /// block height are in the active chain. A federation is used only after the block one before
+    /// its block height has the key in the xfield, so the node falls back to the previous
+    /// federation if the block was not mined or was reorganized away.
+    fn confirm_federations(&mut self, block_height: u64) {
+        let federations: Vec<&Federation> = self
+            .params
+            .federations()
+            .iter()
+            .skip(1)
+            .filter(|f| f.block_height() <= block_height)
+            .collect();
+
+        let mut inactive = HashSet::new();
+        for federation in federations.into_iter().rev() {
+            let expected = XField::AggregatePublicKey(federation.aggregated_public_key());
+            match self
+                .params
+                .rpc
+                .getblockheader(federation.block_height() - 1)
+            {
+                Ok(header) if header.xfield == expected => break,
+                Ok(_) => {
+                    inactive.insert(federation.block_height());
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Failed to confirm the federation at {} height. reason={:?}",
+                        federation.block_height(),
+                        e
+                    );
+                    return;
+                }
+            }
+        }
+
+        if &inactive != self.params.inactive_federations() {
+            let mut heights: Vec<&u64> = inactive.iter().collect();
+            heights.sort();
+            let event = format!(
+                "The federations at {:?} height are not activated in the active chain. Use the federation at {} height.",
+                heights,
+                self.params
+                    .federations()
+                    .get_active_by_block_height(block_height, &inactive)
+                    .block_height()
+            );
+            log::warn!("{}", event);
+            self.journal.record(block_height, event);
+            self.params.set_inactive_federations(inactive);
+        }
+    }
+
+    /// Returns true if the number of alive signers in the federation, including the node itself,
+    /// meets the threshold.
+    fn has_enough_alive_signers(&self, block_height: u64) -> bool {
+        let federation = self.params.get_federation_by_block_height(block_height);
+        let others: Vec<SignerID> = federation
+            .signers()
+            .into_iter()
+            .filter(|signer_id| *signer_id != self.params.signer_id)
+            .collect();
+        let timeout =
+            Duration::from_secs(self.params.heartbeat_interval * HEARTBEAT_TIMEOUT_INTERVALS);
+        let alive = self.peers.count_alive(&others, timeout) + 1;
+        let threshold = self.params.threshold(block_height) as usize;
+        if alive < threshold {
+            log::warn!(
+                "Skip the round because of lack of alive signers. block_height: {}, alive signers: {}, threshold: {}",
+                block_height,
+                alive,
+                threshold
+            );
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Answer requests from the control server.
+    fn handle_control_request(&mut self) {
+        let requests: Vec<ControlRequest> = match &self.control_requests {
+            Some(receiver) => receiver.try_iter().collect(),
+            None => return,
+        };
+
+        for ControlRequest { command, responder } in requests {
+            let response = self.process_control_command(command);
+            if let Err(e) = responder.send(response) {
+                log::warn!("Can't respond to control request: {:?}", e);
+            }
+        }
+    }
+
+    fn process_control_command(&mut self, command: ControlCommand) -> ControlResponse {
+        match command {
+            ControlCommand::Status => to_control_response(&self.status()),
+            ControlCommand::Federations => to_control_response(&self.federations_status()),
+            ControlCommand::UpdateFederations(toml) => match self.update_federations(&toml) {
+                Ok(()) => to_control_response(&self.federations_status()),
+                Err(e) => ControlResponse::error(400, &e.to_string()),
+            },
+            ControlCommand::Peers => to_control_response(&self.peers_status()),
+            ControlCommand::Pause => self.set_paused(true),
+            ControlCommand::Resume => self.set_paused(false),
+            ControlCommand::BlockNotify(hash) => match BlockHash::from_hex(hash.trim()) {
+                Ok(hash) => {
+                    log::debug!("Got new tip notification {}", hash);
+                    self.follow_tip();
+                    to_control_response(&serde_json::json!({
+                        "blockhash": hash.to_string(),
+                        "tip": self.tip.map(|tip| tip.to_string()),
+                    }))
+                }
+                Err(_) => ControlResponse::error(400, "The block hash is invalid."),
+            },
+            ControlCommand::Journal(block_height) => {
+                let record = match block_height {
+                    Some(block_height) => self.journal.get(block_height),
+                    None => self.journal.latest(),
+                };
+                match record {
+                    Some(record) => to_control_response(&record),
+                    None => ControlResponse::error(404, "No journal for the block height."),
+                }
+            }
+        }
+    }
+
+    fn set_paused(&mut self, paused: bool) -> ControlResponse {
+        self.paused = paused;
+        let event = if paused {
+            "The node is paused by the operator."
+        } else {
+            "The node is resumed by the operator."
+        };
+        log::info!("{}", event);
+        self.record_journal(event.to_string());
+        to_control_response(&serde_json::json!({ "paused": self.paused }))
+    }
+
+    fn federations_status(&self) -> Vec<FederationStatus> {
+        self.params
+            .federations()
+            .iter()
+            .map(FederationStatus::from)
+            .collect()
+    }
+
+    fn peers_status(&self) -> Vec<PeerInfo> {
+        let timeout =
+            Duration::from_secs(self.params.heartbeat_interval * HEARTBEAT_TIMEOUT_INTERVALS);
+        self.peers
+            .iter()
+            .map(|(signer_id, status)| PeerInfo {
+                signer_id: signer_id.to_string(),
+                version: status.heartbeat.version.clone(),
+                tip_height: status.heartbeat.tip_height,
+                state: status.heartbeat.state.clone(),
+                last_seen: status.last_seen.elapsed().as_secs(),
+                alive: self.peers.is_alive(signer_id, timeout),
+            })
+            .collect()
+    }
+
+    /// Replace federations with new one. The federation of the current round must not be changed,
+    /// so that the node can't break the round in progress.
+    fn update_federations(&mut self, toml: &str) -> Result<(), Error> {
+        if let NodeState::Joining = self.current_state {
+            return Err(Error::InvalidNodeState);
+        }
+
+        let pubkey = &self.params.signer_id.pubkey;
+        let federations = if self.params.signing_service.needs_secret_shares() {
+            Federations::from_pubkey_and_toml(pubkey, toml)?
+        } else {
+            Federations::from_pubkey_and_toml_without_secret_shares(pubkey, toml)?
+        };
+        let block_height = self.current_state.block_height();
+        if federations.get_active_by_block_height(block_height, self.params.inactive_federations())
+            != self.params.get_federation_by_block_height(block_height)
+        {
+            return Err(Error::InvalidFederation(
+                Some(block_height),
+                "The federation for the current block height can not be changed.",
+            ));
+        }
+
+        let parameter_schedule = ParameterSchedule::from_toml(toml, &federations)?;
+
+        log::info!("Federations are updated by the operator.");
+        self.record_journal("Federations are updated by the operator.".to_string());
+        self.params.set_federations(federations);
+        self.params.set_parameter_schedule(parameter_schedule);
+        Ok(())
+    }
+
+    /// Record the event in the journal for the current block height.
+    fn record_journal(&mut self, event: String) {
+        if let NodeState::Joining = self.current_state {
+            return;
+        }
+        let block_height = self.current_state.block_height();
+        self.journal.record(block_height, event);
+    }
+
+    /// Returns the status of the node. It doesn't include any secrets.
+    pub fn status(&self) -> NodeStatus {
+        let (master_index, active_federation, upcoming_federations) = match self.current_state {
+            NodeState::Joining => (None, None, vec![]),
+            _ => {
+                let block_height = self.current_state.block_height();
+                let federations = self.params.federations();
+                (
+                    master_index(&self.current_state, &self.params),
+                    Some(FederationStatus::from(
+                        self.params.get_federation_by_block_height(block_height),
+                    )),
+                    federations
+                        .upcoming(block_height)
+                        .into_iter()
+                        .map(FederationStatus::from)
+                        .collect(),
+                )
+            }
+        };
+
+        NodeStatus {
+            signer_id: self.params.signer_id.to_string(),
+            state: StateSummary::from(&self.current_state),
+            master_index,
+            paused: self.paused,
+            active_federation,
+            upcoming_federations,
+            timers: TimersStatus {
+                round_limit: self.round_limit_timer.is_started(),
+                round_interval: self.round_interval_timer.is_started(),
+                heartbeat: self.heartbeat_timer.is_started(),
+            },
+            connections: ConnectionsStatus {
+                rpc: self.rpc_connected,
+                rpc_endpoint: self.params.rpc.endpoint(),
+                redis: self.redis_connected,
+            },
+        }
+    }
+
     /// Check connection to redis server.
     fn handle_connection_error(&mut self) -> Option<ConnectionManagerError<C::ERROR>> {
         // Checking network connection error
         match self.connection_manager.take_error() {
             Ok(e) => {
                 log::error!("Connection Manager Error {:?}", e);
+                self.redis_connected = false;
                 Some(e)
             }
             Err(TryRecvError::Empty) => {
@@ -310,31 +957,31 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
         }
     }
     /// Signer Node waits for connected Tapyrus Core Node complete IBD(Initial Block Download).
-    fn wait_for_ibd_finish(&self, interval: Duration) {
+    fn wait_for_ibd_finish(&mut self, interval: Duration) {
         log::info!("Waiting finish Initial Block Download ...");
         log::info!("If you start right away, you can set `--skip-waiting-ibd` option. ");
 
         loop {
-            match self
-                .params
-                .rpc
-                .getblockchaininfo()
-                .expect("RPC connection failed")
-            {
-                GetBlockchainInfoResult {
+            let info = self.params.rpc.getblockchaininfo();
+            self.rpc_connected = info.is_ok();
+            match info {
+                Ok(GetBlockchainInfoResult {
                     initialblockdownload: false,
                     ..
-                } => {
+                }) => {
                     break;
                 }
-                GetBlockchainInfoResult {
+                Ok(GetBlockchainInfoResult {
                     initialblockdownload: true,
                     blocks: height,
                     bestblockhash: hash,
                     ..
-                } => {
+                }) => {
                     log::info!("Waiting for finish Initial Block Download. Current block height: {}, current best hash: {}", height, hash);
                 }
+                Err(e) => {
+                    log::warn!("RPC connection failed. Retry later. reason={:?}", e);
+                }
             }
             std::thread::sleep(interval);
         }
@@ -346,7 +993,9 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
     }
 
     /// A master node of the round starts a round communication with sending candidateblock message.
-    pub fn start_round_communication(&mut self, block_height: u64) -> NodeState {
+    /// If the block is too early for the block intervals of the policy, the master waits for
+    /// proposing a block with the production timer, and the reason is recorded if `record` is true.
+    pub fn start_round_communication(&mut self, block_height: u64, record: bool) -> NodeState {
         let block = match self.params.rpc.getnewblock(&self.params.address) {
             Ok(block) => block,
             Err(e) => {
@@ -362,19 +1011,80 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             return Master::default().block_height(block_height).build();
         }
 
-        let block = self.add_aggregated_public_key_if_needed(block_height, block);
+        // The block time is checked with the same rules as the members, so that they don't reject
+        // the block even if the local clock differs from the clock of Tapyrus Core.
+        match self.params.block_policy().interval_violation(
+            &block,
+            block_height,
+            self.params.rpc.as_ref(),
+        ) {
+            Ok(Some(reason)) => {
+                self.wait_for_production(block_height, &reason, record);
+                //Behave as master without block.
+                return Master::default().block_height(block_height).build();
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("Failed to check the block intervals. reason={:?}", e);
+                //Behave as master without block.
+                return Master::default().block_height(block_height).build();
+            }
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if let Err(e) = self.params.block_policy().check_contents(&block, now) {
+            log::warn!("The block is rejected by the policy. reason={:?}", e);
+            self.journal
+                .record(block_height, format!("Refuse to propose the block. {}", e));
+            //Behave as master without block.
+            return Master::default().block_height(block_height).build();
+        }
+
+        if let Err(Error::FederationMismatch(reason)) =
+            self.check_federation_consensus(block_height)
+        {
+            log::error!("Refuse to propose the block. reason={}", reason);
+            self.journal.record(
+                block_height,
+                format!("Refuse to propose the block. {}", reason),
+            );
+            //Behave as master without block.
+            return Master::default().block_height(block_height).build();
+        }
+
+        let block = self.add_xfield_if_needed(block_height, block);
+        let (block_key, block_vss) = match create_block_vss(&block, &self.params, block_height) {
+            Ok(r) => r,
+            Err(e) => {
+                log::error!("Failed to create block VSS. reason={:?}", e);
+                //Behave as master without block.
+                return Master::default().block_height(block_height).build();
+            }
+        };
+
         log::info!(
             "Broadcast candidate block. block hash for signing: {:?}",
             block.header.signature_hash()
         );
+        self.journal.record(
+            block_height,
+            format!(
+                "Broadcast candidate block {}.",
+                block.header.signature_hash()
+            ),
+        );
         self.connection_manager.broadcast_message(Message {
             message_type: MessageType::Candidateblock(block.clone()),
             sender_id: self.params.signer_id,
             receiver_id: None,
         });
 
-        let (keys, shared_secret_for_positive, shared_secret_for_negative) = create_block_vss(
-            block.clone(),
+        let (shared_secret_for_positive, shared_secret_for_negative) = send_block_vss(
+            &block,
+            &block_vss,
             &self.params,
             &self.connection_manager,
             block_height,
@@ -382,7 +1092,7 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
 
         Master::default()
             .candidate_block(Some(block))
-            .block_key(Some(keys.u_i))
+            .block_key(block_key)
             .insert_shared_block_secrets(
                 self.params.signer_id.clone(),
                 shared_secret_for_positive,
@@ -399,19 +1109,20 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
         federation.signers().contains(signer_id)
     }
 
-    fn add_aggregated_public_key_if_needed(&self, block_height: u64, mut block: Block) -> Block {
+    /// Sets the xfield of the block if the next block starts a new federation or changes the
+    /// chain parameters.
+    fn add_xfield_if_needed(&self, block_height: u64, mut block: Block) -> Block {
         let next_block_height = block_height + 1;
         let federation = self
             .params
             .get_federation_by_block_height(next_block_height);
         if federation.block_height() == next_block_height {
             let aggregated_public_key = self.params.aggregated_public_key(next_block_height);
-            // block.add_aggregated_public_key(aggregated_public_key)
             block.header.xfield = XField::AggregatePublicKey(aggregated_public_key);
-            block
-        } else {
-            block
+        } else if let Some(change) = self.params.parameter_schedule().get(next_block_height) {
+            block.header.xfield = change.xfield();
         }
+        block
     }
 
     pub fn process_round_message(
@@ -428,6 +1139,35 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             return self.current_state.clone();
         }
 
+        if let MessageType::Candidateblock(block) = &message {
+            let block_height = self.current_state.block_height();
+            if let Some(tip) = self.tip {
+                if block.header.prev_blockhash != tip {
+                    log::error!(
+                        "Refuse to sign the candidate block. prev_blockhash {} is not the current tip {}.",
+                        block.header.prev_blockhash,
+                        tip
+                    );
+                    self.journal.record(
+                        block_height,
+                        "Refuse to sign the candidate block which is not on the current tip."
+                            .to_string(),
+                    );
+                    return self.current_state.clone();
+                }
+            }
+            if let Err(Error::FederationMismatch(reason)) =
+                self.check_federation_consensus(block_height)
+            {
+                log::error!("Refuse to sign the candidate block. reason={}", reason);
+                self.journal.record(
+                    block_height,
+                    format!("Refuse to sign the candidate block. {}", reason),
+                );
+                return self.current_state.clone();
+            }
+        }
+
         match message {
             MessageType::Candidateblock(block) => process_candidateblock(
                 &sender_id,
@@ -473,20 +1213,37 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
                 &self.connection_manager,
                 &self.params,
             ),
+            // Heartbeat and Dkg don't affect the round.
+            MessageType::Heartbeat(_) | MessageType::Dkg(_) => self.current_state.clone(),
         }
     }
 
     /// Start next round.
     /// decide master of next round according to Round-robin.
     fn start_next_round(&mut self) {
+        self.start_round(false);
+    }
+
+    /// Start a round at the next of the tip block. If `keep_master` is true, the master of the
+    /// current round is kept, so that nodes which abandon a stale round don't shift the
+    /// round-robin.
+    fn start_round(&mut self, keep_master: bool) {
         self.round_limit_timer.restart().unwrap();
+        self.production_timer.stop();
 
         // Get a block height at next of the tip block.
-        let block_height = match self.params.rpc.getblockchaininfo() {
+        self.tip = None;
+        let info = self.params.rpc.getblockchaininfo();
+        self.rpc_connected = info.is_ok();
+        let block_height = match info {
             Ok(GetBlockchainInfoResult {
                 blocks: block_height,
+                bestblockhash,
                 ..
-            }) => block_height + 1,
+            }) => {
+                self.tip = BlockHash::from_hex(&bestblockhash).ok();
+                block_height + 1
+            }
             _ => match self.current_state {
                 NodeState::Idling { block_height } => block_height + 1,
                 NodeState::RoundComplete { block_height, .. } => block_height + 1,
@@ -501,6 +1258,8 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             },
         };
 
+        self.confirm_federations(block_height);
+
         let federation = self.params.get_federation_by_block_height(block_height);
         if !federation.is_member() {
             log::info!(
@@ -508,10 +1267,17 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             block_height,
         );
             self.current_state = NodeState::Idling { block_height };
+            self.journal.record(
+                block_height,
+                "Idling because the node is not a member of the federation.".to_string(),
+            );
             return;
         }
 
-        let next_master_index = next_master_index(&self.current_state, &self.params, block_height);
+        let next_master_index = match master_index(&self.current_state, &self.params) {
+            Some(index) if keep_master => index % self.params.pubkey_list(block_height).len(),
+            _ => next_master_index(&self.current_state, &self.params, block_height),
+        };
 
         log::info!(
             "Start next round: target_block_height={}, self_index={}, master_index={}",
@@ -519,6 +1285,10 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
             self.params.self_node_index(block_height),
             next_master_index,
         );
+        self.journal.record(
+            block_height,
+            format!("Round started. master_index={}", next_master_index),
+        );
 
         if self.params.self_node_index(block_height) == next_master_index {
             self.current_state = self.start_new_round(block_height);
@@ -539,6 +1309,13 @@ impl<T: TapyrusApi, C: ConnectionManager> SignerNode<T, C> {
     }
 }
 
+fn to_control_response<S: Serialize>(value: &S) -> ControlResponse {
+    match serde_json::to_string(value) {
+        Ok(body) => ControlResponse::ok(body),
+        Err(e) => ControlResponse::error(503, &e.to_string()),
+    }
+}
+
 pub fn master_index<T>(state: &NodeState, params: &NodeParameters<T>) -> Option<usize>
 where
     T: TapyrusApi,
@@ -599,11 +1376,19 @@ where
 
 #[cfg(test)]
 mod tests {
+    use crate::block_policy::BlockPolicy;
+    use crate::control::ControlCommand;
     use crate::errors;
     use crate::federation::{Federation, Federations};
-    use crate::net::{ConnectionManager, ConnectionManagerError, Message, SignerID};
+    use crate::net::{
+        ConnectionManager, ConnectionManagerError, Heartbeat, Message, MessageType, SignerID,
+    };
+    use crate::parameter_schedule::{ParameterChange, ParameterSchedule};
     use crate::rpc::tests::{safety, MockRpc};
-    use crate::rpc::TapyrusApi;
+    use crate::rpc::{GetBlockchainInfoResult, GetMempoolInfoResult, TapyrusApi};
+    use crate::signer_node::journal::RoundRecord;
+    use crate::signer_node::node_state::builder::{Builder, Master, Member};
+    use crate::signer_node::status::FederationStatus;
     use crate::signer_node::{
         master_index, BidirectionalSharedSecretMap, NodeParameters, NodeState, SignerNode,
     };
@@ -618,9 +1403,11 @@ mod tests {
     use std::sync::Arc;
     use std::thread;
     use std::thread::JoinHandle;
-    use std::time::Duration;
-    use tapyrus::blockdata::block::Block;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use tapyrus::blockdata::block::{Block, XField};
     use tapyrus::consensus::encode::deserialize;
+    use tapyrus::hash_types::BlockHash;
+    use tapyrus::hashes::hex::FromHex;
     use tapyrus::PublicKey;
 
     pub type SpyMethod = Box<dyn Fn(Arc<Message>) -> () + Send + 'static>;
@@ -831,6 +1618,271 @@ mod tests {
         assert_eq!(master_index(&node.current_state, &node.params).unwrap(), 0);
     }
 
+    #[test]
+    fn test_check_tip() {
+        let block = get_block(0);
+        let tip = block.header.prev_blockhash;
+        let new_tip =
+            BlockHash::from_hex("7303687fb5d80781bd9fece466e76d97a94613d409d127030ff7f34081a899f7")
+                .unwrap();
+        let info = |blocks, hash: BlockHash| GetBlockchainInfoResult {
+            chain: "regtest".to_string(),
+            blocks,
+            headers: blocks,
+            bestblockhash: hash.to_string(),
+            mediantime: 0,
+            initialblockdownload: false,
+        };
+        let mut rpc = crate::tests::helper::rpc::MockRpc::new();
+        rpc.should_call_getblockchaininfo(Ok(info(10, tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(10, tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(12, new_tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(12, new_tip)));
+        let mut node = create_node(
+            Member::default().master_index(0).block_height(10).build(),
+            rpc,
+            None,
+        );
+
+        node.start_next_round();
+        assert_eq!(node.current_state.block_height(), 11);
+        assert_eq!(node.tip, Some(tip));
+        let master = master_index(&node.current_state, &node.params);
+
+        // The tip is not changed.
+        node.check_tip();
+        assert_eq!(node.current_state.block_height(), 11);
+
+        // The round is restarted at the new tip with the same master.
+        node.check_tip();
+        assert_eq!(node.current_state.block_height(), 13);
+        assert_eq!(node.tip, Some(new_tip));
+        assert_eq!(master_index(&node.current_state, &node.params), master);
+        node.params.rpc.assert();
+
+        // The candidate block on the old tip is refused.
+        let sender_id = TEST_KEYS.signer_ids()[0];
+        node.process_round_message(&sender_id, MessageType::Candidateblock(block));
+        let record = node.journal.get(13).unwrap();
+        assert_eq!(
+            record.entries.last().unwrap().event,
+            "Refuse to sign the candidate block which is not on the current tip."
+        );
+    }
+
+    #[test]
+    fn test_check_tip_after_round_success() {
+        let tip = get_block(0).header.prev_blockhash;
+        let own_block =
+            BlockHash::from_hex("7303687fb5d80781bd9fece466e76d97a94613d409d127030ff7f34081a899f7")
+                .unwrap();
+        let info = |blocks, hash: BlockHash| GetBlockchainInfoResult {
+            chain: "regtest".to_string(),
+            blocks,
+            headers: blocks,
+            bestblockhash: hash.to_string(),
+            mediantime: 0,
+            initialblockdownload: false,
+        };
+        let mut rpc = crate::tests::helper::rpc::MockRpc::new();
+        rpc.should_call_getblockchaininfo(Ok(info(11, own_block)));
+        let mut node = create_node(
+            Master::default()
+                .block_height(11)
+                .round_is_done(true)
+                .build(),
+            rpc,
+            None,
+        );
+        node.tip = Some(tip);
+
+        // The master which has completed the round doesn't treat its own block as a new tip.
+        node.check_tip();
+        match node.current_state {
+            NodeState::Master {
+                block_height: 11,
+                round_is_done: true,
+                ..
+            } => assert!(true),
+            _ => assert!(false, "the round should not be restarted"),
+        }
+
+        // The member doesn't treat the block of the current round as a new tip.
+        node.current_state = Member::default().master_index(0).block_height(11).build();
+        node.check_tip();
+        assert_eq!(node.current_state.block_height(), 11);
+        assert_eq!(node.tip, Some(tip));
+        node.params.rpc.assert();
+    }
+
+    #[test]
+    fn test_block_notification() {
+        let tip = get_block(0).header.prev_blockhash;
+        let new_tip =
+            BlockHash::from_hex("7303687fb5d80781bd9fece466e76d97a94613d409d127030ff7f34081a899f7")
+                .unwrap();
+        let next_tip =
+            BlockHash::from_hex("0b7fc4b5e4e1c40e6fc4b7f1f5a5e3a1fa47ec4f3e2f8b5d3e7c1a2b9f0d6e4c")
+                .unwrap();
+        let info = |blocks, hash: BlockHash| GetBlockchainInfoResult {
+            chain: "regtest".to_string(),
+            blocks,
+            headers: blocks,
+            bestblockhash: hash.to_string(),
+            mediantime: 0,
+            initialblockdownload: false,
+        };
+        let mut rpc = crate::tests::helper::rpc::MockRpc::new();
+        rpc.should_call_getblockchaininfo(Ok(info(10, tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(10, tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(11, new_tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(12, new_tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(12, new_tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(13, next_tip)));
+        rpc.should_call_getblockchaininfo(Ok(info(13, next_tip)));
+        let mut node = create_node(
+            Member::default().master_index(0).block_height(10).build(),
+            rpc,
+            None,
+        );
+        let (sender, receiver) = channel();
+        node.block_notification_handler(receiver);
+
+        node.start_next_round();
+        let master = master_index(&node.current_state, &node.params);
+
+        // The notified hash is not trusted. The tip of Tapyrus Core is not changed.
+        sender.send(new_tip).unwrap();
+        node.handle_block_notification();
+        assert_eq!(node.current_state.block_height(), 11);
+        assert_eq!(node.tip, Some(tip));
+
+        // The block of the current round is not treated as a new tip.
+        sender.send(new_tip).unwrap();
+        node.handle_block_notification();
+        assert_eq!(node.current_state.block_height(), 11);
+        assert_eq!(node.tip, Some(tip));
+
+        // The round is restarted at the latest tip with the same master.
+        sender.send(tip).unwrap();
+        sender.send(new_tip).unwrap();
+        node.handle_block_notification();
+        assert_eq!(node.current_state.block_height(), 13);
+        assert_eq!(node.tip, Some(new_tip));
+        assert_eq!(master_index(&node.current_state, &node.params), master);
+
+        // An idling node starts the next round.
+        node.current_state = NodeState::Idling { block_height: 13 };
+        let response =
+            node.process_control_command(ControlCommand::BlockNotify(next_tip.to_string()));
+        assert_eq!(response.code, 200);
+        assert_eq!(node.current_state.block_height(), 14);
+        assert_eq!(node.tip, Some(next_tip));
+
+        // The master which has completed the round doesn't read the tip.
+        node.current_state = Master::default()
+            .block_height(14)
+            .round_is_done(true)
+            .build();
+        let response =
+            node.process_control_command(ControlCommand::BlockNotify(next_tip.to_string()));
+        assert_eq!(response.code, 200);
+        assert_eq!(node.current_state.block_height(), 14);
+        node.params.rpc.assert();
+
+        let response =
+            node.process_control_command(ControlCommand::BlockNotify("invalid".to_string()));
+        assert_eq!(response.code, 400);
+    }
+
+    #[test]
+    fn test_propose_block_waits_for_transactions() {
+        let header = get_block(0).header;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32;
+        let mut prev = header.clone();
+        prev.time = now;
+        let mut rpc = crate::tests::helper::rpc::MockRpc::new();
+        rpc.should_call_getblockheader(Ok(prev.clone()));
+        rpc.should_call_getmempoolinfo(Ok(GetMempoolInfoResult { size: 0, bytes: 0 }));
+        rpc.should_call_getblockheader(Ok(prev));
+        rpc.should_call_getmempoolinfo(Ok(GetMempoolInfoResult { size: 0, bytes: 0 }));
+        let mut node = create_node(Master::default().block_height(11).build(), rpc, None);
+        node.params.set_block_policy(BlockPolicy {
+            max_block_interval: Some(600),
+            ..Default::default()
+        });
+
+        // The master doesn't propose an empty block and checks it again later.
+        node.propose_block(11, true);
+        assert!(node.production_timer.is_started());
+        match &node.current_state {
+            NodeState::Master {
+                candidate_block: None,
+                ..
+            } => {}
+            s => panic!("unexpected state {:?}", s),
+        }
+        let record = node.journal.get(11).unwrap();
+        assert!(record
+            .entries
+            .last()
+            .unwrap()
+            .event
+            .starts_with("Wait for proposing a block."));
+
+        // Waiting is not recorded again.
+        node.propose_block(11, false);
+        assert_eq!(node.journal.get(11).unwrap().entries.len(), 1);
+        node.params.rpc.assert();
+        node.production_timer.stop();
+    }
+
+    #[test]
+    fn test_propose_block_waits_for_block_time() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32;
+        let mut block = get_block(0);
+        let mut prev = block.header.clone();
+        prev.time = now - 100;
+        // The clock of Tapyrus Core is behind the local clock.
+        block.header.time = now - 90;
+        let mut rpc = crate::tests::helper::rpc::MockRpc::new();
+        rpc.should_call_getblockheader(Ok(prev.clone()));
+        rpc.should_call_getnewblock(Ok(block));
+        rpc.should_call_getblockheader(Ok(prev));
+        let mut node = create_node(Master::default().block_height(11).build(), rpc, None);
+        node.params.set_block_policy(BlockPolicy {
+            min_block_interval: Some(30),
+            ..Default::default()
+        });
+
+        // min-block-interval has passed by the local clock, but not by the block time which the
+        // members check. The master waits instead of refusing the block.
+        node.propose_block(11, true);
+        assert!(node.production_timer.is_started());
+        match &node.current_state {
+            NodeState::Master {
+                candidate_block: None,
+                ..
+            } => {}
+            s => panic!("unexpected state {:?}", s),
+        }
+        let record = node.journal.get(11).unwrap();
+        assert!(record
+            .entries
+            .last()
+            .unwrap()
+            .event
+            .starts_with("Wait for proposing a block."));
+        node.params.rpc.assert();
+        node.production_timer.stop();
+    }
+
     #[test]
     fn test_verify_block() {
         let arc_block = safety(get_block(0));
@@ -855,14 +1907,276 @@ mod tests {
         assert!(node.verify_block(&get_invalid_block()).is_err());
     }
 
+    #[test]
+    fn test_check_federation_consensus() {
+        let rpc = MockRpc {
+            return_block: safety(get_block(0)),
+        };
+        let federation = |block_height, aggregated_public_key| {
+            Federation::new(
+                TEST_KEYS.pubkeys()[4],
+                block_height,
+                Some(3),
+                Some(node_vss(0)),
+                aggregated_public_key,
+            )
+        };
+        let federations = Federations::new(vec![
+            federation(0, TEST_KEYS.aggregated()),
+            federation(100, TEST_KEYS.aggregated()),
+        ]);
+        let mut node = create_node(NodeState::Joining, rpc, Some(federations.clone()));
+        let peer = TEST_KEYS.signer_ids()[0];
+
+        // The peer which doesn't send fingerprints is regarded as disagreeing.
+        node.peers.update(peer, Heartbeat::new(0, "Member"));
+        assert!(node.check_federation_consensus(98).is_ok());
+        match node.check_federation_consensus(99) {
+            Err(errors::Error::FederationMismatch(m)) => {
+                assert!(m.ends_with("the peer doesn't send fingerprints of federations"))
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        node.peers.update(
+            peer,
+            Heartbeat::new(0, "Member").with_federations(federations.fingerprints()),
+        );
+        assert!(node.check_federation_consensus(99).is_ok());
+
+        // The peer has another aggregated public key.
+        let other = Federations::new(vec![
+            federation(0, TEST_KEYS.aggregated()),
+            federation(100, TEST_KEYS.pubkeys()[0]),
+        ]);
+        node.peers.update(
+            peer,
+            Heartbeat::new(0, "Member").with_federations(other.fingerprints()),
+        );
+        assert!(node.check_federation_consensus(98).is_ok());
+        match node.check_federation_consensus(99) {
+            Err(errors::Error::FederationMismatch(m)) => {
+                assert!(m.starts_with("The federation at 100 height is different from the one of"))
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        // The peer doesn't have the next federation.
+        let other = Federations::new(vec![federation(0, TEST_KEYS.aggregated())]);
+        node.peers.update(
+            peer,
+            Heartbeat::new(0, "Member").with_federations(other.fingerprints()),
+        );
+        assert!(node.check_federation_consensus(99).is_err());
+
+        // The peers which are not alive are not checked, whether they sent fingerprints or not.
+        node.peers
+            .update(TEST_KEYS.signer_ids()[1], Heartbeat::new(0, "Member"));
+        node.params.heartbeat_interval = 0;
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(node.check_federation_consensus(99).is_ok());
+    }
+
+    #[test]
+    fn test_confirm_federations() {
+        let arc_block = safety(get_block(0));
+        let rpc = MockRpc {
+            return_block: arc_block.clone(),
+        };
+        let federation = |block_height, aggregated_public_key| {
+            Federation::new(
+                TEST_KEYS.pubkeys()[4],
+                block_height,
+                Some(3),
+                Some(node_vss(0)),
+                aggregated_public_key,
+            )
+        };
+        let federations = Federations::new(vec![
+            federation(0, TEST_KEYS.aggregated()),
+            federation(100, TEST_KEYS.pubkeys()[0]),
+        ]);
+        let mut node = create_node(NodeState::Joining, rpc, Some(federations));
+
+        // The federation at 100 is not used before its block height.
+        node.confirm_federations(99);
+        assert!(node.params.inactive_federations().is_empty());
+
+        // The block at 99 doesn't have the xfield, so the node keeps using the previous federation.
+        node.confirm_federations(100);
+        assert!(node.params.inactive_federations().contains(&100));
+        assert_eq!(
+            node.params
+                .get_federation_by_block_height(100)
+                .block_height(),
+            0
+        );
+
+        // The block at 99 which has the xfield gets into the active chain.
+        let mut block = get_block(0);
+        block.header.xfield = XField::AggregatePublicKey(TEST_KEYS.pubkeys()[0]);
+        *arc_block.lock().unwrap() = Ok(block);
+        node.confirm_federations(100);
+        assert!(node.params.inactive_federations().is_empty());
+        assert_eq!(
+            node.params
+                .get_federation_by_block_height(100)
+                .block_height(),
+            100
+        );
+
+        // The block is reorganized away.
+        *arc_block.lock().unwrap() = Ok(get_block(0));
+        node.confirm_federations(101);
+        assert_eq!(
+            node.params
+                .get_federation_by_block_height(101)
+                .block_height(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_add_xfield_if_needed() {
+        let rpc = MockRpc {
+            return_block: safety(get_block(0)),
+        };
+        let federations = Federations::new(vec![
+            Federation::new(
+                TEST_KEYS.pubkeys()[4],
+                0,
+                Some(3),
+                Some(node_vss(0)),
+                TEST_KEYS.aggregated(),
+            ),
+            Federation::new(
+                TEST_KEYS.pubkeys()[4],
+                100,
+                Some(3),
+                Some(node_vss(0)),
+                TEST_KEYS.aggregated(),
+            ),
+        ]);
+        let mut node = create_node(NodeState::Joining, rpc, Some(federations));
+        node.params
+            .set_parameter_schedule(ParameterSchedule::new(vec![ParameterChange {
+                block_height: 200,
+                max_block_size: 2_000_000,
+            }]));
+
+        let block = node.add_xfield_if_needed(98, get_block(0));
+        assert_eq!(block.header.xfield, XField::None);
+
+        let block = node.add_xfield_if_needed(99, get_block(0));
+        assert_eq!(
+            block.header.xfield,
+            XField::AggregatePublicKey(TEST_KEYS.aggregated())
+        );
+
+        let block = node.add_xfield_if_needed(199, get_block(0));
+        assert_eq!(block.header.xfield, XField::MaxBlockSize(2_000_000));
+    }
+
+    #[test]
+    fn test_has_enough_alive_signers() {
+        let arc_block = safety(get_block(0));
+        let rpc = MockRpc {
+            return_block: arc_block.clone(),
+        };
+        let mut node = create_node(NodeState::Joining, rpc, None);
+
+        // The node itself is the only alive signer. The threshold is 3.
+        assert!(!node.has_enough_alive_signers(0));
+
+        node.peers
+            .update(TEST_KEYS.signer_ids()[0], Heartbeat::new(0, "Member"));
+        assert!(!node.has_enough_alive_signers(0));
+
+        node.peers
+            .update(TEST_KEYS.signer_ids()[1], Heartbeat::new(0, "Member"));
+        assert!(node.has_enough_alive_signers(0));
+    }
+
+    #[test]
+    fn test_status() {
+        let arc_block = safety(get_block(0));
+        let rpc = MockRpc {
+            return_block: arc_block.clone(),
+        };
+        let mut node = create_node(NodeState::Joining, rpc, None);
+        let status = node.status();
+        assert_eq!(status.state.kind, "Joining");
+        assert_eq!(status.master_index, None);
+        assert_eq!(status.active_federation, None);
+        // The connections are not checked yet.
+        assert!(!status.connections.rpc);
+        assert!(!status.connections.redis);
+
+        // The status reports the result of the last RPC call in the rounds.
+        node.start_next_round();
+        assert!(node.status().connections.rpc);
+
+        let arc_block = safety(get_block(0));
+        let rpc = MockRpc {
+            return_block: arc_block.clone(),
+        };
+        let state = Member::default().master_index(2).block_height(5).build();
+        let node = create_node(state, rpc, None);
+        let status = node.status();
+        assert_eq!(status.state.kind, "Member");
+        assert_eq!(status.state.block_height, Some(5));
+        assert_eq!(status.master_index, Some(2));
+        let federation = status.active_federation.unwrap();
+        assert_eq!(federation.block_height, 0);
+        assert_eq!(federation.threshold, Some(3));
+        assert_eq!(federation.signers.len(), 5);
+        assert!(status.upcoming_federations.is_empty());
+        assert_eq!(status.timers.round_limit, false);
+    }
+
+    #[test]
+    fn test_process_control_command() {
+        let arc_block = safety(get_block(0));
+        let rpc = MockRpc {
+            return_block: arc_block.clone(),
+        };
+        let state = Member::default().master_index(0).block_height(5).build();
+        let mut node = create_node(state, rpc, None);
+
+        let response = node.process_control_command(ControlCommand::Pause);
+        assert_eq!(response.code, 200);
+        assert_eq!(response.body, "{\"paused\":true}");
+        assert!(node.status().paused);
+
+        let response = node.process_control_command(ControlCommand::Resume);
+        assert_eq!(response.body, "{\"paused\":false}");
+        assert!(!node.status().paused);
+
+        let response = node.process_control_command(ControlCommand::Journal(None));
+        let record: RoundRecord = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(record.block_height, 5);
+        assert_eq!(record.entries.len(), 2);
+
+        let response = node.process_control_command(ControlCommand::Journal(Some(4)));
+        assert_eq!(response.code, 404);
+
+        let response = node.process_control_command(ControlCommand::Federations);
+        let federations: Vec<FederationStatus> = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(federations.len(), 1);
+
+        let response =
+            node.process_control_command(ControlCommand::UpdateFederations("invalid".to_string()));
+        assert_eq!(response.code, 400);
+    }
+
     mod test_for_waiting_ibd_finish {
         use crate::errors::Error;
-        use crate::rpc::{GetBlockchainInfoResult, TapyrusApi};
+        use crate::rpc::{GetBlockchainInfoResult, GetMempoolInfoResult, TapyrusApi};
         use crate::signer_node::tests::create_node;
         use crate::signer_node::{BidirectionalSharedSecretMap, NodeState};
         use std::cell::Cell;
         use std::collections::HashSet;
-        use tapyrus::blockdata::block::Block;
+        use tapyrus::blockdata::block::{Block, BlockHeader};
         use tapyrus::Address;
 
         struct MockRpc {
@@ -889,6 +2203,14 @@ mod tests {
 
                 Ok(result)
             }
+
+            fn getblockheader(&self, _block_height: u64) -> Result<BlockHeader, Error> {
+                unimplemented!()
+            }
+
+            fn getmempoolinfo(&self) -> Result<GetMempoolInfoResult, Error> {
+                unimplemented!()
+            }
         }
 
         #[test]
@@ -906,7 +2228,6 @@ mod tests {
 
             let node = create_node(
                 NodeState::Member {
-                    block_key: None,
                     block_shared_keys: None,
                     shared_block_secrets: BidirectionalSharedSecretMap::new(),
                     candidate_block: None,
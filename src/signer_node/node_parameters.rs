@@ -1,7 +1,12 @@
+use crate::block_policy::BlockPolicy;
 use crate::crypto::multi_party_schnorr::Parameters;
 use crate::federation::{Federation, Federations};
 use crate::net::SignerID;
+use crate::parameter_schedule::ParameterSchedule;
 use crate::rpc::TapyrusApi;
+use crate::signer_node::{HEARTBEAT_INTERVAL_DEFAULT_SECS, TIP_CHECK_INTERVAL_DEFAULT_SECS};
+use crate::signing_service::{InProcessSigningService, SigningService};
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::sync::Arc;
 use tapyrus::{Address, PublicKey};
@@ -14,7 +19,17 @@ pub struct NodeParameters<T: TapyrusApi> {
     pub round_duration: u64,
     pub round_limit: u64,
     pub skip_waiting_ibd: bool,
+    /// Interval(sec) of broadcasting heartbeat messages.
+    pub heartbeat_interval: u64,
+    /// Interval(sec) of checking the chain tip during a round.
+    pub tip_check_interval: u64,
+    /// Computes local signatures with the node secret share.
+    pub signing_service: Box<dyn SigningService>,
     federations: Federations,
+    /// Block heights of the federations whose aggregated public key is not in the active chain.
+    inactive_federations: HashSet<u64>,
+    parameter_schedule: ParameterSchedule,
+    block_policy: BlockPolicy,
 }
 
 impl<T: TapyrusApi> NodeParameters<T> {
@@ -36,12 +51,51 @@ impl<T: TapyrusApi> NodeParameters<T> {
             round_duration,
             round_limit,
             skip_waiting_ibd,
+            heartbeat_interval: HEARTBEAT_INTERVAL_DEFAULT_SECS,
+            tip_check_interval: TIP_CHECK_INTERVAL_DEFAULT_SECS,
+            signing_service: Box::new(InProcessSigningService),
             federations,
+            inactive_federations: HashSet::new(),
+            parameter_schedule: ParameterSchedule::default(),
+            block_policy: BlockPolicy::default(),
         }
     }
 
     pub fn get_federation_by_block_height(&self, block_height: u64) -> &Federation {
-        self.federations.get_by_block_height(block_height)
+        self.federations
+            .get_active_by_block_height(block_height, &self.inactive_federations)
+    }
+
+    pub fn federations(&self) -> &Federations {
+        &self.federations
+    }
+
+    pub fn set_federations(&mut self, federations: Federations) {
+        self.federations = federations;
+    }
+
+    pub fn inactive_federations(&self) -> &HashSet<u64> {
+        &self.inactive_federations
+    }
+
+    pub fn set_inactive_federations(&mut self, inactive_federations: HashSet<u64>) {
+        self.inactive_federations = inactive_federations;
+    }
+
+    pub fn parameter_schedule(&self) -> &ParameterSchedule {
+        &self.parameter_schedule
+    }
+
+    pub fn set_parameter_schedule(&mut self, parameter_schedule: ParameterSchedule) {
+        self.parameter_schedule = parameter_schedule;
+    }
+
+    pub fn block_policy(&self) -> &BlockPolicy {
+        &self.block_policy
+    }
+
+    pub fn set_block_policy(&mut self, block_policy: BlockPolicy) {
+        self.block_policy = block_policy;
     }
 
     pub fn get_signer_id_by_index(&self, block_height: u64, index: usize) -> SignerID {
@@ -19,14 +19,16 @@ pub enum NodeState {
     Master {
         /// *block_key* is random value for using int the Signature Issuing Protocol.
         /// VSS which is distributed to each other signer is generated by this key. All signers in
-        /// all block generation rounds has each own block_key.
+        /// all block generation rounds has each own block_key. It is None if the federation masks
+        /// block VSSs, because the signing service creates the VSS.
         block_key: Option<FE>,
         /// Map of VSSs and commitment in Signature Issuing Protocol. A Signer broadcasts this value
         /// on blockvss message and collected by all signers who include oneself.
         shared_block_secrets: BidirectionalSharedSecretMap,
         /// Share in which is generated from above shared_block_secrets. It is produced by
         /// aggregating VSSs and first element of the commitments which are come from the
-        /// participants who are selected by round master.
+        /// participants who are selected by round master. If the federation masks block VSSs, the
+        /// share is zero, because it is kept in the signing service.
         block_shared_keys: Option<(bool, FE, GE)>,
         /// Candidate block of a round.
         /// It is broadcasted by master node of a round. The goal of rounds are generating signature
@@ -45,14 +47,16 @@ pub enum NodeState {
     Member {
         /// *block_key* is random value for using int the Signature Issuing Protocol.
         /// VSS which is distributed to each other signer is generated by this key. All signers in
-        /// all block generation rounds has each own block_key.
+        /// all block generation rounds has each own block_key. It is None if the federation masks
+        /// block VSSs, because the signing service creates the VSS.
         block_key: Option<FE>,
         /// Map of VSSs and commitment in Signature Issuing Protocol. A Signer broadcasts this value
         /// on blockvss message and collected by all signers who include oneself.
         shared_block_secrets: BidirectionalSharedSecretMap,
         /// Share in which is generated from above shared_block_secrets. It is produced by
         /// aggregating VSSs and first element of the commitments which are come from the
-        /// participants who are selected by round master.
+        /// participants who are selected by round master. If the federation masks block VSSs, the
+        /// share is zero, because it is kept in the signing service.
         block_shared_keys: Option<(bool, FE, GE)>,
         /// Candidate block of a round.
         /// It is broadcasted by master node of a round. The goal of rounds are generating signature
@@ -80,6 +84,17 @@ impl NodeState {
             NodeState::Joining => unreachable!(),
         }
     }
+
+    /// Returns the name of the state variant.
+    pub fn kind(&self) -> &'static str {
+        match &self {
+            NodeState::Joining => "Joining",
+            NodeState::Idling { .. } => "Idling",
+            NodeState::Master { .. } => "Master",
+            NodeState::Member { .. } => "Member",
+            NodeState::RoundComplete { .. } => "RoundComplete",
+        }
+    }
 }
 
 pub mod builder {
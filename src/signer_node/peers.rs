@@ -0,0 +1,100 @@
+use crate::net::{Heartbeat, SignerID};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// The latest heartbeat which is received from a peer.
+#[derive(Debug, Clone)]
+pub struct PeerStatus {
+    pub heartbeat: Heartbeat,
+    /// The time when the node received the heartbeat.
+    pub last_seen: Instant,
+}
+
+/// Table of the peers which sent heartbeat messages to the node.
+#[derive(Debug, Default)]
+pub struct PeerTable {
+    peers: BTreeMap<SignerID, PeerStatus>,
+}
+
+impl PeerTable {
+    pub fn new() -> Self {
+        PeerTable {
+            peers: BTreeMap::new(),
+        }
+    }
+
+    /// Record the heartbeat as the latest one from the peer.
+    pub fn update(&mut self, signer_id: SignerID, heartbeat: Heartbeat) {
+        self.peers.insert(
+            signer_id,
+            PeerStatus {
+                heartbeat,
+                last_seen: Instant::now(),
+            },
+        );
+    }
+
+    pub fn get(&self, signer_id: &SignerID) -> Option<&PeerStatus> {
+        self.peers.get(signer_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&SignerID, &PeerStatus)> {
+        self.peers.iter()
+    }
+
+    /// Returns true if the peer sent a heartbeat within `timeout`.
+    pub fn is_alive(&self, signer_id: &SignerID, timeout: Duration) -> bool {
+        match self.peers.get(signer_id) {
+            Some(status) => status.last_seen.elapsed() <= timeout,
+            None => false,
+        }
+    }
+
+    /// Returns the number of alive peers among the `signers`.
+    pub fn count_alive(&self, signers: &[SignerID], timeout: Duration) -> usize {
+        signers
+            .iter()
+            .filter(|signer_id| self.is_alive(signer_id, timeout))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::helper::keys::TEST_KEYS;
+
+    #[test]
+    fn test_update() {
+        let mut peers = PeerTable::new();
+        let signer_id = TEST_KEYS.signer_ids()[0];
+        assert!(peers.get(&signer_id).is_none());
+
+        peers.update(signer_id, Heartbeat::new(10, "Member"));
+        assert_eq!(peers.get(&signer_id).unwrap().heartbeat.tip_height, 10);
+
+        // The latest heartbeat overwrites the previous one.
+        peers.update(signer_id, Heartbeat::new(11, "Master"));
+        let status = peers.get(&signer_id).unwrap();
+        assert_eq!(status.heartbeat.tip_height, 11);
+        assert_eq!(status.heartbeat.state, "Master");
+        assert_eq!(peers.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_count_alive() {
+        let mut peers = PeerTable::new();
+        let signer_ids = TEST_KEYS.signer_ids();
+        peers.update(signer_ids[0], Heartbeat::new(10, "Member"));
+        peers.update(signer_ids[1], Heartbeat::new(10, "Member"));
+
+        let timeout = Duration::from_secs(60);
+        assert!(peers.is_alive(&signer_ids[0], timeout));
+        assert!(!peers.is_alive(&signer_ids[2], timeout));
+        assert_eq!(peers.count_alive(&signer_ids, timeout), 2);
+
+        // All peers are regarded as dead if they didn't send heartbeat within the timeout.
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(peers.count_alive(&signer_ids, Duration::from_millis(1)), 0);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::federation::Federation;
+use crate::signer_node::NodeState;
+use std::fmt;
+
+/// Summary of a node state. Secrets in the state, like block key and shares, are never included,
+/// so that it can be put on logs or returned to operators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSummary {
+    pub kind: String,
+    pub block_height: Option<u64>,
+    /// Signature hash of the candidate block.
+    pub candidate_block: Option<String>,
+    /// Number of collected blockvss.
+    pub vss_count: usize,
+    /// Number of collected local signatures.
+    pub signature_count: usize,
+    pub participants: Vec<String>,
+}
+
+impl From<&NodeState> for StateSummary {
+    fn from(state: &NodeState) -> Self {
+        let (candidate_block, vss_count, signature_count, participants) = match state {
+            NodeState::Master {
+                candidate_block,
+                shared_block_secrets,
+                signatures,
+                participants,
+                ..
+            } => (
+                candidate_block,
+                shared_block_secrets.len(),
+                signatures.len(),
+                Some(participants),
+            ),
+            NodeState::Member {
+                candidate_block,
+                shared_block_secrets,
+                participants,
+                ..
+            } => (
+                candidate_block,
+                shared_block_secrets.len(),
+                0,
+                Some(participants),
+            ),
+            _ => (&None, 0, 0, None),
+        };
+
+        let mut participants: Vec<String> = participants
+            .map(|p| p.iter().map(|i| i.to_string()).collect())
+            .unwrap_or_default();
+        participants.sort();
+
+        let block_height = match state {
+            NodeState::Joining => None,
+            _ => Some(state.block_height()),
+        };
+
+        StateSummary {
+            kind: state.kind().to_string(),
+            block_height,
+            candidate_block: candidate_block
+                .as_ref()
+                .map(|b| b.header.signature_hash().to_string()),
+            vss_count,
+            signature_count,
+            participants,
+        }
+    }
+}
+
+impl fmt::Display for StateSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(block_height) = self.block_height {
+            write!(
+                f,
+                " {{ block_height: {}, candidate_block: {:?}, vss: {}, signatures: {}, participants: {} }}",
+                block_height,
+                self.candidate_block,
+                self.vss_count,
+                self.signature_count,
+                self.participants.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Public information of a federation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FederationStatus {
+    pub block_height: u64,
+    pub is_member: bool,
+    pub threshold: Option<u8>,
+    /// Signers in the federation. It is empty if the node is not a member of the federation.
+    pub signers: Vec<String>,
+    pub aggregated_public_key: String,
+}
+
+impl From<&Federation> for FederationStatus {
+    fn from(federation: &Federation) -> Self {
+        let signers = if federation.is_member() {
+            federation.signers().iter().map(|i| i.to_string()).collect()
+        } else {
+            vec![]
+        };
+
+        FederationStatus {
+            block_height: federation.block_height(),
+            is_member: federation.is_member(),
+            threshold: federation.threshold(),
+            signers,
+            aggregated_public_key: federation.aggregated_public_key().to_string(),
+        }
+    }
+}
+
+/// Whether each timer of the node is running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimersStatus {
+    pub round_limit: bool,
+    pub round_interval: bool,
+    pub heartbeat: bool,
+}
+
+/// Whether the node could reach to Tapyrus Core RPC and Redis when it checked them last in the
+/// rounds. A status request doesn't call them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionsStatus {
+    pub rpc: bool,
+    /// The Tapyrus Core RPC endpoint which serves calls.
+    #[serde(default)]
+    pub rpc_endpoint: Option<String>,
+    pub redis: bool,
+}
+
+/// The latest heartbeat from a peer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub signer_id: String,
+    pub version: String,
+    pub tip_height: u64,
+    pub state: String,
+    /// Elapsed time(sec) since the node received the heartbeat.
+    pub last_seen: u64,
+    pub alive: bool,
+}
+
+/// Response of the status endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub signer_id: String,
+    pub state: StateSummary,
+    pub master_index: Option<usize>,
+    /// True if the node is paused by operators. A paused node doesn't propose or sign blocks.
+    pub paused: bool,
+    pub active_federation: Option<FederationStatus>,
+    pub upcoming_federations: Vec<FederationStatus>,
+    pub timers: TimersStatus,
+    pub connections: ConnectionsStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer_node::node_state::builder::{Builder, Master, Member};
+    use crate::tests::helper::blocks::get_block;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_state_summary() {
+        let summary = StateSummary::from(&NodeState::Joining);
+        assert_eq!(summary.kind, "Joining");
+        assert_eq!(summary.block_height, None);
+        assert_eq!(format!("{}", summary), "Joining");
+
+        let block = get_block(0);
+        let participants: HashSet<_> = TEST_KEYS.signer_ids().into_iter().take(3).collect();
+        let state = Master::default()
+            .candidate_block(Some(block.clone()))
+            .participants(participants)
+            .block_height(10)
+            .build();
+        let summary = StateSummary::from(&state);
+        assert_eq!(summary.kind, "Master");
+        assert_eq!(summary.block_height, Some(10));
+        assert_eq!(
+            summary.candidate_block,
+            Some(block.header.signature_hash().to_string())
+        );
+        assert_eq!(summary.vss_count, 0);
+        assert_eq!(summary.signature_count, 0);
+        assert_eq!(summary.participants.len(), 3);
+
+        let state = Member::default().block_height(11).build();
+        let summary = StateSummary::from(&state);
+        assert_eq!(summary.kind, "Member");
+        assert_eq!(
+            format!("{}", summary),
+            "Member { block_height: 11, candidate_block: None, vss: 0, signatures: 0, participants: 0 }"
+        );
+    }
+}
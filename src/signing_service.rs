@@ -0,0 +1,820 @@
+// Copyright (c) 2019 Chaintope Inc.
+// Distributed under the MIT software license, see the accompanying
+// file COPYING or http://www.opensource.org/licenses/mit-license.php.
+
+//! Computation of block VSSs and local signatures with the node secret share.
+//!
+//! `tapyrus-signerd` creates its block VSS and computes local signatures through `SigningService`.
+//! With `InProcessSigningService` the node secret share is constructed inside the daemon. With
+//! `UnixSocketSigningService` the daemon asks `tapyrus-signing-service`, a separate process which
+//! holds the share, over a Unix domain socket.
+//!
+//! The signing service is used for the federations with `masked-block-vss`. The secret shares of
+//! block VSSs are masked for each receiver with a key which only the sender and the receiver can
+//! derive from their node secret shares. So the daemon relays the shares but never knows the nonce
+//! of the local signature, which would reveal the node secret share. The other federations use the
+//! plain shares, and the daemon computes the local signatures by itself.
+
+use crate::crypto::multi_party_schnorr::{LocalSig, SharedKeys};
+use crate::crypto::vss::Vss;
+use crate::errors::Error;
+use crate::federation::{Federation, Federations};
+use crate::sign::Sign;
+use crate::signer_node::{BidirectionalSharedSecretMap, SharedSecret};
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use curv::{BigInt, FE, GE};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tapyrus::blockdata::block::Block;
+use tapyrus::hash_types::BlockSigHash;
+
+/// Time to wait for the signing service to answer a request.
+const RESPONSE_TIMEOUT_SECS: u64 = 5;
+
+/// The block VSS of the node. The secret shares are masked for each receiver if the federation has
+/// `masked-block-vss`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockVss {
+    pub vss_for_positive: VerifiableSS,
+    pub vss_for_negative: VerifiableSS,
+    /// The secret shares for positive and negative in the order of the signers of the federation.
+    /// It includes the shares for the node itself.
+    pub secret_shares: Vec<(FE, FE)>,
+}
+
+/// Creates block VSSs and local signatures for the federations with `masked-block-vss`.
+pub trait SigningService {
+    /// Creates the block VSS of the node for the block. The secret shares for all signers of the
+    /// federation, including the node itself, are masked for each receiver.
+    fn create_block_vss(&self, federation: &Federation, block: &Block) -> Result<BlockVss, Error>;
+
+    /// Returns whether the block VSSs for positive are used, the aggregated public key of the
+    /// block VSSs and the local signature for the block. `shared_block_secrets` are the block VSSs
+    /// of the participants which have the masked secret shares for the node.
+    fn sign(
+        &self,
+        federation: &Federation,
+        block: &Block,
+        shared_block_secrets: &BidirectionalSharedSecretMap,
+    ) -> Result<(bool, GE, LocalSig), Error>;
+
+    /// Returns true if the service uses the node secret shares in the federations of the node.
+    /// Otherwise, the node doesn't keep the shares.
+    fn needs_secret_shares(&self) -> bool;
+}
+
+/// Signs with the node secret share which is constructed from the nodevss of the federation.
+pub struct InProcessSigningService;
+
+impl SigningService for InProcessSigningService {
+    fn create_block_vss(&self, federation: &Federation, block: &Block) -> Result<BlockVss, Error> {
+        Ok(create_block_vss(
+            federation,
+            &federation.node_secret_share().x_i,
+            &block.header.signature_hash(),
+        ))
+    }
+
+    fn sign(
+        &self,
+        federation: &Federation,
+        block: &Block,
+        shared_block_secrets: &BidirectionalSharedSecretMap,
+    ) -> Result<(bool, GE, LocalSig), Error> {
+        local_sig(
+            federation,
+            &federation.node_secret_share(),
+            block,
+            shared_block_secrets,
+        )
+    }
+
+    fn needs_secret_shares(&self) -> bool {
+        true
+    }
+}
+
+/// Returns the block height which is set in the coinbase of the block. As the other coinbases in
+/// Tapyrus, the index of the previous output is the block height.
+fn block_height(block: &Block) -> Option<u64> {
+    block
+        .txdata
+        .first()
+        .filter(|tx| tx.input.len() == 1 && tx.input[0].previous_output.txid == Default::default())
+        .map(|tx| tx.input[0].previous_output.vout as u64)
+}
+
+/// Returns the masks of the secret shares for positive and negative of the block VSS which the
+/// sender sends to the receiver. Both of them can compute the masks from their own node secret
+/// share and the public share of the other, but nobody else can.
+fn masks(
+    node_share: &FE,
+    other: &GE,
+    sighash: &BlockSigHash,
+    sender: usize,
+    receiver: usize,
+) -> (FE, FE) {
+    let point = other * node_share;
+    let mask = |tag: u8| -> FE {
+        let mut hasher = Sha256::new();
+        hasher.input(&sighash[..]);
+        hasher.input(&point.get_element().serialize()[..]);
+        hasher.input(&(sender as u64).to_be_bytes());
+        hasher.input(&(receiver as u64).to_be_bytes());
+        hasher.input(&[tag]);
+        ECScalar::from(&BigInt::from(&hasher.result()[..]))
+    };
+    (mask(0), mask(1))
+}
+
+/// Creates the block VSS of the node and masks the secret shares for each signer.
+fn create_block_vss(federation: &Federation, node_share: &FE, sighash: &BlockSigHash) -> BlockVss {
+    let signer_count = federation.signers().len();
+    let index = federation.node_index();
+    let threshold = federation
+        .threshold()
+        .expect("The node must be a member of the federation.");
+    let (_, vss_for_positive, shares_for_positive, vss_for_negative, shares_for_negative) =
+        Vss::create_block_shares(index + 1, threshold as usize, signer_count);
+
+    let secret_shares = (0..signer_count)
+        .map(|i| {
+            let (positive, negative) = masks(
+                node_share,
+                &federation.public_share(i + 1),
+                sighash,
+                index,
+                i,
+            );
+            (
+                shares_for_positive[i] + positive,
+                shares_for_negative[i] + negative,
+            )
+        })
+        .collect();
+    BlockVss {
+        vss_for_positive,
+        vss_for_negative,
+        secret_shares,
+    }
+}
+
+/// Unmasks the secret shares of the block VSSs for the node, and computes the local signature. The
+/// block VSS of the node must be included, so that the nonce is not known to the other signers.
+fn local_sig(
+    federation: &Federation,
+    node_share: &SharedKeys,
+    block: &Block,
+    shared_block_secrets: &BidirectionalSharedSecretMap,
+) -> Result<(bool, GE, LocalSig), Error> {
+    let sighash = block.header.signature_hash();
+    let signers = federation.signers();
+    let index = federation.node_index();
+    if !shared_block_secrets.contains_key(&signers[index]) {
+        return Err(Error::SigningService(
+            "The block VSS of the node is not included.".to_string(),
+        ));
+    }
+
+    let mut unmasked = BidirectionalSharedSecretMap::new();
+    for (sender_id, (positive, negative)) in shared_block_secrets {
+        let sender = signers
+            .iter()
+            .position(|i| i == sender_id)
+            .ok_or(Error::InvalidSS)?;
+        let (mask_positive, mask_negative) = masks(
+            &node_share.x_i,
+            &federation.public_share(sender + 1),
+            &sighash,
+            sender,
+            index,
+        );
+        unmasked.insert(
+            *sender_id,
+            (
+                unmask(positive, &mask_positive),
+                unmask(negative, &mask_negative),
+            ),
+        );
+    }
+
+    let (is_positive, block_shared_keys) = Vss::create_block_shared_keys(index + 1, &unmasked)?;
+    let local_sig = Sign::sign(&block_shared_keys, node_share, sighash);
+    Ok((is_positive, block_shared_keys.y, local_sig))
+}
+
+fn unmask(shared_secret: &SharedSecret, mask: &FE) -> SharedSecret {
+    SharedSecret {
+        vss: shared_secret.vss.clone(),
+        secret_share: shared_secret.secret_share.sub(&mask.get_element()),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SigningRequest {
+    CreateBlockVss {
+        block: Block,
+        /// The block height of the federation which the node uses. The node keeps using the
+        /// previous federation until the new one is activated in the chain.
+        federation_block_height: u64,
+    },
+    Sign {
+        block: Block,
+        federation_block_height: u64,
+        shared_block_secrets: BidirectionalSharedSecretMap,
+    },
+}
+
+impl SigningRequest {
+    fn block(&self) -> &Block {
+        match self {
+            SigningRequest::CreateBlockVss { block, .. } => block,
+            SigningRequest::Sign { block, .. } => block,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SigningResponse {
+    BlockVss(BlockVss),
+    Signed {
+        is_positive: bool,
+        y: GE,
+        gamma_i: FE,
+        e: FE,
+    },
+    Rejected(String),
+}
+
+/// Asks `tapyrus-signing-service` listening on the Unix domain socket for block VSSs and local
+/// signatures.
+pub struct UnixSocketSigningService {
+    path: PathBuf,
+}
+
+impl UnixSocketSigningService {
+    pub fn new(path: &str) -> Self {
+        UnixSocketSigningService {
+            path: PathBuf::from(path),
+        }
+    }
+
+    fn request(&self, request: &SigningRequest) -> Result<SigningResponse, Error> {
+        let stream = UnixStream::connect(&self.path).map_err(|e| {
+            Error::SigningService(format!("can not connect to {:?}: {}", self.path, e))
+        })?;
+        stream.set_read_timeout(Some(Duration::from_secs(RESPONSE_TIMEOUT_SECS)))?;
+        let mut writer = &stream;
+        writer.write_all(format!("{}\n", serde_json::to_string(request)?).as_bytes())?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        match serde_json::from_str(&line)? {
+            SigningResponse::Rejected(message) => Err(Error::SigningService(message)),
+            response => Ok(response),
+        }
+    }
+}
+
+impl SigningService for UnixSocketSigningService {
+    fn create_block_vss(&self, federation: &Federation, block: &Block) -> Result<BlockVss, Error> {
+        let request = SigningRequest::CreateBlockVss {
+            block: block.clone(),
+            federation_block_height: federation.block_height(),
+        };
+        match self.request(&request)? {
+            SigningResponse::BlockVss(block_vss) => Ok(block_vss),
+            response => Err(Error::SigningService(format!(
+                "unexpected response: {:?}",
+                response
+            ))),
+        }
+    }
+
+    fn sign(
+        &self,
+        federation: &Federation,
+        block: &Block,
+        shared_block_secrets: &BidirectionalSharedSecretMap,
+    ) -> Result<(bool, GE, LocalSig), Error> {
+        let request = SigningRequest::Sign {
+            block: block.clone(),
+            federation_block_height: federation.block_height(),
+            shared_block_secrets: shared_block_secrets.clone(),
+        };
+        match self.request(&request)? {
+            SigningResponse::Signed {
+                is_positive,
+                y,
+                gamma_i,
+                e,
+            } => Ok((is_positive, y, LocalSig { gamma_i, e })),
+            response => Err(Error::SigningService(format!(
+                "unexpected response: {:?}",
+                response
+            ))),
+        }
+    }
+
+    fn needs_secret_shares(&self) -> bool {
+        false
+    }
+}
+
+/// The block which the signing server signed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SignedBlock {
+    block_height: u64,
+    sighash: BlockSigHash,
+    /// The first commitment of the block VSS of the node, which identifies the nonce.
+    nonce: GE,
+}
+
+/// Holds the federations which include the node secret share, and serves the requests from
+/// `UnixSocketSigningService`.
+pub struct SigningServer {
+    federations: Federations,
+    /// The file which the signed blocks are appended to.
+    path: PathBuf,
+    signed: Vec<SignedBlock>,
+}
+
+impl SigningServer {
+    /// Creates the server which records the signed blocks in the file at `path`. The blocks which
+    /// were recorded before the server restarted are loaded from the file.
+    pub fn new(federations: Federations, path: &Path) -> Result<Self, Error> {
+        let signed = match File::open(path) {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .map(|line| Ok(serde_json::from_str(&line?)?))
+                .collect::<Result<Vec<SignedBlock>, Error>>()?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e.into()),
+        };
+        Ok(SigningServer {
+            federations,
+            path: path.to_path_buf(),
+            signed,
+        })
+    }
+
+    /// Handles the request. The server never signs with the same block VSS of the node twice,
+    /// even after it restarts, because reusing the nonce of the local signature would reveal the
+    /// node secret share. A block can be signed again with a new block VSS, so that a failed round
+    /// can be retried with the same or another block at the same height.
+    pub fn handle(&mut self, request: &SigningRequest) -> SigningResponse {
+        match self.try_handle(request) {
+            Ok(response) => response,
+            Err(message) => SigningResponse::Rejected(message),
+        }
+    }
+
+    fn try_handle(&mut self, request: &SigningRequest) -> Result<SigningResponse, String> {
+        match request {
+            SigningRequest::CreateBlockVss {
+                block,
+                federation_block_height,
+            } => {
+                let (_, federation) = self.check_block(block, *federation_block_height)?;
+                Ok(SigningResponse::BlockVss(create_block_vss(
+                    &federation,
+                    &federation.node_secret_share().x_i,
+                    &block.header.signature_hash(),
+                )))
+            }
+            SigningRequest::Sign {
+                block,
+                federation_block_height,
+                shared_block_secrets,
+            } => {
+                let (block_height, federation) =
+                    self.check_block(block, *federation_block_height)?;
+                let nonce = shared_block_secrets
+                    .get(&federation.signers()[federation.node_index()])
+                    .and_then(|(positive, _)| positive.vss.commitments.first().cloned())
+                    .ok_or_else(|| "The block VSS of the node is not included.".to_string())?;
+                if self.signed.iter().any(|i| i.nonce == nonce) {
+                    return Err("The block VSS of the node has already been used.".to_string());
+                }
+
+                let (is_positive, y, local_sig) = local_sig(
+                    &federation,
+                    &federation.node_secret_share(),
+                    block,
+                    shared_block_secrets,
+                )
+                .map_err(|e| format!("Failed to compute the local signature: {:?}", e))?;
+                self.record(SignedBlock {
+                    block_height,
+                    sighash: block.header.signature_hash(),
+                    nonce,
+                })
+                .map_err(|e| format!("Failed to record the signed block: {:?}", e))?;
+                Ok(SigningResponse::Signed {
+                    is_positive,
+                    y,
+                    gamma_i: local_sig.gamma_i,
+                    e: local_sig.e,
+                })
+            }
+        }
+    }
+
+    /// Checks the block before the node secret share is used for it. Returns the block height in
+    /// the coinbase and the federation of the node.
+    fn check_block(
+        &self,
+        block: &Block,
+        federation_block_height: u64,
+    ) -> Result<(u64, Federation), String> {
+        let block_height =
+            block_height(block).ok_or_else(|| "The block doesn't have a coinbase.".to_string())?;
+        let federation = self
+            .federations
+            .iter()
+            .find(|f| {
+                f.block_height() == federation_block_height
+                    && federation_block_height <= block_height
+            })
+            .ok_or_else(|| {
+                format!(
+                    "The federation at block height {} is not found.",
+                    federation_block_height
+                )
+            })?;
+        if !federation.is_member() {
+            return Err(format!(
+                "The signer is not a member of the federation at block height {}.",
+                federation_block_height
+            ));
+        }
+        if !federation.masked_block_vss() {
+            return Err(format!(
+                "The federation at block height {} doesn't mask the block VSSs.",
+                federation_block_height
+            ));
+        }
+
+        // The block which starts a new federation must have the aggregated public key of it.
+        if let Some(public_key) = block.header.aggregated_public_key() {
+            if !self.federations.iter().any(|f| {
+                f.block_height() == block_height + 1 && f.aggregated_public_key() == public_key
+            }) {
+                return Err(format!(
+                    "The block at block height {} has the unknown aggregated public key {}.",
+                    block_height, public_key
+                ));
+            }
+        }
+        Ok((block_height, federation.clone()))
+    }
+
+    /// Appends the signed block to the file before the local signature is returned.
+    fn record(&mut self, signed: SignedBlock) -> Result<(), Error> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(format!("{}\n", serde_json::to_string(&signed)?).as_bytes())?;
+        file.sync_all()?;
+        self.signed.push(signed);
+        Ok(())
+    }
+
+    /// Handles connections on the listener. Each connection has one request.
+    pub fn serve(&mut self, listener: UnixListener) {
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(Error::from)
+                .and_then(|stream| self.handle_connection(stream));
+            if let Err(e) = result {
+                log::warn!("Failed to handle signing request: {:?}", e);
+            }
+        }
+    }
+
+    fn handle_connection(&mut self, stream: UnixStream) -> Result<(), Error> {
+        stream.set_read_timeout(Some(Duration::from_secs(RESPONSE_TIMEOUT_SECS)))?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        let response = match serde_json::from_str::<SigningRequest>(&line) {
+            Ok(request) => {
+                let response = self.handle(&request);
+                match &response {
+                    SigningResponse::BlockVss(_) => log::info!(
+                        "Created block VSS. sighash: {}",
+                        request.block().header.signature_hash()
+                    ),
+                    SigningResponse::Signed { .. } => log::info!(
+                        "Signed block. sighash: {}",
+                        request.block().header.signature_hash()
+                    ),
+                    SigningResponse::Rejected(message) => log::warn!("{}", message),
+                }
+                response
+            }
+            Err(e) => SigningResponse::Rejected(format!("Invalid request: {}", e)),
+        };
+        let mut writer = &stream;
+        writer.write_all(format!("{}\n", serde_json::to_string(&response)?).as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::SignerID;
+    use crate::tests::helper::blocks::get_block;
+    use crate::tests::helper::keys::TEST_KEYS;
+    use crate::tests::helper::node_vss::node_vss;
+    use std::collections::BTreeMap;
+    use tapyrus::blockdata::block::XField;
+
+    fn federations(index: usize) -> Federations {
+        Federations::new(vec![
+            Federation::new(
+                TEST_KEYS.pubkeys()[index],
+                0,
+                Some(3),
+                Some(node_vss(index)),
+                TEST_KEYS.aggregated(),
+            )
+            .with_masked_block_vss(true),
+            Federation::new(
+                TEST_KEYS.pubkeys()[index],
+                100,
+                None,
+                None,
+                TEST_KEYS.aggregated(),
+            ),
+        ])
+    }
+
+    fn federation(index: usize) -> Federation {
+        federations(index).get_by_block_height(0).clone()
+    }
+
+    /// Returns the block at the block height. The sighash is changed by `time`.
+    fn block(block_height: u32, time: u32) -> Block {
+        let mut block = get_block(0);
+        block.txdata[0].input[0].previous_output.vout = block_height;
+        block.header.time += time;
+        block
+    }
+
+    /// Returns the block VSSs which the signer at `receiver` receives from the senders.
+    fn shared_block_secrets(
+        block_vss: &BTreeMap<usize, BlockVss>,
+        receiver: usize,
+    ) -> BidirectionalSharedSecretMap {
+        block_vss
+            .iter()
+            .map(|(sender, vss)| {
+                (
+                    SignerID::new(TEST_KEYS.pubkeys()[*sender]),
+                    (
+                        SharedSecret {
+                            vss: vss.vss_for_positive.clone(),
+                            secret_share: vss.secret_shares[receiver].0,
+                        },
+                        SharedSecret {
+                            vss: vss.vss_for_negative.clone(),
+                            secret_share: vss.secret_shares[receiver].1,
+                        },
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    fn create_block_vss(block: &Block, senders: &[usize]) -> BTreeMap<usize, BlockVss> {
+        senders
+            .iter()
+            .map(|i| {
+                let vss = InProcessSigningService
+                    .create_block_vss(&federation(*i), block)
+                    .unwrap();
+                (*i, vss)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sign_with_masked_shares() {
+        let block = block(10, 0);
+        let participants = [0, 1, 2];
+        let block_vss = create_block_vss(&block, &participants);
+
+        // The shares are masked. They are not valid for the commitments as they are.
+        let vss = &block_vss[&0];
+        assert!(vss
+            .vss_for_positive
+            .validate_share(&vss.secret_shares[1].0, 2)
+            .is_err());
+
+        let mut signatures = BTreeMap::new();
+        let mut block_shared_keys = None;
+        for i in participants.iter() {
+            let (is_positive, y, local_sig) = InProcessSigningService
+                .sign(
+                    &federation(*i),
+                    &block,
+                    &shared_block_secrets(&block_vss, *i),
+                )
+                .unwrap();
+            // The share of the block VSSs is kept in the signing service.
+            block_shared_keys = Some((is_positive, ECScalar::zero(), y));
+            signatures.insert(
+                SignerID::new(TEST_KEYS.pubkeys()[*i]),
+                (local_sig.gamma_i, local_sig.e),
+            );
+        }
+
+        let federation = federation(0);
+        let signature = Vss::aggregate_and_verify_signature(
+            &block,
+            signatures,
+            &TEST_KEYS.pubkeys(),
+            &federation.node_shared_secrets(),
+            &block_shared_keys,
+            &shared_block_secrets(&block_vss, 0),
+            &federation.aggregated_point(),
+        );
+        assert!(signature.is_ok());
+
+        // The block VSS of the node must be included.
+        let mut block_vss = block_vss;
+        block_vss.remove(&0);
+        match InProcessSigningService.sign(
+            &federation,
+            &block,
+            &shared_block_secrets(&block_vss, 0),
+        ) {
+            Err(Error::SigningService(_)) => {}
+            _ => panic!("should be rejected"),
+        }
+    }
+
+    fn assert_rejected(response: SigningResponse) {
+        if let SigningResponse::Rejected(_) = response {
+            return;
+        }
+        panic!("should be rejected: {:?}", response);
+    }
+
+    fn create_request(block: &Block, federation_block_height: u64) -> SigningRequest {
+        SigningRequest::CreateBlockVss {
+            block: block.clone(),
+            federation_block_height,
+        }
+    }
+
+    fn sign_request(server: &mut SigningServer, block: &Block) -> SigningRequest {
+        let mut block_vss = create_block_vss(block, &[1, 2]);
+        match server.handle(&create_request(block, 0)) {
+            SigningResponse::BlockVss(vss) => {
+                block_vss.insert(0, vss);
+            }
+            response => panic!("unexpected response: {:?}", response),
+        }
+        SigningRequest::Sign {
+            block: block.clone(),
+            federation_block_height: 0,
+            shared_block_secrets: shared_block_secrets(&block_vss, 0),
+        }
+    }
+
+    #[test]
+    fn test_handle() {
+        let path = std::env::temp_dir().join(format!(
+            "tapyrus-signing-service-test-handle-{}.log",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut server = SigningServer::new(federations(0), &path).unwrap();
+
+        // The signature is same with the one which is signed in process.
+        let request = sign_request(&mut server, &block(10, 0));
+        let expected = match &request {
+            SigningRequest::Sign {
+                block,
+                shared_block_secrets,
+                ..
+            } => InProcessSigningService
+                .sign(&federation(0), block, shared_block_secrets)
+                .unwrap(),
+            _ => unreachable!(),
+        };
+        match server.handle(&request) {
+            SigningResponse::Signed {
+                is_positive,
+                y,
+                gamma_i,
+                e,
+            } => {
+                assert_eq!(is_positive, expected.0);
+                assert_eq!(y, expected.1);
+                assert_eq!(gamma_i, expected.2.gamma_i);
+                assert_eq!(e, expected.2.e);
+            }
+            response => panic!("unexpected response: {:?}", response),
+        }
+
+        // The block VSS of the node can't be used twice.
+        assert_rejected(server.handle(&request));
+
+        // The same block can be signed again with a new block VSS.
+        let request = sign_request(&mut server, &block(10, 0));
+        if let SigningResponse::Rejected(m) = server.handle(&request) {
+            panic!("unexpected rejection: {}", m);
+        }
+
+        // The block VSS can't be used again even after the server restarts.
+        let mut server = SigningServer::new(federations(0), &path).unwrap();
+        assert_rejected(server.handle(&request));
+
+        // A retried round can sign another block at the same height.
+        let request = sign_request(&mut server, &block(10, 1));
+        if let SigningResponse::Rejected(m) = server.handle(&request) {
+            panic!("unexpected rejection: {}", m);
+        }
+
+        // The signer is not a member of the federation at height 100.
+        assert_rejected(server.handle(&create_request(&block(100, 2), 100)));
+
+        // The node keeps using the federation at height 0 because the one at 100 is not activated.
+        if let SigningResponse::Rejected(m) = server.handle(&create_request(&block(100, 2), 0)) {
+            panic!("unexpected rejection: {}", m);
+        }
+
+        // The federation which is not started yet or doesn't exist can't be used.
+        assert_rejected(server.handle(&create_request(&block(12, 3), 100)));
+        assert_rejected(server.handle(&create_request(&block(12, 3), 50)));
+
+        // The federation which doesn't mask the block VSSs can't be used.
+        let plain = Federations::new(vec![federation(0).with_masked_block_vss(false)]);
+        let mut plain_server = SigningServer::new(plain, &path).unwrap();
+        assert_rejected(plain_server.handle(&create_request(&block(12, 3), 0)));
+
+        // The block which changes the aggregated public key to the unknown one is rejected.
+        let mut unknown = block(99, 4);
+        unknown.header.xfield = XField::AggregatePublicKey(TEST_KEYS.pubkeys()[0]);
+        assert_rejected(server.handle(&create_request(&unknown, 0)));
+        let mut known = block(99, 4);
+        known.header.xfield = XField::AggregatePublicKey(TEST_KEYS.aggregated());
+        if let SigningResponse::Rejected(m) = server.handle(&create_request(&known, 0)) {
+            panic!("unexpected rejection: {}", m);
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unix_socket_signing_service() {
+        let path = std::env::temp_dir().join(format!(
+            "tapyrus-signing-service-test-{}.sock",
+            std::process::id()
+        ));
+        let signed_path = std::env::temp_dir().join(format!(
+            "tapyrus-signing-service-test-{}.log",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&signed_path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut server = SigningServer::new(federations(0), &signed_path).unwrap();
+        std::thread::spawn(move || server.serve(listener));
+
+        let service = UnixSocketSigningService::new(path.to_str().unwrap());
+        let federation = federation(0);
+        let block = block(10, 0);
+        let mut block_vss = create_block_vss(&block, &[1, 2]);
+        block_vss.insert(0, service.create_block_vss(&federation, &block).unwrap());
+        let shared_block_secrets = shared_block_secrets(&block_vss, 0);
+
+        let (is_positive, y, local_sig) = service
+            .sign(&federation, &block, &shared_block_secrets)
+            .unwrap();
+        let expected = InProcessSigningService
+            .sign(&federation, &block, &shared_block_secrets)
+            .unwrap();
+        assert_eq!(is_positive, expected.0);
+        assert_eq!(y, expected.1);
+        assert_eq!(local_sig.gamma_i, expected.2.gamma_i);
+        assert_eq!(local_sig.e, expected.2.e);
+
+        match service.sign(&federation, &block, &shared_block_secrets) {
+            Err(Error::SigningService(_)) => {}
+            _ => panic!("should be rejected"),
+        }
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&signed_path).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
 use crate::federation::{Federation, Federations};
+use crate::parameter_schedule::ParameterSchedule;
 use crate::signer_node::NodeParameters;
 use crate::tests::helper::address;
 use crate::tests::helper::keys::TEST_KEYS;
@@ -14,6 +15,7 @@ pub struct NodeParametersBuilder {
     skip_waiting_ibd: bool,
     public_key: PublicKey,
     federations: Federations,
+    parameter_schedule: ParameterSchedule,
 }
 
 impl NodeParametersBuilder {
@@ -33,11 +35,12 @@ impl NodeParametersBuilder {
                 Some(node_vss(0)),
                 TEST_KEYS.aggregated(),
             )]),
+            parameter_schedule: ParameterSchedule::default(),
         }
     }
 
     pub fn build(&mut self) -> NodeParameters<MockRpc> {
-        NodeParameters::new(
+        let mut params = NodeParameters::new(
             self.address.clone(),
             self.public_key,
             self.rpc.take().unwrap_or(MockRpc::new()),
@@ -45,7 +48,9 @@ impl NodeParametersBuilder {
             self.round_limit,
             self.skip_waiting_ibd,
             self.federations.clone(),
-        )
+        );
+        params.set_parameter_schedule(self.parameter_schedule.clone());
+        params
     }
 
     pub fn public_key(&mut self, public_key: PublicKey) -> &mut Self {
@@ -82,4 +87,9 @@ impl NodeParametersBuilder {
         self.federations = federations;
         self
     }
+
+    pub fn parameter_schedule(&mut self, parameter_schedule: ParameterSchedule) -> &mut Self {
+        self.parameter_schedule = parameter_schedule;
+        self
+    }
 }
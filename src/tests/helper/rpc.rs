@@ -1,8 +1,8 @@
 use crate::errors::Error;
-use crate::rpc::{GetBlockchainInfoResult, TapyrusApi};
+use crate::rpc::{GetBlockchainInfoResult, GetMempoolInfoResult, TapyrusApi};
 use std::cell::RefCell;
 use std::collections::VecDeque;
-use tapyrus::blockdata::block::Block;
+use tapyrus::blockdata::block::{Block, BlockHeader};
 use tapyrus::Address;
 
 /// Mock for Rpc
@@ -38,6 +38,8 @@ pub struct MockRpc {
     getblockchaininfo_results: RefCell<VecDeque<GetBlockchainInfoResult>>,
     testproposedblock_results: RefCell<VecDeque<Result<bool, Error>>>,
     submitblock_results: RefCell<VecDeque<Result<(), Error>>>,
+    getblockheader_results: RefCell<VecDeque<Result<BlockHeader, Error>>>,
+    getmempoolinfo_results: RefCell<VecDeque<Result<GetMempoolInfoResult, Error>>>,
 }
 
 impl MockRpc {
@@ -47,6 +49,8 @@ impl MockRpc {
             getblockchaininfo_results: RefCell::new(VecDeque::new()),
             testproposedblock_results: RefCell::new(VecDeque::new()),
             submitblock_results: RefCell::new(VecDeque::new()),
+            getblockheader_results: RefCell::new(VecDeque::new()),
+            getmempoolinfo_results: RefCell::new(VecDeque::new()),
         }
     }
 
@@ -67,6 +71,14 @@ impl MockRpc {
             self.submitblock_results.borrow().is_empty(),
             "submitblock RPC should be called once or more, but not."
         );
+        assert!(
+            self.getblockheader_results.borrow().is_empty(),
+            "getblockheader RPC should be called once or more, but not."
+        );
+        assert!(
+            self.getmempoolinfo_results.borrow().is_empty(),
+            "getmempoolinfo RPC should be called once or more, but not."
+        );
     }
 
     pub fn should_call_getnewblock(&mut self, result: Result<Block, Error>) {
@@ -98,6 +110,16 @@ impl MockRpc {
         list.push_front(result);
     }
 
+    pub fn should_call_getblockheader(&mut self, result: Result<BlockHeader, Error>) {
+        let mut list = self.getblockheader_results.borrow_mut();
+        list.push_front(result);
+    }
+
+    pub fn should_call_getmempoolinfo(&mut self, result: Result<GetMempoolInfoResult, Error>) {
+        let mut list = self.getmempoolinfo_results.borrow_mut();
+        list.push_front(result);
+    }
+
     pub fn should_call_testproposedblock_and_returns_invalid_block_error(&mut self) {
         let err = Error::JsonRpc(jsonrpc::error::Error::Rpc(jsonrpc::error::RpcError {
             code: -25,
@@ -141,4 +163,18 @@ impl TapyrusApi for MockRpc {
             .expect("Unexpected RPC call method=getblockchaininfo");
         Ok(result)
     }
+
+    fn getblockheader(&self, block_height: u64) -> Result<BlockHeader, Error> {
+        let mut list = self.getblockheader_results.borrow_mut();
+        list.pop_back().expect(&format!(
+            "Unexpected RPC call method=getblockheader, args(block_height={})",
+            block_height
+        ))
+    }
+
+    fn getmempoolinfo(&self) -> Result<GetMempoolInfoResult, Error> {
+        let mut list = self.getmempoolinfo_results.borrow_mut();
+        list.pop_back()
+            .expect("Unexpected RPC call method=getmempoolinfo")
+    }
 }